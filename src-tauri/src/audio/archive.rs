@@ -0,0 +1,255 @@
+// Opt-in archive of recorded speech.
+//
+// When enabled, the 16kHz speech buffer of every recording is saved as a
+// 16-bit mono WAV file under `recordings/` in the app data directory, so a
+// bad transcription can be re-run (e.g. with a bigger model) or attached to a
+// bug report. Recordings are named after their creation time in Unix
+// milliseconds, which is also their ID. Retention limits on count, age and
+// total size are applied after every save.
+
+use super::capture::TARGET_SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Recordings directory inside the app data directory.
+const RECORDINGS_DIRNAME: &str = "recordings";
+
+const RECORDING_EXTENSION: &str = "wav";
+
+/// Archive settings. Persisted as part of the user settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArchiveConfig {
+    /// Save every recording (off by default: audio never touches disk).
+    pub enabled: bool,
+    /// Most recordings kept.
+    pub max_recordings: u32,
+    /// Recordings older than this are deleted (days).
+    pub max_age_days: u32,
+    /// Total size of the kept recordings (MB).
+    pub max_size_mb: u32,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_recordings: 100,
+            max_age_days: 30,
+            max_size_mb: 500,
+        }
+    }
+}
+
+impl ArchiveConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=10_000).contains(&self.max_recordings) {
+            return Err("Maximum recordings must be between 1 and 10000".to_string());
+        }
+        if !(1..=3_650).contains(&self.max_age_days) {
+            return Err("Maximum age must be between 1 and 3650 days".to_string());
+        }
+        if !(1..=100_000).contains(&self.max_size_mb) {
+            return Err("Maximum size must be between 1 and 100000 MB".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// An archived recording, as listed in the settings UI.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordingInfo {
+    pub id: String,
+    /// WAV file, for playback.
+    pub path: String,
+    /// Creation time (Unix milliseconds).
+    pub created_ms: u64,
+    pub duration_ms: u64,
+    pub size_bytes: u64,
+}
+
+/// Get the recordings directory, creating it if needed.
+pub fn recordings_dir(app_data_dir: &Path) -> PathBuf {
+    let dir = app_data_dir.join(RECORDINGS_DIRNAME);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// Path of recording `id`. IDs come from the frontend, so anything that is
+/// not a plain recording name is rejected.
+pub fn recording_path(app_data_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if created_ms(id).is_none() {
+        return Err(format!("Invalid recording ID: {}", id));
+    }
+    Ok(recordings_dir(app_data_dir).join(format!("{}.{}", id, RECORDING_EXTENSION)))
+}
+
+/// Creation time encoded in a recording ID ("<ms>" or "<ms>-<n>").
+fn created_ms(id: &str) -> Option<u64> {
+    let (ms, suffix) = match id.split_once('-') {
+        Some((ms, n)) => (ms, Some(n)),
+        None => (id, None),
+    };
+    if let Some(n) = suffix {
+        if n.is_empty() || !n.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+    }
+    if ms.is_empty() || !ms.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    ms.parse().ok()
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Save 16kHz mono `samples` recorded at `now`.
+pub fn save(
+    app_data_dir: &Path,
+    samples: &[f32],
+    now: SystemTime,
+) -> Result<RecordingInfo, String> {
+    let ms = unix_ms(now);
+    let mut id = ms.to_string();
+    let mut n = 1;
+    while recording_path(app_data_dir, &id)?.exists() {
+        n += 1;
+        id = format!("{}-{}", ms, n);
+    }
+    let path = recording_path(app_data_dir, &id)?;
+
+    // Written under a temporary name so a crash never leaves a truncated WAV
+    let partial = path.with_extension("wav.part");
+    write_wav(&partial, samples).map_err(|e| {
+        let _ = fs::remove_file(&partial);
+        format!("Failed to save recording: {}", e)
+    })?;
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to save recording: {}", e))?;
+
+    info(&id, &path).ok_or_else(|| "Failed to save recording".to_string())
+}
+
+fn write_wav(path: &Path, samples: &[f32]) -> Result<(), hound::Error> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for &sample in samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()
+}
+
+/// Read recording `id` back as 16kHz mono samples.
+pub fn load(app_data_dir: &Path, id: &str) -> Result<Vec<f32>, String> {
+    let path = recording_path(app_data_dir, id)?;
+    if !path.exists() {
+        return Err(format!("Recording not found: {}", id));
+    }
+    let reader =
+        hound::WavReader::open(&path).map_err(|e| format!("Failed to open recording: {}", e))?;
+    let spec = reader.spec();
+    if spec.channels != 1 || spec.sample_rate != TARGET_SAMPLE_RATE || spec.bits_per_sample != 16 {
+        return Err(format!("Unexpected recording format: {:?}", spec));
+    }
+    reader
+        .into_samples::<i16>()
+        .map(|s| s.map(|s| s as f32 / i16::MAX as f32))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to read recording: {}", e))
+}
+
+fn info(id: &str, path: &Path) -> Option<RecordingInfo> {
+    let size_bytes = fs::metadata(path).ok()?.len();
+    let samples = hound::WavReader::open(path).ok()?.duration() as u64;
+    Some(RecordingInfo {
+        id: id.to_string(),
+        path: path.to_string_lossy().to_string(),
+        created_ms: created_ms(id)?,
+        duration_ms: samples * 1000 / TARGET_SAMPLE_RATE as u64,
+        size_bytes,
+    })
+}
+
+/// All recordings, newest first. Unreadable files are skipped.
+pub fn list(app_data_dir: &Path) -> Result<Vec<RecordingInfo>, String> {
+    let entries = fs::read_dir(recordings_dir(app_data_dir))
+        .map_err(|e| format!("Failed to read recordings: {}", e))?;
+
+    let mut recordings: Vec<RecordingInfo> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != RECORDING_EXTENSION {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            info(&id, &path)
+        })
+        .collect();
+    recordings.sort_by(|a, b| b.created_ms.cmp(&a.created_ms).then(b.id.cmp(&a.id)));
+    Ok(recordings)
+}
+
+/// Delete recording `id`.
+pub fn delete(app_data_dir: &Path, id: &str) -> Result<(), String> {
+    let path = recording_path(app_data_dir, id)?;
+    if !path.exists() {
+        return Err(format!("Recording not found: {}", id));
+    }
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete recording: {}", e))
+}
+
+/// Delete the recordings beyond the limits of `config`, oldest first.
+/// Returns how many were deleted.
+pub fn apply_retention(
+    app_data_dir: &Path,
+    config: &ArchiveConfig,
+    now: SystemTime,
+) -> Result<usize, String> {
+    retain(app_data_dir, list(app_data_dir)?, config, now)
+}
+
+/// Apply the retention limits to `recordings` (newest first). A recording
+/// deleted since it was listed is skipped.
+fn retain(
+    app_data_dir: &Path,
+    recordings: Vec<RecordingInfo>,
+    config: &ArchiveConfig,
+    now: SystemTime,
+) -> Result<usize, String> {
+    let max_age = Duration::from_secs(config.max_age_days as u64 * 24 * 60 * 60);
+    let oldest_kept = unix_ms(now).saturating_sub(max_age.as_millis() as u64);
+    let max_size = config.max_size_mb as u64 * 1024 * 1024;
+
+    let mut kept_size = 0;
+    let mut deleted = 0;
+    for (index, recording) in recordings.into_iter().enumerate() {
+        kept_size += recording.size_bytes;
+        let keep = index < config.max_recordings as usize
+            && recording.created_ms >= oldest_kept
+            && kept_size <= max_size;
+        if keep {
+            continue;
+        }
+        kept_size -= recording.size_bytes;
+        match fs::remove_file(recording_path(app_data_dir, &recording.id)?) {
+            Ok(()) => deleted += 1,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to delete recording: {}", e)),
+        }
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests;
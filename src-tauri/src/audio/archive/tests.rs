@@ -0,0 +1,214 @@
+use super::*;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("archive")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn at(ms: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(ms)
+}
+
+/// One second of a quiet ramp.
+fn speech() -> Vec<f32> {
+    (0..16_000)
+        .map(|i| (i % 200) as f32 / 1000.0 - 0.1)
+        .collect()
+}
+
+// --------------- ArchiveConfig tests ---------------
+
+#[test]
+fn default_config_is_valid_and_off() {
+    let config = ArchiveConfig::default();
+    assert!(config.validate().is_ok());
+    assert!(!config.enabled);
+}
+
+#[test]
+fn config_rejects_zero_limits() {
+    for config in [
+        ArchiveConfig {
+            max_recordings: 0,
+            ..Default::default()
+        },
+        ArchiveConfig {
+            max_age_days: 0,
+            ..Default::default()
+        },
+        ArchiveConfig {
+            max_size_mb: 0,
+            ..Default::default()
+        },
+    ] {
+        assert!(config.validate().is_err(), "{:?}", config);
+    }
+}
+
+#[test]
+fn config_deserializes_partial_json_with_defaults() {
+    let config: ArchiveConfig = serde_json::from_str(r#"{"enabled":true}"#).unwrap();
+    assert!(config.enabled);
+    assert_eq!(config.max_recordings, 100);
+}
+
+// --------------- Storage tests ---------------
+
+#[test]
+fn save_and_load_round_trip() {
+    let dir = unique_dir("round_trip");
+    let samples = speech();
+    let saved = save(&dir, &samples, at(1_700_000_000_000)).unwrap();
+
+    assert_eq!(saved.id, "1700000000000");
+    assert_eq!(saved.created_ms, 1_700_000_000_000);
+    assert_eq!(saved.duration_ms, 1_000);
+    assert!(saved.path.ends_with("1700000000000.wav"));
+    assert!(Path::new(&saved.path).starts_with(dir.join("recordings")));
+
+    let loaded = load(&dir, &saved.id).unwrap();
+    assert_eq!(loaded.len(), samples.len());
+    for (a, b) in loaded.iter().zip(&samples) {
+        assert!((a - b).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn save_clamps_out_of_range_samples() {
+    let dir = unique_dir("clamp");
+    let saved = save(&dir, &[2.0, -2.0], at(1)).unwrap();
+    let loaded = load(&dir, &saved.id).unwrap();
+    assert!((loaded[0] - 1.0).abs() < 1e-4);
+    assert!((loaded[1] + 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn recordings_in_the_same_millisecond_get_distinct_ids() {
+    let dir = unique_dir("same_ms");
+    let first = save(&dir, &speech(), at(5_000)).unwrap();
+    let second = save(&dir, &speech(), at(5_000)).unwrap();
+    assert_eq!(first.id, "5000");
+    assert_eq!(second.id, "5000-2");
+    assert_eq!(second.created_ms, 5_000);
+}
+
+#[test]
+fn list_is_newest_first_and_skips_other_files() {
+    let dir = unique_dir("list");
+    save(&dir, &speech(), at(1_000)).unwrap();
+    save(&dir, &speech(), at(3_000)).unwrap();
+    save(&dir, &speech(), at(2_000)).unwrap();
+    fs::write(recordings_dir(&dir).join("notes.txt"), "x").unwrap();
+    fs::write(recordings_dir(&dir).join("4000.wav.part"), "x").unwrap();
+
+    let ids: Vec<String> = list(&dir).unwrap().into_iter().map(|r| r.id).collect();
+    assert_eq!(ids, ["3000", "2000", "1000"]);
+}
+
+#[test]
+fn rejects_ids_outside_the_archive() {
+    let dir = unique_dir("ids");
+    for id in [
+        "",
+        "../settings",
+        "..",
+        "12/34",
+        "12-",
+        "-1",
+        "abc",
+        "12.wav",
+    ] {
+        assert!(recording_path(&dir, id).is_err(), "{:?}", id);
+    }
+    assert!(recording_path(&dir, "12-3").is_ok());
+}
+
+#[test]
+fn delete_removes_the_recording() {
+    let dir = unique_dir("delete");
+    let saved = save(&dir, &speech(), at(1_000)).unwrap();
+    delete(&dir, &saved.id).unwrap();
+    assert!(list(&dir).unwrap().is_empty());
+    assert!(delete(&dir, &saved.id).is_err());
+    assert!(load(&dir, &saved.id).is_err());
+}
+
+// --------------- Retention tests ---------------
+
+#[test]
+fn retention_keeps_newest_recordings() {
+    let dir = unique_dir("retention_count");
+    for ms in [1_000, 2_000, 3_000, 4_000] {
+        save(&dir, &speech(), at(ms)).unwrap();
+    }
+    let config = ArchiveConfig {
+        max_recordings: 2,
+        ..Default::default()
+    };
+    assert_eq!(apply_retention(&dir, &config, at(5_000)).unwrap(), 2);
+    let ids: Vec<String> = list(&dir).unwrap().into_iter().map(|r| r.id).collect();
+    assert_eq!(ids, ["4000", "3000"]);
+}
+
+#[test]
+fn retention_drops_old_recordings() {
+    let dir = unique_dir("retention_age");
+    let now = at(100 * DAY.as_millis() as u64);
+    save(&dir, &speech(), now - DAY * 40).unwrap();
+    save(&dir, &speech(), now - DAY * 2).unwrap();
+    let config = ArchiveConfig {
+        max_age_days: 30,
+        ..Default::default()
+    };
+    assert_eq!(apply_retention(&dir, &config, now).unwrap(), 1);
+    assert_eq!(list(&dir).unwrap().len(), 1);
+}
+
+#[test]
+fn retention_skips_recordings_deleted_meanwhile() {
+    let dir = unique_dir("retention_deleted");
+    for ms in [1_000, 2_000, 3_000] {
+        save(&dir, &speech(), at(ms)).unwrap();
+    }
+    let listed = list(&dir).unwrap();
+    delete(&dir, "1000").unwrap();
+
+    let config = ArchiveConfig {
+        max_recordings: 1,
+        ..Default::default()
+    };
+    assert_eq!(retain(&dir, listed, &config, at(4_000)).unwrap(), 1);
+    let ids: Vec<String> = list(&dir).unwrap().into_iter().map(|r| r.id).collect();
+    assert_eq!(ids, ["3000"]);
+}
+
+#[test]
+fn retention_limits_total_size() {
+    let dir = unique_dir("retention_size");
+    // Each 40 s recording is ~1.2 MB, so 1 MB holds none and 3 MB holds two
+    let long: Vec<f32> = speech().repeat(40);
+    for ms in [1_000, 2_000, 3_000] {
+        save(&dir, &long, at(ms)).unwrap();
+    }
+    let config = ArchiveConfig {
+        max_size_mb: 3,
+        ..Default::default()
+    };
+    assert_eq!(apply_retention(&dir, &config, at(4_000)).unwrap(), 1);
+    assert_eq!(list(&dir).unwrap()[1].id, "2000");
+
+    let config = ArchiveConfig {
+        max_size_mb: 1,
+        ..Default::default()
+    };
+    assert_eq!(apply_retention(&dir, &config, at(4_000)).unwrap(), 2);
+    assert!(list(&dir).unwrap().is_empty());
+}
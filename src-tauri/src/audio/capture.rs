@@ -1,15 +1,172 @@
+pub mod resampler;
+
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use resampler::Resampler;
 use serde::Serialize;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
 
 /// Target sample rate for Whisper and Silero VAD.
 pub const TARGET_SAMPLE_RATE: u32 = 16_000;
 
+/// Sample rates reported as supported when a device's ranges cover them.
+const COMMON_SAMPLE_RATES: [u32; 11] = [
+    8_000, 11_025, 16_000, 22_050, 24_000, 32_000, 44_100, 48_000, 88_200, 96_000, 192_000,
+];
+
 #[derive(Debug, Clone, Serialize)]
 pub struct AudioDeviceInfo {
+    /// Stable ID: "<host>:<name>", with "#<n>" appended for the nth device
+    /// of the same name.
     pub id: String,
     pub name: String,
+    /// Audio API the device belongs to (e.g. "WASAPI", "ALSA", "CoreAudio").
+    pub host: String,
     pub is_default: bool,
+    pub capabilities: DeviceCapabilities,
+    /// Format capture opens the device with, if the device reports one.
+    pub default_format: Option<StreamFormat>,
+}
+
+/// What a device can capture.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeviceCapabilities {
+    /// Common sample rates (Hz) within the supported ranges.
+    pub sample_rates: Vec<u32>,
+    pub min_sample_rate: Option<u32>,
+    pub max_sample_rate: Option<u32>,
+    pub channels: Vec<u16>,
+    /// Sample formats, e.g. "f32" or "i16".
+    pub sample_formats: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StreamFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sample_format: String,
+}
+
+fn format_name(format: cpal::SampleFormat) -> String {
+    format!("{:?}", format).to_lowercase()
+}
+
+/// Summarize a device's supported configuration ranges.
+pub fn capabilities(ranges: &[cpal::SupportedStreamConfigRange]) -> DeviceCapabilities {
+    let mut caps = DeviceCapabilities {
+        min_sample_rate: ranges.iter().map(|r| r.min_sample_rate().0).min(),
+        max_sample_rate: ranges.iter().map(|r| r.max_sample_rate().0).max(),
+        ..Default::default()
+    };
+    caps.sample_rates = COMMON_SAMPLE_RATES
+        .into_iter()
+        .filter(|&rate| {
+            ranges
+                .iter()
+                .any(|r| (r.min_sample_rate().0..=r.max_sample_rate().0).contains(&rate))
+        })
+        .collect();
+    for range in ranges {
+        if !caps.channels.contains(&range.channels()) {
+            caps.channels.push(range.channels());
+        }
+        let format = format_name(range.sample_format());
+        if !caps.sample_formats.contains(&format) {
+            caps.sample_formats.push(format);
+        }
+    }
+    caps.channels.sort_unstable();
+    caps
+}
+
+/// Stable IDs for the devices of one host, in enumeration order. Devices
+/// with the same name are told apart by their position among each other.
+pub fn device_ids(host: &str, names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let nth = names[..i].iter().filter(|n| *n == name).count() + 1;
+            if nth == 1 {
+                format!("{}:{}", host, name)
+            } else {
+                format!("{}:{}#{}", host, name, nth)
+            }
+        })
+        .collect()
+}
+
+/// Index of the device `id` selects among `ids` (with their `names`): the
+/// exact ID, else another device with the same host and name (a duplicate
+/// was unplugged), else a device whose name is `id` (IDs saved by earlier
+/// versions were plain names).
+pub fn resolve_device(ids: &[String], names: &[String], id: &str) -> Option<usize> {
+    let base = |id: &str| -> String {
+        match id.rsplit_once('#') {
+            Some((base, n)) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => {
+                base.to_string()
+            }
+            _ => id.to_string(),
+        }
+    };
+    ids.iter()
+        .position(|candidate| candidate == id)
+        .or_else(|| ids.iter().position(|candidate| base(candidate) == base(id)))
+        .or_else(|| names.iter().position(|name| name == id))
+}
+
+/// Index of the system default device among the devices `names`. cpal only
+/// tells devices apart by name, so when several share the default's name
+/// none of them is known to be the default.
+pub fn default_index(names: &[String], default_name: Option<&str>) -> Option<usize> {
+    let default_name = default_name?;
+    let mut matches = names
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| *name == default_name)
+        .map(|(index, _)| index);
+    match (matches.next(), matches.next()) {
+        (Some(index), None) => Some(index),
+        _ => None,
+    }
+}
+
+/// Devices to try for a recording, in order: the selected one, then the
+/// preferred ones. The system default comes after all of them.
+pub fn capture_candidates(selected: Option<&str>, preferred: &[String]) -> Vec<String> {
+    let mut candidates: Vec<String> = Vec::new();
+    for id in selected.into_iter().chain(preferred.iter().map(String::as_str)) {
+        if !candidates.iter().any(|c| c == id) {
+            candidates.push(id.to_string());
+        }
+    }
+    candidates
+}
+
+/// Index of the first of `candidates` that is connected, resolved as by
+/// `resolve_device`.
+pub fn pick_device(ids: &[String], names: &[String], candidates: &[String]) -> Option<usize> {
+    candidates
+        .iter()
+        .find_map(|candidate| resolve_device(ids, names, candidate))
+}
+
+/// All input devices of the default host with their stable IDs and names.
+fn input_devices(host: &cpal::Host) -> Result<Vec<(String, String, cpal::Device)>, String> {
+    let devices: Vec<cpal::Device> = host
+        .input_devices()
+        .map_err(|e| format!("Failed to enumerate input devices: {}", e))?
+        .collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_else(|_| "Unknown".to_string()))
+        .collect();
+    let ids = device_ids(host.id().name(), &names);
+    Ok(ids
+        .into_iter()
+        .zip(names)
+        .zip(devices)
+        .map(|((id, name), device)| (id, name, device))
+        .collect())
 }
 
 /// List all available audio input devices.
@@ -17,39 +174,56 @@ pub fn list_devices() -> Result<Vec<AudioDeviceInfo>, String> {
     let host = cpal::default_host();
     let default_device = host.default_input_device();
     let default_name = default_device.as_ref().and_then(|d| d.name().ok());
-
-    let devices = host
-        .input_devices()
-        .map_err(|e| format!("Failed to enumerate input devices: {}", e))?;
+    let devices = input_devices(&host)?;
+    let names: Vec<String> = devices.iter().map(|(_, name, _)| name.clone()).collect();
+    let default = default_index(&names, default_name.as_deref());
 
     let mut result = Vec::new();
-    for device in devices {
-        let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-        let is_default = default_name.as_deref() == Some(&name);
+    for (index, (id, name, device)) in devices.into_iter().enumerate() {
+        let is_default = default == Some(index);
+        let ranges: Vec<cpal::SupportedStreamConfigRange> = device
+            .supported_input_configs()
+            .map(|configs| configs.collect())
+            .unwrap_or_default();
+        let default_format = device.default_input_config().ok().map(|c| StreamFormat {
+            sample_rate: c.sample_rate().0,
+            channels: c.channels(),
+            sample_format: format_name(c.sample_format()),
+        });
         result.push(AudioDeviceInfo {
-            id: name.clone(),
+            id,
             name,
+            host: host.id().name().to_string(),
             is_default,
+            capabilities: capabilities(&ranges),
+            default_format,
         });
     }
     Ok(result)
 }
 
-/// Get an input device by name, or the system default if `device_id` is None.
+/// IDs of the connected input devices.
+pub fn connected_device_ids() -> Result<Vec<String>, String> {
+    let host = cpal::default_host();
+    Ok(input_devices(&host)?
+        .into_iter()
+        .map(|(id, _, _)| id)
+        .collect())
+}
+
+/// Get an input device by ID, or the system default if `device_id` is None.
 pub fn get_device(device_id: Option<&str>) -> Result<cpal::Device, String> {
     let host = cpal::default_host();
 
     match device_id {
         Some(id) => {
-            let devices = host
-                .input_devices()
-                .map_err(|e| format!("Failed to enumerate devices: {}", e))?;
-            for device in devices {
-                if device.name().ok().as_deref() == Some(id) {
-                    return Ok(device);
-                }
+            let devices = input_devices(&host)?;
+            let ids: Vec<String> = devices.iter().map(|(id, _, _)| id.clone()).collect();
+            let names: Vec<String> = devices.iter().map(|(_, name, _)| name.clone()).collect();
+            match resolve_device(&ids, &names, id) {
+                Some(index) => Ok(devices.into_iter().nth(index).unwrap().2),
+                None => Err(format!("Audio device not found: {}", id)),
             }
-            Err(format!("Audio device not found: {}", id))
         }
         None => host
             .default_input_device()
@@ -57,14 +231,72 @@ pub fn get_device(device_id: Option<&str>) -> Result<cpal::Device, String> {
     }
 }
 
+/// The device a capture stream was opened on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureDevice {
+    pub id: String,
+    pub name: String,
+}
+
 /// Start capturing audio from the specified device.
 /// Sends resampled 16kHz mono f32 chunks through the sender.
 /// Returns the cpal Stream (must be kept alive) and the device's native config.
+/// The sender is dropped when the device goes away.
 pub fn start_capture(
     device_id: Option<&str>,
     sender: mpsc::Sender<Vec<f32>>,
 ) -> Result<(cpal::Stream, cpal::SupportedStreamConfig), String> {
-    let device = get_device(device_id)?;
+    let device = match get_device(device_id) {
+        Ok(device) => device,
+        Err(e) if device_id.is_some() => {
+            log::warn!("{}, using the default input device", e);
+            get_device(None)?
+        }
+        Err(e) => return Err(e),
+    };
+    open_stream(&device, sender)
+}
+
+/// Start capturing from the first connected device of `candidates` (see
+/// `capture_candidates`), or the system default when none is connected.
+/// Also returns the device opened.
+pub fn start_capture_preferred(
+    candidates: &[String],
+    sender: mpsc::Sender<Vec<f32>>,
+) -> Result<(cpal::Stream, cpal::SupportedStreamConfig, CaptureDevice), String> {
+    let host = cpal::default_host();
+    let mut devices = input_devices(&host)?;
+    let ids: Vec<String> = devices.iter().map(|(id, _, _)| id.clone()).collect();
+    let names: Vec<String> = devices.iter().map(|(_, name, _)| name.clone()).collect();
+
+    let (device, opened) = match pick_device(&ids, &names, candidates) {
+        Some(index) => {
+            let (id, name, device) = devices.swap_remove(index);
+            (device, CaptureDevice { id, name })
+        }
+        None => {
+            if !candidates.is_empty() {
+                log::warn!("No preferred input device connected, using the default");
+            }
+            let device = get_device(None)?;
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            // Without a unique match the name is the best ID there is
+            let id = default_index(&names, Some(&name))
+                .map(|index| ids[index].clone())
+                .unwrap_or_else(|| name.clone());
+            (device, CaptureDevice { id, name })
+        }
+    };
+
+    let (stream, config) = open_stream(&device, sender)?;
+    Ok((stream, config, opened))
+}
+
+/// Open and start an input stream on `device` in its default format.
+fn open_stream(
+    device: &cpal::Device,
+    sender: mpsc::Sender<Vec<f32>>,
+) -> Result<(cpal::Stream, cpal::SupportedStreamConfig), String> {
     let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
 
     let config = device
@@ -85,13 +317,13 @@ pub fn start_capture(
 
     let stream = match sample_format {
         cpal::SampleFormat::F32 => {
-            build_stream::<f32>(&device, &config.clone().into(), sample_rate, channels, sender)
+            build_stream::<f32>(device, &config.clone().into(), sample_rate, channels, sender)
         }
         cpal::SampleFormat::I16 => {
-            build_stream::<i16>(&device, &config.clone().into(), sample_rate, channels, sender)
+            build_stream::<i16>(device, &config.clone().into(), sample_rate, channels, sender)
         }
         cpal::SampleFormat::I32 => {
-            build_stream::<i32>(&device, &config.clone().into(), sample_rate, channels, sender)
+            build_stream::<i32>(device, &config.clone().into(), sample_rate, channels, sender)
         }
         other => Err(format!("Unsupported sample format: {:?}", other)),
     }?;
@@ -114,6 +346,12 @@ fn build_stream<T: cpal::SizedSample + Send + 'static>(
 where
     f32: cpal::FromSample<T>,
 {
+    // Filter state carries over between callbacks
+    let mut resampler = Resampler::new(sample_rate, TARGET_SAMPLE_RATE);
+    // Dropped when the device goes away, so the receiver sees the disconnect
+    let sender = Arc::new(Mutex::new(Some(sender)));
+    let error_sender = sender.clone();
+
     let stream = device
         .build_input_stream(
             config,
@@ -128,12 +366,18 @@ where
                 let mono = to_mono(&f32_data, channels);
 
                 // Resample to 16kHz
-                let resampled = resample(&mono, sample_rate, TARGET_SAMPLE_RATE);
+                let resampled = resampler.process(&mono);
 
-                let _ = sender.send(resampled);
+                if let Some(sender) = sender.lock().unwrap().as_ref() {
+                    let _ = sender.send(resampled);
+                }
             },
-            move |err| {
-                log::error!("Audio stream error: {}", err);
+            move |err| match err {
+                cpal::StreamError::DeviceNotAvailable => {
+                    log::warn!("Audio device no longer available");
+                    error_sender.lock().unwrap().take();
+                }
+                err => log::error!("Audio stream error: {}", err),
             },
             None,
         )
@@ -142,28 +386,12 @@ where
     Ok(stream)
 }
 
-/// Resample audio from `src_rate` to `dst_rate` using linear interpolation.
+/// Resample a complete signal from `src_rate` to `dst_rate` with the
+/// band-limited resampler. For streams use a `Resampler` directly.
 pub fn resample(samples: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
-    if src_rate == dst_rate || samples.is_empty() {
-        return samples.to_vec();
-    }
-
-    let ratio = src_rate as f64 / dst_rate as f64;
-    let out_len = (samples.len() as f64 / ratio).ceil() as usize;
-    let mut output = Vec::with_capacity(out_len);
-
-    for i in 0..out_len {
-        let src_idx = i as f64 * ratio;
-        let idx = src_idx as usize;
-        let frac = (src_idx - idx as f64) as f32;
-
-        let sample = if idx + 1 < samples.len() {
-            samples[idx] * (1.0 - frac) + samples[idx + 1] * frac
-        } else {
-            samples[idx.min(samples.len() - 1)]
-        };
-        output.push(sample);
-    }
+    let mut resampler = Resampler::new(src_rate, dst_rate);
+    let mut output = resampler.process(samples);
+    output.extend(resampler.flush());
     output
 }
 
@@ -0,0 +1,199 @@
+// Band-limited sample rate conversion.
+//
+// A polyphase windowed-sinc resampler for the rational ratio between the two
+// rates. The low-pass cutoff sits just below the lower Nyquist frequency, so
+// content above 8 kHz is removed before decimating to 16 kHz instead of
+// aliasing into the speech band. Input history is kept between calls, so a
+// stream fed in callback-sized chunks produces exactly the same output as the
+// whole signal resampled at once.
+
+use std::f64::consts::PI;
+
+/// Zero crossings of the sinc on each side of the centre tap.
+const ZERO_CROSSINGS: usize = 24;
+
+/// Cutoff as a fraction of the lower Nyquist frequency (7.6 kHz at 16 kHz).
+const ROLLOFF: f64 = 0.95;
+
+/// Kaiser window shape; ~90 dB stopband attenuation.
+const KAISER_BETA: f64 = 9.0;
+
+/// Stateful resampler from `src_rate` to `dst_rate`.
+pub struct Resampler {
+    /// Output samples per `down` input samples, after reducing the ratio.
+    up: usize,
+    down: usize,
+    /// Taps of each polyphase branch.
+    taps: usize,
+    /// `up` branches of `taps` coefficients, flattened.
+    coeffs: Vec<f32>,
+    /// Input not fully consumed yet, starting with the oldest tap in use.
+    buffer: Vec<f32>,
+    /// Position of the next output in `buffer`, in units of 1/`up` samples.
+    position: usize,
+    /// Whether the history has been primed with the first input sample.
+    primed: bool,
+}
+
+impl Resampler {
+    pub fn new(src_rate: u32, dst_rate: u32) -> Self {
+        let divisor = gcd(src_rate as usize, dst_rate as usize).max(1);
+        let up = dst_rate as usize / divisor;
+        let down = src_rate as usize / divisor;
+
+        if up == down {
+            return Self {
+                up: 1,
+                down: 1,
+                taps: 0,
+                coeffs: Vec::new(),
+                buffer: Vec::new(),
+                position: 0,
+                primed: true,
+            };
+        }
+
+        // Cutoff in cycles per input sample, and the filter half-length in
+        // input samples that spans ZERO_CROSSINGS zero crossings
+        let cutoff = 0.5 * ROLLOFF * (up as f64 / down as f64).min(1.0);
+        let half_len = ZERO_CROSSINGS as f64 / (2.0 * cutoff);
+        let half_taps = half_len.ceil() as usize;
+        let taps = 2 * half_taps;
+
+        // Branch p computes outputs that fall p/up samples after a tap; tap j
+        // sits (half_taps - 1 - j) + p/up input samples before the output.
+        let mut coeffs = Vec::with_capacity(up * taps);
+        for phase in 0..up {
+            let branch: Vec<f64> = (0..taps)
+                .map(|j| {
+                    let t = (half_taps as f64 - 1.0 - j as f64) + phase as f64 / up as f64;
+                    windowed_sinc(t, cutoff, half_len)
+                })
+                .collect();
+            // Normalize each branch to unity DC gain
+            let sum: f64 = branch.iter().sum();
+            coeffs.extend(branch.iter().map(|c| (c / sum) as f32));
+        }
+
+        Self {
+            up,
+            down,
+            taps,
+            coeffs,
+            buffer: Vec::new(),
+            position: 0,
+            primed: false,
+        }
+    }
+
+    /// Whether input passes through unchanged (equal rates).
+    pub fn is_passthrough(&self) -> bool {
+        self.taps == 0
+    }
+
+    /// Resample the next chunk of a stream. Output lags the input by half the
+    /// filter length (about 1.6 ms at 48 kHz); `flush` drains it at the end.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.is_passthrough() {
+            return input.to_vec();
+        }
+        if input.is_empty() {
+            return Vec::new();
+        }
+
+        // Hold the first sample as history so the stream does not start with a click
+        if !self.primed {
+            self.buffer.resize(self.taps / 2 - 1, input[0]);
+            self.primed = true;
+        }
+        self.buffer.extend_from_slice(input);
+
+        let mut output = Vec::with_capacity(input.len() * self.up / self.down + 1);
+        loop {
+            let start = self.position / self.up;
+            if start + self.taps > self.buffer.len() {
+                break;
+            }
+            let phase = self.position % self.up;
+            let branch = &self.coeffs[phase * self.taps..(phase + 1) * self.taps];
+            let window = &self.buffer[start..start + self.taps];
+            output.push(window.iter().zip(branch).map(|(x, c)| x * c).sum());
+            self.position += self.down;
+        }
+
+        // Drop input no later output will read
+        let consumed = (self.position / self.up).min(self.buffer.len());
+        self.buffer.drain(..consumed);
+        self.position -= consumed * self.up;
+
+        output
+    }
+
+    /// Produce the outputs still held back by the filter delay, holding the
+    /// last sample, and reset for a new stream.
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.is_passthrough() || !self.primed {
+            return Vec::new();
+        }
+        let last = *self.buffer.last().unwrap_or(&0.0);
+
+        // Outputs whose centre lies before the end of the input
+        let end = self.buffer.len() * self.up;
+        let next_centre = self.position + (self.taps / 2 - 1) * self.up;
+        let expected = end.saturating_sub(next_centre).div_ceil(self.down);
+        let mut output = self.process(&vec![last; self.taps / 2]);
+        output.truncate(expected);
+
+        self.reset();
+        output
+    }
+
+    /// Forget all history.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.position = 0;
+        self.primed = self.is_passthrough();
+    }
+}
+
+/// Kaiser-windowed sinc low-pass at `cutoff` cycles/sample, evaluated at `t` samples.
+fn windowed_sinc(t: f64, cutoff: f64, half_len: f64) -> f64 {
+    let ratio = t / half_len;
+    if ratio.abs() >= 1.0 {
+        return 0.0;
+    }
+    let x = 2.0 * cutoff * t;
+    let sinc = if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    };
+    let window = bessel_i0(KAISER_BETA * (1.0 - ratio * ratio).sqrt()) / bessel_i0(KAISER_BETA);
+    2.0 * cutoff * sinc * window
+}
+
+/// Zeroth-order modified Bessel function of the first kind (power series).
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x / 2.0;
+    for k in 1..50 {
+        term *= (half / k as f64) * (half / k as f64);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,227 @@
+use super::*;
+use crate::audio::capture::resample;
+use std::f64::consts::PI;
+
+/// Sum of sines `(frequency Hz, amplitude)` sampled at `rate` for `len` samples.
+fn tones(components: &[(f64, f64)], rate: u32, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| {
+            let t = i as f64 / rate as f64;
+            components
+                .iter()
+                .map(|&(freq, amp)| amp * (2.0 * PI * freq * t).sin())
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
+
+/// Amplitude of the `freq` component of `signal` (single-bin DFT, Hann window).
+fn magnitude(signal: &[f32], freq: f64, rate: u32) -> f64 {
+    let n = signal.len();
+    let (mut re, mut im, mut gain) = (0.0, 0.0, 0.0);
+    for (i, &x) in signal.iter().enumerate() {
+        let window = 0.5 - 0.5 * (2.0 * PI * i as f64 / n as f64).cos();
+        let phase = 2.0 * PI * freq * i as f64 / rate as f64;
+        re += x as f64 * window * phase.cos();
+        im -= x as f64 * window * phase.sin();
+        gain += window;
+    }
+    2.0 * (re * re + im * im).sqrt() / gain
+}
+
+fn db(ratio: f64) -> f64 {
+    20.0 * ratio.max(1e-12).log10()
+}
+
+/// Resample in the irregular chunk sizes audio callbacks deliver.
+fn resample_chunked(input: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
+    let mut resampler = Resampler::new(src_rate, dst_rate);
+    let sizes = [480, 441, 1, 17, 1024, 333, 96, 2048];
+    let mut output = Vec::new();
+    let mut offset = 0;
+    for size in sizes.iter().cycle() {
+        if offset >= input.len() {
+            break;
+        }
+        let end = (offset + size).min(input.len());
+        output.extend(resampler.process(&input[offset..end]));
+        offset = end;
+    }
+    output.extend(resampler.flush());
+    output
+}
+
+// --------------- Spectral tests ---------------
+
+#[test]
+fn passband_matches_reference_spectrum() {
+    // Speech-band tones resampled to 16 kHz against the same tones
+    // synthesized at 16 kHz directly
+    let components = [(300.0, 0.2), (1_000.0, 0.3), (3_000.0, 0.2), (6_500.0, 0.1)];
+    for src_rate in [44_100, 48_000, 22_050, 32_000, 96_000] {
+        let input = tones(&components, src_rate, src_rate as usize);
+        let output = resample(&input, src_rate, 16_000);
+        let reference = tones(&components, 16_000, 16_000);
+        assert_eq!(output.len(), reference.len(), "{} Hz", src_rate);
+
+        let mut freq = 100.0;
+        while freq < 7_000.0 {
+            let actual = magnitude(&output, freq, 16_000);
+            let expected = magnitude(&reference, freq, 16_000);
+            assert!(
+                (actual - expected).abs() < 0.005,
+                "{} Hz input, {} Hz bin: {} vs reference {}",
+                src_rate,
+                freq,
+                actual,
+                expected
+            );
+            freq += 100.0;
+        }
+    }
+}
+
+#[test]
+fn passband_matches_reference_waveform() {
+    let components = [(440.0, 0.5), (2_500.0, 0.25)];
+    let input = tones(&components, 48_000, 48_000);
+    let output = resample(&input, 48_000, 16_000);
+    let reference = tones(&components, 16_000, 16_000);
+    // Away from the edges the output is the band-limited signal itself
+    for i in 200..output.len() - 200 {
+        assert!(
+            (output[i] - reference[i]).abs() < 1e-3,
+            "sample {}: {} vs {}",
+            i,
+            output[i],
+            reference[i]
+        );
+    }
+}
+
+#[test]
+fn content_above_nyquist_does_not_alias() {
+    // Each tone would fold back into the speech band without filtering
+    for (src_rate, freq, alias) in [
+        (48_000, 12_000.0, 4_000.0),
+        (48_000, 13_000.0, 3_000.0),
+        (48_000, 21_000.0, 5_000.0),
+        (44_100, 10_500.0, 5_500.0),
+        (44_100, 15_000.0, 1_000.0),
+    ] {
+        let input = tones(&[(freq, 0.5)], src_rate, src_rate as usize);
+        let output = resample(&input, src_rate, 16_000);
+        let level = db(magnitude(&output, alias, 16_000) / 0.5);
+        assert!(
+            level < -70.0,
+            "{} Hz at {} Hz aliased to {} Hz at {:.1} dB",
+            freq,
+            src_rate,
+            alias,
+            level
+        );
+    }
+}
+
+#[test]
+fn mixed_signal_keeps_speech_and_drops_aliases() {
+    let speech = [(500.0, 0.3), (1_700.0, 0.2), (4_200.0, 0.1)];
+    let mut components = speech.to_vec();
+    components.extend([(9_000.0, 0.3), (13_000.0, 0.3), (20_000.0, 0.3)]);
+
+    let input = tones(&components, 48_000, 48_000);
+    let output = resample(&input, 48_000, 16_000);
+    let reference = tones(&speech, 16_000, 16_000);
+
+    for freq in [500.0, 1_700.0, 4_200.0, 7_000.0, 3_000.0, 4_000.0] {
+        let actual = magnitude(&output, freq, 16_000);
+        let expected = magnitude(&reference, freq, 16_000);
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{} Hz: {} vs reference {}",
+            freq,
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn upsampling_does_not_create_images() {
+    let input = tones(&[(1_000.0, 0.5)], 16_000, 16_000);
+    let output = resample(&input, 16_000, 48_000);
+    assert_eq!(output.len(), 48_000);
+    assert!((magnitude(&output, 1_000.0, 48_000) - 0.5).abs() < 0.005);
+    // The first image of a 1 kHz tone sampled at 16 kHz is at 15 kHz
+    let image = db(magnitude(&output, 15_000.0, 48_000) / 0.5);
+    assert!(image < -70.0, "image at {:.1} dB", image);
+}
+
+// --------------- Streaming tests ---------------
+
+#[test]
+fn chunked_stream_matches_one_shot() {
+    let input = tones(
+        &[(300.0, 0.4), (3_100.0, 0.2), (11_000.0, 0.2)],
+        44_100,
+        44_100,
+    );
+    for (src_rate, dst_rate) in [(44_100, 16_000), (48_000, 16_000), (16_000, 48_000)] {
+        let whole = resample(&input, src_rate, dst_rate);
+        let chunked = resample_chunked(&input, src_rate, dst_rate);
+        assert_eq!(whole.len(), chunked.len());
+        for (i, (a, b)) in whole.iter().zip(&chunked).enumerate() {
+            assert!((a - b).abs() < 1e-6, "sample {}: {} vs {}", i, a, b);
+        }
+    }
+}
+
+#[test]
+fn chunk_boundaries_are_seamless() {
+    // 10 ms callbacks of a 48 kHz tone: the output has no steps at the seams
+    let input = tones(&[(1_000.0, 0.5)], 48_000, 48_000);
+    let output = resample_chunked(&input, 48_000, 16_000);
+    let reference = tones(&[(1_000.0, 0.5)], 16_000, 16_000);
+    for i in 100..output.len() - 100 {
+        assert!((output[i] - reference[i]).abs() < 1e-3, "sample {}", i);
+    }
+}
+
+#[test]
+fn stream_output_rate_matches_ratio() {
+    let mut resampler = Resampler::new(44_100, 16_000);
+    let mut produced = 0;
+    for _ in 0..1_000 {
+        produced += resampler.process(&[0.1; 441]).len();
+    }
+    // 441 000 input samples make 160 000 outputs, minus the filter delay
+    assert!(produced <= 160_000 && produced > 159_900, "{}", produced);
+    produced += resampler.flush().len();
+    assert_eq!(produced, 160_000);
+}
+
+#[test]
+fn flush_resets_for_a_new_stream() {
+    let input = tones(&[(700.0, 0.5)], 48_000, 4_800);
+    let mut resampler = Resampler::new(48_000, 16_000);
+    let mut first = resampler.process(&input);
+    first.extend(resampler.flush());
+    let mut second = resampler.process(&input);
+    second.extend(resampler.flush());
+    assert_eq!(first, second);
+}
+
+#[test]
+fn equal_rates_pass_through() {
+    let mut resampler = Resampler::new(16_000, 16_000);
+    assert!(resampler.is_passthrough());
+    assert_eq!(resampler.process(&[0.1, 0.2]), vec![0.1, 0.2]);
+    assert!(resampler.flush().is_empty());
+}
+
+#[test]
+fn empty_chunks_produce_nothing() {
+    let mut resampler = Resampler::new(48_000, 16_000);
+    assert!(resampler.process(&[]).is_empty());
+    assert!(resampler.flush().is_empty());
+}
@@ -137,3 +137,149 @@ fn get_device_nonexistent_returns_error() {
     let err = result.err().unwrap();
     assert!(err.contains("not found"), "Expected 'not found' in error: {}", err);
 }
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn device_ids_include_host_and_name() {
+    let ids = device_ids("WASAPI", &strings(&["Microphone", "Line In"]));
+    assert_eq!(ids, strings(&["WASAPI:Microphone", "WASAPI:Line In"]));
+}
+
+#[test]
+fn device_ids_number_duplicate_names() {
+    let names = strings(&["USB Headset", "Microphone", "USB Headset", "USB Headset"]);
+    let ids = device_ids("ALSA", &names);
+    assert_eq!(
+        ids,
+        strings(&[
+            "ALSA:USB Headset",
+            "ALSA:Microphone",
+            "ALSA:USB Headset#2",
+            "ALSA:USB Headset#3",
+        ])
+    );
+}
+
+#[test]
+fn resolve_device_exact_id() {
+    let names = strings(&["USB Headset", "USB Headset"]);
+    let ids = device_ids("ALSA", &names);
+    assert_eq!(resolve_device(&ids, &names, "ALSA:USB Headset#2"), Some(1));
+    assert_eq!(resolve_device(&ids, &names, "ALSA:USB Headset"), Some(0));
+}
+
+#[test]
+fn resolve_device_falls_back_to_same_name() {
+    // The second headset is now the only one
+    let names = strings(&["Microphone", "USB Headset"]);
+    let ids = device_ids("ALSA", &names);
+    assert_eq!(resolve_device(&ids, &names, "ALSA:USB Headset#2"), Some(1));
+}
+
+#[test]
+fn resolve_device_accepts_legacy_name_ids() {
+    let names = strings(&["Microphone", "USB Headset"]);
+    let ids = device_ids("WASAPI", &names);
+    assert_eq!(resolve_device(&ids, &names, "USB Headset"), Some(1));
+}
+
+#[test]
+fn resolve_device_gone_returns_none() {
+    let names = strings(&["Microphone"]);
+    let ids = device_ids("WASAPI", &names);
+    assert_eq!(resolve_device(&ids, &names, "WASAPI:USB Headset"), None);
+    // Same name on another host is a different device
+    assert_eq!(resolve_device(&ids, &names, "ASIO:Microphone"), None);
+    assert_eq!(resolve_device(&[], &[], "WASAPI:Microphone"), None);
+}
+
+#[test]
+fn default_index_finds_the_default_by_name() {
+    let names = strings(&["Headset", "Webcam"]);
+    assert_eq!(default_index(&names, Some("Webcam")), Some(1));
+    assert_eq!(default_index(&names, Some("Speakerphone")), None);
+    assert_eq!(default_index(&names, None), None);
+}
+
+#[test]
+fn default_index_is_unknown_among_same_named_devices() {
+    let names = strings(&["Headset", "Webcam", "Headset"]);
+    assert_eq!(default_index(&names, Some("Headset")), None);
+    assert_eq!(default_index(&names, Some("Webcam")), Some(1));
+}
+
+#[test]
+fn capture_candidates_put_selected_first() {
+    let preferred = strings(&["ALSA:USB Mic", "ALSA:Headset"]);
+    assert_eq!(
+        capture_candidates(Some("ALSA:Webcam"), &preferred),
+        strings(&["ALSA:Webcam", "ALSA:USB Mic", "ALSA:Headset"])
+    );
+    // A selected device that is also preferred is tried once
+    assert_eq!(
+        capture_candidates(Some("ALSA:Headset"), &preferred),
+        strings(&["ALSA:Headset", "ALSA:USB Mic"])
+    );
+    assert!(capture_candidates(None, &[]).is_empty());
+}
+
+#[test]
+fn pick_device_takes_first_connected_candidate() {
+    let names = strings(&["Microphone", "Headset"]);
+    let ids = device_ids("ALSA", &names);
+    let candidates = strings(&["ALSA:USB Mic", "ALSA:Headset", "ALSA:Microphone"]);
+    assert_eq!(pick_device(&ids, &names, &candidates), Some(1));
+}
+
+#[test]
+fn pick_device_none_connected() {
+    let names = strings(&["Microphone"]);
+    let ids = device_ids("ALSA", &names);
+    assert_eq!(pick_device(&ids, &names, &strings(&["ALSA:USB Mic"])), None);
+    assert_eq!(pick_device(&ids, &names, &[]), None);
+}
+
+#[test]
+fn connected_device_ids_returns_ok() {
+    assert!(connected_device_ids().is_ok());
+}
+
+fn range(
+    channels: u16,
+    min: u32,
+    max: u32,
+    format: cpal::SampleFormat,
+) -> cpal::SupportedStreamConfigRange {
+    cpal::SupportedStreamConfigRange::new(
+        channels,
+        cpal::SampleRate(min),
+        cpal::SampleRate(max),
+        cpal::SupportedBufferSize::Unknown,
+        format,
+    )
+}
+
+#[test]
+fn capabilities_summarize_ranges() {
+    let caps = capabilities(&[
+        range(2, 44_100, 48_000, cpal::SampleFormat::F32),
+        range(1, 8_000, 16_000, cpal::SampleFormat::I16),
+        range(2, 96_000, 96_000, cpal::SampleFormat::I16),
+    ]);
+    assert_eq!(
+        caps.sample_rates,
+        vec![8_000, 11_025, 16_000, 44_100, 48_000, 96_000]
+    );
+    assert_eq!(caps.min_sample_rate, Some(8_000));
+    assert_eq!(caps.max_sample_rate, Some(96_000));
+    assert_eq!(caps.channels, vec![1, 2]);
+    assert_eq!(caps.sample_formats, strings(&["f32", "i16"]));
+}
+
+#[test]
+fn capabilities_of_no_ranges_are_empty() {
+    assert_eq!(capabilities(&[]), DeviceCapabilities::default());
+}
@@ -0,0 +1,123 @@
+// Audio file decoding.
+//
+// Files are decoded packet by packet with symphonia and converted on the fly
+// to the 16kHz mono the recognizer expects, with the same downmix and
+// resampler as live capture. Only the converted signal is kept in memory.
+
+use super::capture::resampler::Resampler;
+use super::capture::{to_mono, TARGET_SAMPLE_RATE};
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// File extensions accepted for transcription.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "flac", "mp3", "ogg", "oga"];
+
+/// Longest file decoded (2 hours, ~460 MB of 16kHz samples).
+const MAX_DURATION_SECS: usize = 2 * 60 * 60;
+
+/// A decoded file, converted to 16kHz mono.
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    /// Format of the file.
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// Whether `path` has one of the `SUPPORTED_EXTENSIONS`.
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Decode the audio file at `path` to 16kHz mono.
+pub fn decode_file(path: &Path) -> Result<DecodedAudio, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open audio file: {}", e))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("Unrecognized audio file: {}", e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "No audio track in file".to_string())?;
+    let track_id = track.id;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| "Unknown sample rate".to_string())?;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported audio codec: {}", e))?;
+
+    let mut resampler = Resampler::new(sample_rate, TARGET_SAMPLE_RATE);
+    let mut samples: Vec<f32> = Vec::new();
+    let mut channels = 0;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(format!("Failed to read audio file: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                // A corrupt frame costs a few milliseconds, not the file
+                log::warn!("Skipping undecodable audio packet: {}", e);
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to decode audio file: {}", e)),
+        };
+
+        let spec = *decoded.spec();
+        channels = spec.channels.count() as u16;
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+
+        samples.extend(resampler.process(&to_mono(buffer.samples(), channels)));
+        if samples.len() > MAX_DURATION_SECS * TARGET_SAMPLE_RATE as usize {
+            return Err(format!(
+                "Audio file is longer than {} hours",
+                MAX_DURATION_SECS / 3600
+            ));
+        }
+    }
+    samples.extend(resampler.flush());
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+        channels,
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,101 @@
+use super::*;
+use crate::audio::capture::compute_rms;
+use std::fs;
+use std::path::PathBuf;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("decode")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// 440 Hz sine of `amplitude` at `rate`.
+fn tone(amplitude: f32, rate: u32, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin())
+        .collect()
+}
+
+/// Write 16-bit PCM WAV with `frames[i]` holding one sample per channel.
+fn write_wav(path: &Path, rate: u32, frames: &[Vec<f32>]) {
+    let spec = hound::WavSpec {
+        channels: frames[0].len() as u16,
+        sample_rate: rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    for frame in frames {
+        for &sample in frame {
+            writer
+                .write_sample((sample * i16::MAX as f32) as i16)
+                .unwrap();
+        }
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn supported_extensions() {
+    assert!(is_supported(Path::new("memo.wav")));
+    assert!(is_supported(Path::new("memo.FLAC")));
+    assert!(is_supported(Path::new("/tmp/voice memo.mp3")));
+    assert!(is_supported(Path::new("memo.ogg")));
+    assert!(!is_supported(Path::new("memo.m4a")));
+    assert!(!is_supported(Path::new("memo")));
+}
+
+#[test]
+fn decodes_16k_mono_unchanged() {
+    let dir = unique_dir("mono_16k");
+    let path = dir.join("memo.wav");
+    let input = tone(0.5, 16_000, 16_000);
+    let frames: Vec<Vec<f32>> = input.iter().map(|&s| vec![s]).collect();
+    write_wav(&path, 16_000, &frames);
+
+    let decoded = decode_file(&path).unwrap();
+    assert_eq!(decoded.sample_rate, 16_000);
+    assert_eq!(decoded.channels, 1);
+    assert_eq!(decoded.samples.len(), input.len());
+    for (a, b) in decoded.samples.iter().zip(&input) {
+        assert!((a - b).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn downmixes_and_resamples_stereo_44k() {
+    let dir = unique_dir("stereo_44k");
+    let path = dir.join("memo.wav");
+    // Tone on the left channel only: the mono mix has half its amplitude
+    let left = tone(0.8, 44_100, 88_200);
+    let frames: Vec<Vec<f32>> = left.iter().map(|&s| vec![s, 0.0]).collect();
+    write_wav(&path, 44_100, &frames);
+
+    let decoded = decode_file(&path).unwrap();
+    assert_eq!(decoded.sample_rate, 44_100);
+    assert_eq!(decoded.channels, 2);
+    assert!((decoded.samples.len() as i64 - 32_000).abs() <= 1);
+    let rms = compute_rms(&decoded.samples[1_000..31_000]);
+    let expected = 0.4 / 2f32.sqrt();
+    assert!((rms - expected).abs() < 0.01, "rms {} vs {}", rms, expected);
+}
+
+#[test]
+fn missing_file_is_an_error() {
+    let dir = unique_dir("missing");
+    let err = decode_file(&dir.join("nope.wav")).err().unwrap();
+    assert!(err.starts_with("Failed to open audio file"), "{}", err);
+}
+
+#[test]
+fn non_audio_file_is_an_error() {
+    let dir = unique_dir("not_audio");
+    let path = dir.join("notes.mp3");
+    fs::write(&path, "these are not the samples you are looking for").unwrap();
+    assert!(decode_file(&path).is_err());
+}
@@ -0,0 +1,332 @@
+// Microphone test.
+//
+// Opens a device the same way a recording does and reports what the
+// microphone delivers: level, peak and clipping, the device format and the
+// sample rate actually measured. The noise floor, speech level and the VAD
+// thresholds suited to the room are measured after the recording's
+// preprocessing, on the audio the VAD sees.
+
+use super::capture::{self, compute_rms, TARGET_SAMPLE_RATE};
+use super::preprocess::{PreprocessConfig, Preprocessor};
+use super::vad::energy::energy_db;
+use super::vad::FRAME_SIZE;
+use super::AudioLevelEvent;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Samples at or above this magnitude count as clipped.
+pub const CLIP_LEVEL: f32 = 0.99;
+
+/// Frame energies kept for the noise floor and speech level (~30 s).
+const MAX_FRAMES: usize = 30_000 / 32;
+
+/// Percentiles of the frame energies taken as noise floor and speech level.
+const NOISE_PERCENTILE: f32 = 0.1;
+const SPEECH_PERCENTILE: f32 = 0.95;
+
+/// The test ends by itself after this long.
+const MAX_DURATION: Duration = Duration::from_secs(60);
+
+/// Level (~30fps) and statistics (~5fps) event intervals.
+const LEVEL_INTERVAL: Duration = Duration::from_millis(33);
+const STATS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Recommended VAD start threshold for quiet rooms and for noisy ones.
+const QUIET_THRESHOLD: f32 = 0.4;
+const NOISY_THRESHOLD: f32 = 0.7;
+
+/// Noise floors (dBFS) mapped to the quiet and noisy thresholds.
+const QUIET_FLOOR_DB: f32 = -60.0;
+const NOISY_FLOOR_DB: f32 = -35.0;
+
+/// Stop threshold as a fraction of the start threshold (as in the defaults).
+const STOP_THRESHOLD_RATIO: f32 = 0.7;
+
+/// Running level statistics of 16kHz mono audio.
+#[derive(Default)]
+pub struct LevelStats {
+    /// Energies (dBFS) of the most recent VAD-sized frames.
+    frame_energies: Vec<f32>,
+    /// Samples not yet making up a whole frame.
+    pending: Vec<f32>,
+    samples: u64,
+    peak: f32,
+    clipped_samples: u64,
+}
+
+impl LevelStats {
+    pub fn new() -> Self {
+        Self {
+            frame_energies: Vec::new(),
+            pending: Vec::new(),
+            samples: 0,
+            peak: 0.0,
+            clipped_samples: 0,
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[f32]) {
+        self.samples += chunk.len() as u64;
+        for &sample in chunk {
+            self.peak = self.peak.max(sample.abs());
+            if sample.abs() >= CLIP_LEVEL {
+                self.clipped_samples += 1;
+            }
+        }
+
+        self.pending.extend_from_slice(chunk);
+        while self.pending.len() >= FRAME_SIZE {
+            let frame: Vec<f32> = self.pending.drain(..FRAME_SIZE).collect();
+            if self.frame_energies.len() == MAX_FRAMES {
+                self.frame_energies.remove(0);
+            }
+            self.frame_energies.push(energy_db(&frame));
+        }
+    }
+
+    /// Samples seen so far.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Largest sample magnitude.
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    pub fn clipped_samples(&self) -> u64 {
+        self.clipped_samples
+    }
+
+    /// Background level (dBFS): the quieter frames.
+    pub fn noise_floor_db(&self) -> Option<f32> {
+        self.percentile(NOISE_PERCENTILE)
+    }
+
+    /// Speaking level (dBFS): the louder frames.
+    pub fn speech_level_db(&self) -> Option<f32> {
+        self.percentile(SPEECH_PERCENTILE)
+    }
+
+    fn percentile(&self, fraction: f32) -> Option<f32> {
+        if self.frame_energies.is_empty() {
+            return None;
+        }
+        let mut sorted = self.frame_energies.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let index = ((sorted.len() - 1) as f32 * fraction).round() as usize;
+        Some(sorted[index])
+    }
+}
+
+/// VAD start and stop thresholds for a room with the given noise floor.
+/// Noisier rooms need a more confident detector to avoid false starts.
+pub fn recommended_thresholds(noise_floor_db: f32) -> (f32, f32) {
+    let position =
+        ((noise_floor_db - QUIET_FLOOR_DB) / (NOISY_FLOOR_DB - QUIET_FLOOR_DB)).clamp(0.0, 1.0);
+    let start = QUIET_THRESHOLD + (NOISY_THRESHOLD - QUIET_THRESHOLD) * position;
+    // Two decimals, as shown in the settings
+    let start = (start * 100.0).round() / 100.0;
+    let stop = (start * STOP_THRESHOLD_RATIO * 100.0).round() / 100.0;
+    (start, stop)
+}
+
+/// Microphone test results, sent as `audio://mic-test` events while the test
+/// runs and returned by `stop_mic_test`. A test ending by itself (time limit,
+/// device lost) sends its final report as `audio://mic-test-ended`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MicTestReport {
+    /// Device format capture opened.
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Device sample rate derived from the audio received (None in the first second).
+    pub measured_sample_rate: Option<u32>,
+    pub duration_ms: u64,
+    /// Current level (dBFS RMS). Levels are floored at -100 dBFS.
+    pub level_db: f32,
+    pub peak: f32,
+    pub peak_db: f32,
+    pub clipped_samples: u64,
+    pub clipping: bool,
+    pub noise_floor_db: Option<f32>,
+    pub speech_level_db: Option<f32>,
+    /// Speech level above the noise floor (dB).
+    pub snr_db: Option<f32>,
+    pub recommended_start_threshold: Option<f32>,
+    pub recommended_stop_threshold: Option<f32>,
+}
+
+/// Build a report from the statistics so far: `stats` of the raw microphone
+/// audio, `vad_stats` of the preprocessed audio the VAD sees.
+pub fn report(
+    stats: &LevelStats,
+    vad_stats: &LevelStats,
+    sample_rate: u32,
+    channels: u16,
+    elapsed: Duration,
+    level_db: f32,
+) -> MicTestReport {
+    let measured_sample_rate = (elapsed >= Duration::from_secs(1)).then(|| {
+        let received_rate = stats.samples() as f64 / elapsed.as_secs_f64();
+        (received_rate * sample_rate as f64 / TARGET_SAMPLE_RATE as f64).round() as u32
+    });
+    let noise_floor_db = vad_stats.noise_floor_db();
+    let speech_level_db = vad_stats.speech_level_db();
+    let thresholds = noise_floor_db.map(recommended_thresholds);
+
+    MicTestReport {
+        sample_rate,
+        channels,
+        measured_sample_rate,
+        duration_ms: elapsed.as_millis() as u64,
+        level_db,
+        peak: stats.peak(),
+        peak_db: energy_db(&[stats.peak()]),
+        clipped_samples: stats.clipped_samples(),
+        clipping: stats.clipped_samples() > 0,
+        noise_floor_db,
+        speech_level_db,
+        snr_db: noise_floor_db
+            .zip(speech_level_db)
+            .map(|(noise, speech)| speech - noise),
+        recommended_start_threshold: thresholds.map(|t| t.0),
+        recommended_stop_threshold: thresholds.map(|t| t.1),
+    }
+}
+
+/// A running microphone test.
+pub struct MicTest {
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<MicTestReport>,
+}
+
+/// Open `device_id` (None = system default) and start measuring. Returns
+/// once the device is open, or with the error opening it. `on_end` gets the
+/// final report when the test ends without `stop`.
+pub fn start<R: Runtime>(
+    app: &AppHandle<R>,
+    device_id: Option<&str>,
+    preprocess_config: &PreprocessConfig,
+    on_end: impl FnOnce(MicTestReport) + Send + 'static,
+) -> Result<MicTest, String> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let (opened_tx, opened_rx) = mpsc::channel::<Result<(), String>>();
+    let app = app.clone();
+    let device_id = device_id.map(str::to_string);
+    let preprocessor = Preprocessor::new(preprocess_config);
+    let stop_flag = stop.clone();
+
+    // The stream lives on the test thread: cpal streams are not Send everywhere
+    let handle = thread::spawn(move || {
+        let (tx, rx) = mpsc::channel::<Vec<f32>>();
+        let (_stream, config) = match capture::start_capture(device_id.as_deref(), tx) {
+            Ok(opened) => opened,
+            Err(e) => {
+                let _ = opened_tx.send(Err(e));
+                let empty = LevelStats::new();
+                return report(&empty, &empty, 0, 0, Duration::ZERO, energy_db(&[]));
+            }
+        };
+        let _ = opened_tx.send(Ok(()));
+        let final_report = run(
+            &app,
+            rx,
+            preprocessor,
+            config.sample_rate().0,
+            config.channels(),
+            &stop_flag,
+        );
+        if !stop_flag.load(Ordering::Relaxed) {
+            on_end(final_report.clone());
+        }
+        final_report
+    });
+
+    match opened_rx.recv() {
+        Ok(Ok(())) => Ok(MicTest { stop, handle }),
+        Ok(Err(e)) => {
+            let _ = handle.join();
+            Err(e)
+        }
+        Err(_) => Err("Microphone test thread exited".to_string()),
+    }
+}
+
+/// End the test and return the final report.
+pub fn stop(test: MicTest) -> Result<MicTestReport, String> {
+    test.stop.store(true, Ordering::Relaxed);
+    test.handle
+        .join()
+        .map_err(|_| "Microphone test thread panicked".to_string())
+}
+
+fn run<R: Runtime>(
+    app: &AppHandle<R>,
+    rx: mpsc::Receiver<Vec<f32>>,
+    mut preprocessor: Preprocessor,
+    sample_rate: u32,
+    channels: u16,
+    stop: &AtomicBool,
+) -> MicTestReport {
+    let started = Instant::now();
+    let mut stats = LevelStats::new();
+    let mut vad_stats = LevelStats::new();
+    let mut level_samples: Vec<f32> = Vec::new();
+    let mut level_db = energy_db(&[]);
+    let mut last_level = Instant::now();
+    let mut last_stats = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) && started.elapsed() < MAX_DURATION {
+        match rx.recv_timeout(Duration::from_millis(50)) {
+            Ok(chunk) => {
+                stats.push(&chunk);
+                vad_stats.push(&preprocessor.process(&chunk));
+                level_samples.extend_from_slice(&chunk);
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                log::warn!("Microphone test stream disconnected");
+                let _ = app.emit(
+                    "audio://error",
+                    super::AudioErrorEvent {
+                        message: "Microphone disconnected".to_string(),
+                    },
+                );
+                break;
+            }
+        }
+
+        if last_level.elapsed() >= LEVEL_INTERVAL && !level_samples.is_empty() {
+            let rms = compute_rms(&level_samples);
+            level_db = energy_db(&level_samples);
+            let _ = app.emit("audio://level", AudioLevelEvent { rms });
+            level_samples.clear();
+            last_level = Instant::now();
+        }
+        if last_stats.elapsed() >= STATS_INTERVAL {
+            let elapsed = started.elapsed();
+            let snapshot = report(&stats, &vad_stats, sample_rate, channels, elapsed, level_db);
+            let _ = app.emit("audio://mic-test", snapshot);
+            last_stats = Instant::now();
+        }
+    }
+
+    let elapsed = started.elapsed();
+    let final_report = report(&stats, &vad_stats, sample_rate, channels, elapsed, level_db);
+    log::info!(
+        "Microphone test: {}Hz {}ch, noise floor {:?} dBFS, speech {:?} dBFS, {} clipped samples",
+        sample_rate,
+        channels,
+        final_report.noise_floor_db,
+        final_report.speech_level_db,
+        final_report.clipped_samples
+    );
+    final_report
+}
+
+#[cfg(test)]
+mod tests;
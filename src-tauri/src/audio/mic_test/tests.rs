@@ -0,0 +1,155 @@
+use super::*;
+
+fn tone(amplitude: f32, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| amplitude * (2.0 * std::f32::consts::PI * 300.0 * i as f32 / 16_000.0).sin())
+        .collect()
+}
+
+// --------------- LevelStats tests ---------------
+
+#[test]
+fn empty_stats_have_no_levels() {
+    let stats = LevelStats::new();
+    assert_eq!(stats.samples(), 0);
+    assert_eq!(stats.peak(), 0.0);
+    assert_eq!(stats.noise_floor_db(), None);
+    assert_eq!(stats.speech_level_db(), None);
+}
+
+#[test]
+fn tracks_peak_across_chunks() {
+    let mut stats = LevelStats::new();
+    stats.push(&[0.1, -0.3, 0.2]);
+    stats.push(&[-0.6, 0.5]);
+    assert_eq!(stats.peak(), 0.6);
+    assert_eq!(stats.samples(), 5);
+    assert_eq!(stats.clipped_samples(), 0);
+}
+
+#[test]
+fn counts_clipped_samples() {
+    let mut stats = LevelStats::new();
+    stats.push(&[0.5, 1.0, -1.0, 0.995, 0.98]);
+    assert_eq!(stats.clipped_samples(), 3);
+}
+
+#[test]
+fn noise_floor_and_speech_level_from_mixed_audio() {
+    let mut stats = LevelStats::new();
+    // Two seconds of room tone (-63 dBFS) around one second of speech (-23 dBFS)
+    stats.push(&tone(0.001, 16_000));
+    stats.push(&tone(0.1, 16_000));
+    stats.push(&tone(0.001, 16_000));
+
+    let noise = stats.noise_floor_db().unwrap();
+    let speech = stats.speech_level_db().unwrap();
+    assert!((noise + 63.0).abs() < 1.0, "noise floor {}", noise);
+    assert!((speech + 23.0).abs() < 1.0, "speech level {}", speech);
+}
+
+#[test]
+fn keeps_only_recent_frames() {
+    let mut stats = LevelStats::new();
+    // A loud minute followed by a quiet one: only the quiet one counts
+    stats.push(&tone(0.1, 16_000 * 40));
+    stats.push(&tone(0.001, 16_000 * 40));
+    assert!(stats.speech_level_db().unwrap() < -60.0);
+}
+
+// --------------- Threshold recommendation tests ---------------
+
+#[test]
+fn quiet_room_gets_sensitive_threshold() {
+    assert_eq!(recommended_thresholds(-80.0), (QUIET_THRESHOLD, 0.28));
+    assert_eq!(recommended_thresholds(QUIET_FLOOR_DB).0, QUIET_THRESHOLD);
+}
+
+#[test]
+fn noisy_room_gets_strict_threshold() {
+    assert_eq!(recommended_thresholds(-20.0).0, NOISY_THRESHOLD);
+    assert_eq!(recommended_thresholds(NOISY_FLOOR_DB).0, NOISY_THRESHOLD);
+}
+
+#[test]
+fn thresholds_rise_with_noise_and_keep_hysteresis() {
+    let mut previous = 0.0;
+    for floor in (-70..=-30).step_by(5) {
+        let (start, stop) = recommended_thresholds(floor as f32);
+        assert!(start >= previous, "{} dB: {}", floor, start);
+        assert!(stop < start);
+        let config = crate::audio::vad::VadConfig {
+            start_threshold: start,
+            stop_threshold: stop,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        previous = start;
+    }
+}
+
+// --------------- Report tests ---------------
+
+#[test]
+fn report_measures_sample_rate_after_a_second() {
+    let mut stats = LevelStats::new();
+    stats.push(&tone(0.1, 32_000));
+    let early = report(&stats, &stats, 48_000, 2, Duration::from_millis(500), -20.0);
+    assert_eq!(early.measured_sample_rate, None);
+
+    // 32 000 resampled samples in 2 s: the device delivers its nominal rate
+    let report = report(&stats, &stats, 48_000, 2, Duration::from_secs(2), -20.0);
+    assert_eq!(report.measured_sample_rate, Some(48_000));
+    assert_eq!(report.sample_rate, 48_000);
+    assert_eq!(report.channels, 2);
+    assert_eq!(report.duration_ms, 2_000);
+}
+
+#[test]
+fn report_flags_clipping_and_recommends_thresholds() {
+    let mut stats = LevelStats::new();
+    stats.push(&tone(0.001, 16_000));
+    stats.push(&[1.0; 10]);
+    let report = report(&stats, &stats, 16_000, 1, Duration::from_secs(1), -20.0);
+    assert!(report.clipping);
+    assert_eq!(report.clipped_samples, 10);
+    assert_eq!(report.peak_db, 0.0);
+    assert!(report.snr_db.unwrap() > 0.0);
+    assert_eq!(report.recommended_start_threshold, Some(QUIET_THRESHOLD));
+    assert!(report.recommended_stop_threshold.unwrap() < QUIET_THRESHOLD);
+}
+
+#[test]
+fn report_takes_room_levels_from_the_audio_the_vad_sees() {
+    // Raw audio with a hum the high-pass filter removes
+    let mut stats = LevelStats::new();
+    stats.push(&tone(0.1, 16_000));
+    stats.push(&[1.0; 10]);
+    let mut vad_stats = LevelStats::new();
+    vad_stats.push(&tone(0.001, 16_000));
+
+    let report = report(&stats, &vad_stats, 16_000, 1, Duration::from_secs(1), -20.0);
+    assert!(report.clipping);
+    assert_eq!(report.peak, 1.0);
+    assert_eq!(report.noise_floor_db, vad_stats.noise_floor_db());
+    assert_eq!(report.recommended_start_threshold, Some(QUIET_THRESHOLD));
+}
+
+#[test]
+fn report_of_silence_is_finite() {
+    let report = report(
+        &LevelStats::new(),
+        &LevelStats::new(),
+        16_000,
+        1,
+        Duration::ZERO,
+        energy_db(&[]),
+    );
+    assert!(report.peak_db.is_finite());
+    assert!(report.level_db.is_finite());
+    assert!(!report.clipping);
+    assert_eq!(report.recommended_start_threshold, None);
+    // Serializes without nulls for the levels
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json["peak_db"].is_number());
+}
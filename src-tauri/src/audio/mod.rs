@@ -1,19 +1,37 @@
+pub mod archive;
 pub mod capture;
+pub mod decode;
+pub mod mic_test;
+pub mod models;
+pub mod preprocess;
 pub mod vad;
-
-use capture::{AudioDeviceInfo, compute_rms};
+pub mod watcher;
+
+use crate::download::{DownloadState, ModelKind};
+use crate::hotkey::{ActivationMode, HotkeyState};
+use crate::settings::config::SettingsState;
+use archive::{ArchiveConfig, RecordingInfo};
+use capture::{AudioDeviceInfo, CaptureDevice, compute_rms};
+use mic_test::{MicTest, MicTestReport};
+use models::VadModelStatus;
+use preprocess::{PreprocessConfig, Preprocessor};
 use serde::Serialize;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter, Runtime};
-use vad::{SileroVad, SpeechDetector, SpeechEvent};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use vad::energy::EnergyVad;
+use vad::{SileroVad, SpeechCollector, SpeechDetector, SpeechEvent, VadConfig, VoiceActivity};
+use watcher::{DeviceWatcher, Interest};
 
 /// Tauri-managed state for audio capture.
 pub struct AudioState {
     /// Selected input device ID (None = system default).
     pub selected_device: Mutex<Option<String>>,
+    /// Devices to fall back to, most preferred first, when the selected one is unavailable.
+    pub preferred_devices: Mutex<Vec<String>>,
     /// Accumulated 16kHz mono speech audio from the last recording session.
     pub speech_buffer: Arc<Mutex<Vec<f32>>>,
     /// Whether we're currently recording.
@@ -24,17 +42,33 @@ pub struct AudioState {
     thread_handle: Mutex<Option<thread::JoinHandle<()>>>,
     /// Path to the Silero VAD ONNX model.
     vad_model_path: Mutex<Option<String>>,
+    /// VAD thresholds and timeouts, applied from the next recording on.
+    pub vad_config: Mutex<VadConfig>,
+    /// Preprocessing stages, applied from the next recording on.
+    pub preprocess_config: Mutex<PreprocessConfig>,
+    /// Whether and how long to keep recordings on disk.
+    pub archive_config: Mutex<ArchiveConfig>,
+    /// Running microphone test, if any.
+    mic_test: Mutex<Option<MicTest>>,
+    /// Input device hot-plug monitoring.
+    pub device_watcher: DeviceWatcher,
 }
 
 impl AudioState {
     pub fn new() -> Self {
         Self {
             selected_device: Mutex::new(None),
+            preferred_devices: Mutex::new(Vec::new()),
             speech_buffer: Arc::new(Mutex::new(Vec::new())),
             is_capturing: AtomicBool::new(false),
             stop_signal: Mutex::new(None),
             thread_handle: Mutex::new(None),
             vad_model_path: Mutex::new(None),
+            vad_config: Mutex::new(VadConfig::default()),
+            preprocess_config: Mutex::new(PreprocessConfig::default()),
+            archive_config: Mutex::new(ArchiveConfig::default()),
+            mic_test: Mutex::new(None),
+            device_watcher: DeviceWatcher::new(),
         }
     }
 
@@ -42,6 +76,16 @@ impl AudioState {
     pub fn set_vad_model_path(&self, path: String) {
         *self.vad_model_path.lock().unwrap() = Some(path);
     }
+
+    /// Use the energy-based VAD from the next recording on.
+    pub fn clear_vad_model_path(&self) {
+        *self.vad_model_path.lock().unwrap() = None;
+    }
+
+    /// Whether recordings use Silero VAD (rather than the energy-based fallback).
+    pub fn vad_active(&self) -> bool {
+        self.vad_model_path.lock().unwrap().is_some()
+    }
 }
 
 /// Audio level event payload.
@@ -56,22 +100,44 @@ struct AudioErrorEvent {
     message: String,
 }
 
-/// Device disconnect event payload.
+/// Device disconnect event payload: the device recording continues on.
 #[derive(Clone, Serialize)]
 struct DeviceDisconnectEvent {
     fallback_device: String,
+    fallback_device_id: String,
 }
 
+/// A stream delivering nothing for this long is treated as disconnected.
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to keep trying to reopen capture after the device went away.
+const REOPEN_TIMEOUT: Duration = Duration::from_secs(5);
+const REOPEN_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Device switches allowed per recording, so a failing device cannot loop.
+const MAX_REOPENS: u32 = 5;
+
 /// Start recording audio. Spawns a thread that captures audio, runs VAD,
 /// and emits level/speech events to the frontend.
 pub fn start_recording<R: Runtime>(app: &AppHandle<R>, state: &AudioState) -> Result<(), String> {
     if state.is_capturing.load(Ordering::Relaxed) {
         return Err("Already recording".to_string());
     }
+    // The recording needs the microphone the test has open
+    end_mic_test(state);
 
     let device_id = state.selected_device.lock().unwrap().clone();
+    let candidates =
+        capture::capture_candidates(device_id.as_deref(), &state.preferred_devices.lock().unwrap());
     let speech_buffer = state.speech_buffer.clone();
     let vad_model_path = state.vad_model_path.lock().unwrap().clone();
+    let mut vad_config = state.vad_config.lock().unwrap().clone();
+    let preprocess_config = state.preprocess_config.lock().unwrap().clone();
+    // Hold mode ends on key release; stopping on silence is for toggle mode
+    let hotkey_state: tauri::State<'_, HotkeyState> = app.state();
+    if *hotkey_state.mode.lock().unwrap() != ActivationMode::Toggle {
+        vad_config.stop_on_silence = false;
+    }
     let app_handle = app.clone();
 
     // Clear previous speech buffer
@@ -83,12 +149,15 @@ pub fn start_recording<R: Runtime>(app: &AppHandle<R>, state: &AudioState) -> Re
 
     state.is_capturing.store(true, Ordering::Relaxed);
     *state.stop_signal.lock().unwrap() = Some(stop.clone());
+    state.device_watcher.set(Interest::Recording, true);
 
     let handle = thread::spawn(move || {
         if let Err(e) = recording_thread(
             &app_handle,
-            device_id.as_deref(),
+            &candidates,
             vad_model_path.as_deref(),
+            &vad_config,
+            &preprocess_config,
             speech_buffer,
             stop_clone,
         ) {
@@ -112,6 +181,16 @@ pub fn stop_recording(state: &AudioState) {
     }
 
     state.is_capturing.store(false, Ordering::Relaxed);
+    state.device_watcher.set(Interest::Recording, false);
+}
+
+/// Stop the microphone test if one is running. The test thread locks
+/// `mic_test` when it ends, so it is taken out before joining the thread.
+pub fn end_mic_test(state: &AudioState) {
+    let running = state.mic_test.lock().unwrap().take();
+    if let Some(test) = running {
+        let _ = mic_test::stop(test);
+    }
 }
 
 /// Take the speech buffer contents (empties it).
@@ -119,39 +198,75 @@ pub fn take_speech_buffer(state: &AudioState) -> Vec<f32> {
     std::mem::take(&mut *state.speech_buffer.lock().unwrap())
 }
 
+/// Save a finished recording to the archive if enabled, then apply the
+/// retention limits, on a background thread so transcription does not wait
+/// for the disk. Failures are logged only: they must not cost the user the
+/// transcription.
+pub fn archive_recording<R: Runtime>(app: &AppHandle<R>, samples: &[f32]) {
+    let state: tauri::State<'_, AudioState> = app.state();
+    let config = state.archive_config.lock().unwrap().clone();
+    if !config.enabled || samples.is_empty() {
+        return;
+    }
+    let data_dir = match app.path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("Recording not archived, no app data directory: {}", e);
+            return;
+        }
+    };
+    let now = std::time::SystemTime::now();
+    let samples = samples.to_vec();
+    thread::spawn(move || {
+        match archive::save(&data_dir, &samples, now) {
+            Ok(recording) => log::info!("Recording archived as {}", recording.id),
+            Err(e) => log::warn!("{}", e),
+        }
+        if let Err(e) = archive::apply_retention(&data_dir, &config, now) {
+            log::warn!("Failed to apply recording retention: {}", e);
+        }
+    });
+}
+
 /// The recording thread: captures audio, runs VAD, emits events.
 fn recording_thread<R: Runtime>(
     app: &AppHandle<R>,
-    device_id: Option<&str>,
+    candidates: &[String],
     vad_model_path: Option<&str>,
+    vad_config: &VadConfig,
+    preprocess_config: &PreprocessConfig,
     speech_buffer: Arc<Mutex<Vec<f32>>>,
     stop: Arc<AtomicBool>,
 ) -> Result<(), String> {
-    let (tx, rx) = mpsc::channel::<Vec<f32>>();
+    let (tx, mut rx) = mpsc::channel::<Vec<f32>>();
 
     // Start capture
-    let (_stream, _config) = capture::start_capture(device_id, tx)?;
+    let (mut stream, _config, mut device) = capture::start_capture_preferred(candidates, tx)?;
+    let mut last_audio = Instant::now();
+    let mut reopens = 0;
 
-    // Load VAD if model is available
-    let mut vad = match vad_model_path {
-        Some(path) => match SileroVad::new(path) {
+    // Silero VAD if the model loads, the energy-based fallback otherwise
+    let mut vad: Box<dyn VoiceActivity> = match vad_model_path {
+        Some(path) => match SileroVad::new(path, vad_config) {
             Ok(v) => {
                 log::info!("VAD loaded successfully");
-                Some(v)
+                Box::new(v)
             }
             Err(e) => {
-                log::warn!("VAD not available, capturing all audio: {}", e);
-                None
+                log::warn!("Silero VAD not available, using energy-based VAD: {}", e);
+                Box::new(EnergyVad::new(vad_config))
             }
         },
         None => {
-            log::info!("No VAD model configured, capturing all audio");
-            None
+            log::info!("No VAD model configured, using energy-based VAD");
+            Box::new(EnergyVad::new(vad_config))
         }
     };
 
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(vad_config);
     detector.start();
+    let mut collector = SpeechCollector::new(vad_config);
+    let mut preprocessor = Preprocessor::new(preprocess_config);
 
     // Buffer for accumulating samples into VAD-sized frames
     let mut frame_buffer: Vec<f32> = Vec::new();
@@ -164,8 +279,11 @@ fn recording_thread<R: Runtime>(
     let mut rms_samples: Vec<f32> = Vec::new();
 
     while !stop.load(Ordering::Relaxed) {
-        match rx.recv_timeout(Duration::from_millis(50)) {
+        let device_lost = match rx.recv_timeout(Duration::from_millis(50)) {
             Ok(chunk) => {
+                last_audio = Instant::now();
+                // Condition the audio before VAD and STT see it
+                let chunk = preprocessor.process(&chunk);
                 rms_samples.extend_from_slice(&chunk);
 
                 // Emit RMS at ~30fps
@@ -176,108 +294,172 @@ fn recording_thread<R: Runtime>(
                     last_rms_emit = Instant::now();
                 }
 
-                if let Some(ref mut vad) = vad {
-                    // Accumulate into frame buffer and process VAD frames
-                    frame_buffer.extend_from_slice(&chunk);
-
-                    while frame_buffer.len() >= vad::FRAME_SIZE {
-                        let frame: Vec<f32> =
-                            frame_buffer.drain(..vad::FRAME_SIZE).collect();
-
-                        match vad.process_frame(&frame) {
-                            Ok(prob) => {
-                                let is_speech = SileroVad::is_speech(prob);
-                                let events = detector.update(is_speech);
-
-                                // If speech is active, accumulate audio
-                                if detector.state() == vad::SpeechState::Speech
-                                    || detector.state() == vad::SpeechState::TrailingSilence
-                                {
-                                    speech_buffer.lock().unwrap().extend_from_slice(&frame);
-                                }
-
-                                // Handle events
-                                for event in events {
-                                    match event {
-                                        SpeechEvent::SpeechStart => {
-                                            log::debug!("Speech started");
-                                        }
-                                        SpeechEvent::SpeechEnd => {
-                                            log::debug!("Speech ended");
-                                        }
-                                        SpeechEvent::NoSpeech => {
-                                            log::info!("No speech detected (5s)");
-                                            let _ = app.emit("audio://no-speech", ());
-                                        }
-                                        SpeechEvent::Timeout => {
-                                            log::info!("Speech timeout (10s), auto-stopping");
-                                            let _ = app.emit("audio://timeout", ());
-                                            return Ok(());
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("VAD processing error: {}", e);
+                // Accumulate into frame buffer and process VAD frames
+                frame_buffer.extend_from_slice(&chunk);
+
+                while frame_buffer.len() >= vad::FRAME_SIZE {
+                    let frame: Vec<f32> = frame_buffer.drain(..vad::FRAME_SIZE).collect();
+
+                    match vad.process_frame(&frame) {
+                        Ok(prob) => {
+                            let is_speech = vad.is_speech(prob);
+                            let events = detector.update(is_speech);
+
+                            // Accumulate speech audio with its pre-roll and post-roll
+                            collector.push(
+                                &frame,
+                                detector.state(),
+                                &events,
+                                &mut speech_buffer.lock().unwrap(),
+                            );
+
+                            if handle_speech_events(app, events, vad_config) {
+                                return Ok(());
                             }
                         }
+                        Err(e) => {
+                            log::error!("VAD processing error: {}", e);
+                        }
                     }
-                } else {
-                    // No VAD — accumulate all audio as speech
-                    speech_buffer.lock().unwrap().extend_from_slice(&chunk);
                 }
+                false
             }
             Err(mpsc::RecvTimeoutError::Timeout) => {
                 // Check for silence timeouts even when no audio arrives
-                if vad.is_some() {
-                    let events = detector.update(false);
-                    for event in events {
-                        match event {
-                            SpeechEvent::NoSpeech => {
-                                let _ = app.emit("audio://no-speech", ());
-                            }
-                            SpeechEvent::Timeout => {
-                                let _ = app.emit("audio://timeout", ());
-                                return Ok(());
-                            }
-                            _ => {}
-                        }
-                    }
+                if handle_speech_events(app, detector.update(false), vad_config) {
+                    return Ok(());
+                }
+                let stalled = last_audio.elapsed() >= STALL_TIMEOUT;
+                if stalled {
+                    log::warn!("No audio from '{}' for {:?}", device.name, STALL_TIMEOUT);
                 }
+                stalled
             }
             Err(mpsc::RecvTimeoutError::Disconnected) => {
-                // Stream ended (device disconnected?)
-                log::warn!("Audio stream disconnected");
-
-                // Try to fall back to default device
-                match capture::list_devices() {
-                    Ok(devices) => {
-                        if let Some(default) = devices.iter().find(|d| d.is_default) {
-                            let _ = app.emit(
-                                "audio://device-disconnected",
-                                DeviceDisconnectEvent {
-                                    fallback_device: default.name.clone(),
-                                },
-                            );
-                        }
-                    }
-                    Err(_) => {}
-                }
+                log::warn!("Audio device '{}' disconnected", device.name);
+                true
+            }
+        };
+        if !device_lost {
+            continue;
+        }
 
+        // The device is gone or stalled: continue on the next best one,
+        // keeping what was recorded so far
+        drop(stream);
+        let reopened = if reopens < MAX_REOPENS {
+            reopen_capture(candidates, &stop)
+        } else {
+            Err("Too many device changes".to_string())
+        };
+        match reopened {
+            Ok((new_stream, new_rx, new_device)) => {
+                reopens += 1;
+                log::info!("Recording continues on '{}'", new_device.name);
+                let _ = app.emit(
+                    "audio://device-disconnected",
+                    DeviceDisconnectEvent {
+                        fallback_device: new_device.name.clone(),
+                        fallback_device_id: new_device.id.clone(),
+                    },
+                );
+                stream = new_stream;
+                rx = new_rx;
+                device = new_device;
+                last_audio = Instant::now();
+            }
+            Err(e) => {
+                log::error!("No input device to continue recording on: {}", e);
                 let _ = app.emit(
                     "audio://error",
                     AudioErrorEvent {
                         message: "Microphone disconnected".to_string(),
                     },
                 );
-                return Err("Audio stream disconnected".to_string());
+                // Transcribe what was recorded before the device went away
+                if !stop.load(Ordering::Relaxed) {
+                    let app = app.clone();
+                    thread::spawn(move || crate::hotkey::stop_recording(&app));
+                }
+                return Ok(());
             }
         }
     }
 
+    // Keep the end of the recording the preprocessor still held back
+    frame_buffer.extend(preprocessor.flush());
+    collector.push(
+        &frame_buffer,
+        detector.state(),
+        &[],
+        &mut speech_buffer.lock().unwrap(),
+    );
+
     Ok(())
 }
 
+/// Open capture on the first available of `candidates` (or the default
+/// device), retrying for a while as devices settle after a change.
+fn reopen_capture(
+    candidates: &[String],
+    stop: &AtomicBool,
+) -> Result<(cpal::Stream, mpsc::Receiver<Vec<f32>>, CaptureDevice), String> {
+    let deadline = Instant::now() + REOPEN_TIMEOUT;
+    loop {
+        let (tx, rx) = mpsc::channel::<Vec<f32>>();
+        match capture::start_capture_preferred(candidates, tx) {
+            Ok((stream, _config, device)) => return Ok((stream, rx, device)),
+            Err(e) if Instant::now() < deadline && !stop.load(Ordering::Relaxed) => {
+                log::debug!("Reopening capture failed, retrying: {}", e);
+                thread::sleep(REOPEN_RETRY_INTERVAL);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Act on speech detector events. Returns true when the recording should end.
+fn handle_speech_events<R: Runtime>(
+    app: &AppHandle<R>,
+    events: Vec<SpeechEvent>,
+    vad_config: &VadConfig,
+) -> bool {
+    for event in events {
+        match event {
+            SpeechEvent::SpeechStart => {
+                log::debug!("Speech started");
+            }
+            SpeechEvent::SpeechEnd => {
+                log::debug!("Speech ended");
+            }
+            SpeechEvent::NoSpeech => {
+                log::info!("No speech detected ({}ms)", vad_config.no_speech_timeout_ms);
+                let _ = app.emit("audio://no-speech", ());
+            }
+            SpeechEvent::Timeout => {
+                log::info!(
+                    "Speech timeout ({}ms), auto-stopping",
+                    vad_config.auto_stop_timeout_ms
+                );
+                let _ = app.emit("audio://timeout", ());
+                return true;
+            }
+            SpeechEvent::UtteranceEnd => {
+                log::info!(
+                    "Speech ended ({}ms silence), stopping recording",
+                    vad_config.stop_silence_ms
+                );
+                // Same path as a second hotkey press, so the recording is
+                // transcribed. From another thread: the stop handler joins this one.
+                let app = app.clone();
+                thread::spawn(move || crate::hotkey::stop_recording(&app));
+                return true;
+            }
+        }
+    }
+    false
+}
+
 // --------------- Tauri commands ---------------
 
 #[tauri::command]
@@ -285,23 +467,239 @@ pub fn list_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
     capture::list_devices()
 }
 
+/// Select the input device by ID (None = system default). The ID is saved
+/// as given, so a device that is unplugged right now is used again once it
+/// returns. Returns the ID of the device used meanwhile: another one with the
+/// same name, or else the system default.
 #[tauri::command]
 pub fn set_audio_device(
     device_id: Option<String>,
     state: tauri::State<'_, AudioState>,
-) -> Result<(), String> {
-    // Verify device exists if an ID was provided
-    if let Some(ref id) = device_id {
-        let devices = capture::list_devices()?;
-        if !devices.iter().any(|d| d.id == *id) {
-            return Err(format!("Device not found: {}", id));
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<Option<String>, String> {
+    settings.update(|s| s.audio_device = device_id.clone())?;
+    *state.selected_device.lock().unwrap() = device_id.clone();
+
+    let Some(id) = device_id else {
+        return Ok(None);
+    };
+    let devices = capture::list_devices()?;
+    let ids: Vec<String> = devices.iter().map(|d| d.id.clone()).collect();
+    let names: Vec<String> = devices.iter().map(|d| d.name.clone()).collect();
+    match capture::resolve_device(&ids, &names, &id) {
+        Some(index) => Ok(Some(ids[index].clone())),
+        None => {
+            log::warn!("Audio device not found: {}, using the system default", id);
+            Ok(None)
         }
     }
-    *state.selected_device.lock().unwrap() = device_id;
-    Ok(())
 }
 
 #[tauri::command]
 pub fn get_audio_device(state: tauri::State<'_, AudioState>) -> Option<String> {
     state.selected_device.lock().unwrap().clone()
 }
+
+#[tauri::command]
+pub fn get_preferred_devices(state: tauri::State<'_, AudioState>) -> Vec<String> {
+    state.preferred_devices.lock().unwrap().clone()
+}
+
+/// Set the devices to fall back to, most preferred first, when the selected
+/// one is unavailable at the start of or during a recording.
+#[tauri::command]
+pub fn set_preferred_devices(
+    device_ids: Vec<String>,
+    state: tauri::State<'_, AudioState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<Vec<String>, String> {
+    if device_ids.iter().any(|id| id.trim().is_empty()) {
+        return Err("Device ID cannot be empty".to_string());
+    }
+    let mut unique: Vec<String> = Vec::new();
+    for id in device_ids {
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+    let device_ids = unique;
+    settings.update(|s| s.preferred_devices = device_ids.clone())?;
+    *state.preferred_devices.lock().unwrap() = device_ids.clone();
+    Ok(device_ids)
+}
+
+#[tauri::command]
+pub fn get_vad_config(state: tauri::State<'_, AudioState>) -> VadConfig {
+    state.vad_config.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_vad_config(
+    config: VadConfig,
+    state: tauri::State<'_, AudioState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.update(|s| s.vad = config.clone())?;
+    *state.vad_config.lock().unwrap() = config;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_preprocess_config(state: tauri::State<'_, AudioState>) -> PreprocessConfig {
+    state.preprocess_config.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_preprocess_config(
+    config: PreprocessConfig,
+    state: tauri::State<'_, AudioState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.update(|s| s.preprocessing = config.clone())?;
+    *state.preprocess_config.lock().unwrap() = config;
+    Ok(())
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("App data directory unavailable: {}", e))
+}
+
+/// Open the microphone (the selected one unless `device_id` is given) and
+/// stream `audio://level` and `audio://mic-test` events until `stop_mic_test`,
+/// or until `audio://mic-test-ended` when the test ends by itself.
+#[tauri::command(async)]
+pub fn start_mic_test(
+    app: AppHandle,
+    device_id: Option<String>,
+    state: tauri::State<'_, AudioState>,
+) -> Result<(), String> {
+    if state.is_capturing.load(Ordering::Relaxed) {
+        return Err("Cannot test the microphone while recording".to_string());
+    }
+    let mut running = state.mic_test.lock().unwrap();
+    if running.is_some() {
+        return Err("Microphone test already running".to_string());
+    }
+    let device_id = device_id.or_else(|| state.selected_device.lock().unwrap().clone());
+    let preprocess_config = state.preprocess_config.lock().unwrap().clone();
+    let handle = app.clone();
+    let on_end = move |report: MicTestReport| {
+        handle.state::<AudioState>().mic_test.lock().unwrap().take();
+        let _ = handle.emit("audio://mic-test-ended", report);
+    };
+    *running = Some(mic_test::start(
+        &app,
+        device_id.as_deref(),
+        &preprocess_config,
+        on_end,
+    )?);
+    Ok(())
+}
+
+/// Watch for microphones being plugged in and out (`audio://devices-changed`)
+/// while the audio settings are open.
+#[tauri::command]
+pub fn set_device_watch(active: bool, state: tauri::State<'_, AudioState>) {
+    state.device_watcher.set(Interest::Settings, active);
+}
+
+/// End the microphone test and return its measurements.
+#[tauri::command]
+pub fn stop_mic_test(state: tauri::State<'_, AudioState>) -> Result<MicTestReport, String> {
+    // Released before joining the test thread, which locks it when it ends
+    let running = state.mic_test.lock().unwrap().take();
+    let test = running.ok_or("No microphone test running")?;
+    mic_test::stop(test)
+}
+
+#[tauri::command]
+pub fn get_archive_config(state: tauri::State<'_, AudioState>) -> ArchiveConfig {
+    state.archive_config.lock().unwrap().clone()
+}
+
+/// Change the archive settings. Tighter limits apply to the kept recordings
+/// right away; turning the archive off keeps them.
+#[tauri::command]
+pub fn set_archive_config(
+    app: AppHandle,
+    config: ArchiveConfig,
+    state: tauri::State<'_, AudioState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.update(|s| s.recording_archive = config.clone())?;
+    *state.archive_config.lock().unwrap() = config.clone();
+    archive::apply_retention(&app_data_dir(&app)?, &config, std::time::SystemTime::now())?;
+    Ok(())
+}
+
+/// Archived recordings, newest first.
+#[tauri::command]
+pub fn list_recordings(app: AppHandle) -> Result<Vec<RecordingInfo>, String> {
+    archive::list(&app_data_dir(&app)?)
+}
+
+/// WAV file of an archived recording, for playback.
+#[tauri::command]
+pub fn get_recording_path(app: AppHandle, id: String) -> Result<String, String> {
+    let path = archive::recording_path(&app_data_dir(&app)?, &id)?;
+    if !path.exists() {
+        return Err(format!("Recording not found: {}", id));
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn delete_recording(app: AppHandle, id: String) -> Result<(), String> {
+    archive::delete(&app_data_dir(&app)?, &id)
+}
+
+/// Whether the VAD model is downloaded and in use. Shown during onboarding.
+#[tauri::command]
+pub fn get_vad_model_status(
+    app: AppHandle,
+    state: tauri::State<'_, AudioState>,
+) -> Result<VadModelStatus, String> {
+    let data_dir = app_data_dir(&app)?;
+    Ok(models::status(&data_dir, state.vad_active()))
+}
+
+/// Download and verify the VAD model, then use it for the next recordings.
+/// Runs off the main thread so `cancel_model_download` can be handled meanwhile.
+#[tauri::command(async)]
+pub fn download_vad_model(
+    app: AppHandle,
+    state: tauri::State<'_, AudioState>,
+    downloads: tauri::State<'_, DownloadState>,
+) -> Result<VadModelStatus, String> {
+    let data_dir = app_data_dir(&app)?;
+
+    let job = downloads.begin(ModelKind::Vad, models::VAD_MODEL_ID)?;
+    let mirror = downloads.mirror_url();
+    let path = models::download_model(&app, &data_dir, &job, mirror.as_deref())?;
+
+    state.set_vad_model_path(path.to_string_lossy().to_string());
+    log::info!("VAD enabled with {}", path.display());
+    Ok(models::status(&data_dir, true))
+}
+
+/// Verify the VAD model. A file failing verification is deleted and
+/// recordings use the energy-based VAD.
+#[tauri::command(async)]
+pub fn verify_vad_model(app: AppHandle, state: tauri::State<'_, AudioState>) -> Result<(), String> {
+    let data_dir = app_data_dir(&app)?;
+    models::verify_or_remove(&data_dir).inspect_err(|_| state.clear_vad_model_path())
+}
+
+/// Delete the VAD model. Recordings use the energy-based VAD until it is downloaded again.
+#[tauri::command]
+pub fn delete_vad_model(app: AppHandle, state: tauri::State<'_, AudioState>) -> Result<(), String> {
+    let data_dir = app_data_dir(&app)?;
+    models::delete_model(&data_dir)?;
+    state.clear_vad_model_path();
+    Ok(())
+}
@@ -0,0 +1,205 @@
+// Silero VAD model: download, verification and removal.
+//
+// The model is the `silero-vad` entry of the catalog's `vad` section. Custom
+// manifests without a VAD section fall back to the bundled entry.
+
+use super::vad::{SileroVad, VadConfig, VoiceActivity, FRAME_SIZE};
+use crate::catalog::{self, CatalogEntry, Manifest};
+use crate::download::{self, Download, DownloadError, DownloadJob, ModelKind};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Catalog ID of the VAD model used for recordings.
+pub const VAD_MODEL_ID: &str = "silero-vad";
+
+/// VAD model status for the settings UI and onboarding.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VadModelStatus {
+    pub model_id: String,
+    pub name: String,
+    pub size_bytes: u64,
+    pub downloaded: bool,
+    /// Recordings use Silero VAD (false = the energy-based fallback).
+    pub active: bool,
+}
+
+/// Download progress event payload for VAD model downloads.
+#[derive(Clone, Serialize)]
+pub struct VadDownloadProgressEvent {
+    pub job_id: u64,
+    pub model_id: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: u64,
+    pub percent: f32,
+}
+
+/// Get the VAD models directory, creating it if needed.
+pub fn models_dir(app_data_dir: &Path) -> PathBuf {
+    let dir = app_data_dir.join("models").join("vad");
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// The active manifest, or the bundled one if it has no VAD model.
+fn manifest(app_data_dir: &Path) -> Manifest {
+    let manifest = catalog::load(app_data_dir);
+    if manifest.find(ModelKind::Vad, VAD_MODEL_ID).is_some() {
+        manifest
+    } else {
+        catalog::default_manifest()
+    }
+}
+
+fn entry(manifest: &Manifest) -> &CatalogEntry {
+    manifest
+        .find(ModelKind::Vad, VAD_MODEL_ID)
+        .expect("manifest has the VAD model")
+}
+
+/// Get the file path of the VAD model.
+pub fn model_path(app_data_dir: &Path) -> PathBuf {
+    models_dir(app_data_dir).join(&entry(&manifest(app_data_dir)).filename)
+}
+
+/// Check if the VAD model is downloaded.
+pub fn is_downloaded(app_data_dir: &Path) -> bool {
+    model_path(app_data_dir).exists()
+}
+
+/// Download and load status of the VAD model. `active` is whether the
+/// recorder has a VAD model configured.
+pub fn status(app_data_dir: &Path, active: bool) -> VadModelStatus {
+    let manifest = manifest(app_data_dir);
+    let entry = entry(&manifest);
+    VadModelStatus {
+        model_id: entry.id.clone(),
+        name: entry.name.clone(),
+        size_bytes: entry.size_bytes,
+        downloaded: is_downloaded(app_data_dir),
+        active,
+    }
+}
+
+/// Check that the downloaded model matches its pinned checksum (or, without
+/// one, its catalog size) and that ONNX Runtime can load it and run a frame.
+pub fn verify_model(app_data_dir: &Path) -> Result<(), String> {
+    let manifest = manifest(app_data_dir);
+    let entry = entry(&manifest);
+    let path = models_dir(app_data_dir).join(&entry.filename);
+    if !path.exists() {
+        return Err("VAD model is not downloaded".to_string());
+    }
+
+    match &entry.sha256 {
+        Some(expected) => {
+            let actual = download::sha256_file(&path)?;
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(DownloadError::ChecksumMismatch {
+                    expected: expected.clone(),
+                    actual,
+                }
+                .into());
+            }
+        }
+        None => {
+            let size = fs::metadata(&path)
+                .map_err(|e| format!("Failed to read VAD model: {}", e))?
+                .len();
+            if size != entry.size_bytes {
+                return Err(format!(
+                    "Downloaded file is corrupt ({} bytes, expected {})",
+                    size, entry.size_bytes
+                ));
+            }
+        }
+    }
+
+    let mut vad = SileroVad::new(&path.to_string_lossy(), &VadConfig::default())?;
+    vad.process_frame(&[0.0; FRAME_SIZE])?;
+    Ok(())
+}
+
+/// Verify the downloaded model and delete it if it fails, so a bad file is
+/// never picked up at startup.
+pub fn verify_or_remove(app_data_dir: &Path) -> Result<(), String> {
+    verify_model(app_data_dir).inspect_err(|e| {
+        log::warn!("Removing VAD model that failed verification: {}", e);
+        let _ = delete_model(app_data_dir);
+    })
+}
+
+/// Delete the downloaded VAD model.
+pub fn delete_model(app_data_dir: &Path) -> Result<(), String> {
+    let path = model_path(app_data_dir);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete VAD model: {}", e))?;
+        log::info!("Deleted VAD model ({})", path.display());
+    }
+    Ok(())
+}
+
+/// Download the VAD model from the catalog (or `mirror`) with progress events
+/// and verify it. A file failing verification is deleted.
+/// Runs synchronously — call from a background thread.
+pub fn download_model<R: Runtime>(
+    app: &AppHandle<R>,
+    app_data_dir: &Path,
+    job: &DownloadJob,
+    mirror: Option<&str>,
+) -> Result<PathBuf, String> {
+    let manifest = manifest(app_data_dir);
+    let entry = entry(&manifest);
+    let dest = models_dir(app_data_dir).join(&entry.filename);
+
+    // Skip if already downloaded and intact
+    if dest.exists() && verify_or_remove(app_data_dir).is_ok() {
+        log::info!("VAD model already downloaded at {}", dest.display());
+        return Ok(dest);
+    }
+
+    let url = manifest.download_url(entry, mirror);
+    log::info!("Downloading VAD model from {}", url);
+
+    let download = Download {
+        url: &url,
+        dest: &dest,
+        sha256: entry.sha256.as_deref(),
+        size_hint: entry.size_bytes,
+        cancel: Some(job.cancel_flag()),
+    };
+    let result = download::download_file(&download, |downloaded_bytes, total_bytes| {
+        let percent = if total_bytes > 0 {
+            (downloaded_bytes as f32 / total_bytes as f32) * 100.0
+        } else {
+            0.0
+        };
+
+        let _ = app.emit(
+            "vad://download-progress",
+            VadDownloadProgressEvent {
+                job_id: job.id,
+                model_id: entry.id.clone(),
+                downloaded_bytes,
+                total_bytes,
+                percent,
+            },
+        );
+    });
+
+    if let Err(e) = result {
+        if e == DownloadError::Cancelled {
+            log::info!("Download of VAD model cancelled");
+            let _ = app.emit("model://download-cancelled", job.info());
+        }
+        return Err(e.into());
+    }
+
+    verify_or_remove(app_data_dir)?;
+    log::info!("VAD model downloaded successfully");
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,106 @@
+use super::*;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("vad_models")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn model_lives_in_vad_dir() {
+    let dir = unique_dir("path");
+    assert_eq!(
+        model_path(&dir),
+        dir.join("models").join("vad").join("silero_vad.onnx")
+    );
+}
+
+#[test]
+fn status_reflects_download() {
+    let dir = unique_dir("status");
+    let status = status(&dir, false);
+    assert_eq!(status.model_id, VAD_MODEL_ID);
+    assert!(!status.downloaded);
+    assert!(!status.active);
+    assert!(status.size_bytes > 0);
+
+    fs::write(model_path(&dir), b"onnx").unwrap();
+    assert!(super::status(&dir, true).downloaded);
+}
+
+#[test]
+fn manifest_without_vad_falls_back_to_bundled_entry() {
+    let dir = unique_dir("fallback");
+    let mut custom = catalog::default_manifest();
+    custom.vad.clear();
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(
+        catalog::cached_manifest_path(&dir),
+        serde_json::to_string(&custom).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(catalog::load(&dir), custom);
+    assert_eq!(status(&dir, false).model_id, VAD_MODEL_ID);
+}
+
+#[test]
+fn delete_removes_model() {
+    let dir = unique_dir("delete");
+    let path = model_path(&dir);
+    fs::write(&path, b"onnx").unwrap();
+
+    delete_model(&dir).unwrap();
+    assert!(!path.exists());
+    // Deleting a missing model is not an error
+    delete_model(&dir).unwrap();
+}
+
+#[test]
+fn verify_requires_download() {
+    let dir = unique_dir("verify_missing");
+    assert!(verify_model(&dir).is_err());
+}
+
+#[test]
+fn model_failing_verification_is_removed() {
+    let dir = unique_dir("verify_remove");
+    let mut custom = catalog::default_manifest();
+    // SHA-256 of b"hello"
+    custom.vad[0].sha256 =
+        Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string());
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(
+        catalog::cached_manifest_path(&dir),
+        serde_json::to_string(&custom).unwrap(),
+    )
+    .unwrap();
+    let path = model_path(&dir);
+    fs::write(&path, b"jello").unwrap();
+
+    let err = verify_or_remove(&dir).unwrap_err();
+    assert!(err.contains("corrupt"), "{}", err);
+    assert!(!path.exists());
+}
+
+#[test]
+fn model_of_wrong_size_fails_verification_without_pinned_hash() {
+    let dir = unique_dir("verify_size");
+    let mut custom = catalog::default_manifest();
+    custom.vad[0].sha256 = None;
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(
+        catalog::cached_manifest_path(&dir),
+        serde_json::to_string(&custom).unwrap(),
+    )
+    .unwrap();
+    fs::write(model_path(&dir), b"<html>Not Found</html>").unwrap();
+
+    let err = verify_model(&dir).unwrap_err();
+    assert!(err.contains("corrupt"), "{}", err);
+}
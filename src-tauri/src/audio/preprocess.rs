@@ -0,0 +1,127 @@
+// Audio conditioning between capture and VAD/STT.
+//
+// Three stages, each toggleable, run on the 16kHz mono stream in order:
+// a high-pass filter that removes DC offset and rumble, spectral-subtraction
+// noise suppression against a learned noise profile, and automatic gain
+// control with a peak limiter. Stage state carries over between chunks, so
+// one `Preprocessor` serves a whole recording.
+
+pub mod denoise;
+pub mod filter;
+pub mod gain;
+
+use denoise::NoiseSuppressor;
+use filter::HighPass;
+use gain::AutoGain;
+use serde::{Deserialize, Serialize};
+
+/// Preprocessing stages and their parameters. Persisted as part of the user settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PreprocessConfig {
+    /// Remove DC offset and low-frequency rumble.
+    pub high_pass: bool,
+    /// High-pass cutoff (Hz).
+    pub high_pass_cutoff_hz: f32,
+    /// Suppress stationary background noise (fans, hum, hiss).
+    pub noise_suppression: bool,
+    /// Maximum attenuation of noise-only frequency bins (dB).
+    pub noise_reduction_db: f32,
+    /// Bring quiet microphones up to a consistent level. Off by default: the
+    /// gain holds through pauses, so it also raises the level the silence
+    /// gates, the hallucination filter and the level meter see.
+    pub auto_gain: bool,
+    /// Level the gain control aims for (dBFS RMS).
+    pub target_level_db: f32,
+    /// Maximum amplification (dB).
+    pub max_gain_db: f32,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            high_pass: true,
+            high_pass_cutoff_hz: 80.0,
+            noise_suppression: false,
+            noise_reduction_db: 12.0,
+            auto_gain: false,
+            target_level_db: -20.0,
+            max_gain_db: 20.0,
+        }
+    }
+}
+
+impl PreprocessConfig {
+    /// Reject parameters the stages would misbehave with.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(20.0..=300.0).contains(&self.high_pass_cutoff_hz) {
+            return Err("High-pass cutoff must be between 20 and 300 Hz".to_string());
+        }
+        if !(0.0..=40.0).contains(&self.noise_reduction_db) {
+            return Err("Noise reduction must be between 0 and 40 dB".to_string());
+        }
+        if !(-40.0..=-3.0).contains(&self.target_level_db) {
+            return Err("Target level must be between -40 and -3 dBFS".to_string());
+        }
+        if !(0.0..=40.0).contains(&self.max_gain_db) {
+            return Err("Maximum gain must be between 0 and 40 dB".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The enabled stages of one recording.
+pub struct Preprocessor {
+    high_pass: Option<HighPass>,
+    denoise: Option<NoiseSuppressor>,
+    gain: Option<AutoGain>,
+}
+
+impl Preprocessor {
+    pub fn new(config: &PreprocessConfig) -> Self {
+        Self {
+            high_pass: config
+                .high_pass
+                .then(|| HighPass::new(config.high_pass_cutoff_hz)),
+            denoise: config
+                .noise_suppression
+                .then(|| NoiseSuppressor::new(config.noise_reduction_db)),
+            gain: config
+                .auto_gain
+                .then(|| AutoGain::new(config.target_level_db, config.max_gain_db)),
+        }
+    }
+
+    /// Run a chunk through the enabled stages. With noise suppression on, the
+    /// output lags the input by `denoise::LATENCY` samples.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut samples = input.to_vec();
+        if let Some(high_pass) = &mut self.high_pass {
+            high_pass.process(&mut samples);
+        }
+        if let Some(denoise) = &mut self.denoise {
+            samples = denoise.process(&samples);
+        }
+        // Last, so gain does not bring up noise the suppressor would remove
+        if let Some(gain) = &mut self.gain {
+            gain.process(&mut samples);
+        }
+        samples
+    }
+
+    /// Output held back by noise suppression, run through the later stages.
+    /// Call when the input ends.
+    pub fn flush(&mut self) -> Vec<f32> {
+        let Some(denoise) = &mut self.denoise else {
+            return Vec::new();
+        };
+        let mut samples = denoise.flush();
+        if let Some(gain) = &mut self.gain {
+            gain.process(&mut samples);
+        }
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,181 @@
+// Spectral-subtraction noise suppression.
+//
+// The signal is analysed in 32 ms frames with 50% overlap (square-root Hann
+// windows on both sides, so unprocessed frames reconstruct exactly). Each
+// frequency bin is attenuated by how much of its power the noise profile
+// accounts for, never by more than the configured reduction.
+//
+// The profile is the average spectrum of the first frames of a recording,
+// which normally precede speech. Afterwards frames that look like noise only
+// update it: quickly where the noise got quieter, slowly where it got louder.
+// Bin powers are smoothed over a few frames before the gain is computed, which
+// keeps random peaks in the noise from passing through as "musical" tones.
+
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::sync::Arc;
+
+/// Analysis frame length (samples).
+const FRAME_SIZE: usize = 512;
+
+/// Hop between frames (samples).
+const HOP_SIZE: usize = FRAME_SIZE / 2;
+
+/// Delay the suppressor adds to the stream (samples).
+pub const LATENCY: usize = FRAME_SIZE - HOP_SIZE;
+
+/// Frames averaged into the initial noise profile (~160 ms).
+const LEARN_FRAMES: usize = 10;
+
+/// Noise power is over-subtracted by this factor to keep residual noise down.
+const OVER_SUBTRACTION: f32 = 2.0;
+
+/// A frame is noise only if its power is below this multiple of the profile.
+const NOISE_FRAME_RATIO: f32 = 2.0;
+
+/// Per-frame smoothing of the profile towards lower and higher bin powers.
+const PROFILE_FALL_RATE: f32 = 0.2;
+const PROFILE_RISE_RATE: f32 = 0.05;
+
+/// Weight of the current frame in the smoothed bin power.
+const POWER_SMOOTHING: f32 = 0.4;
+
+pub struct NoiseSuppressor {
+    forward: Arc<dyn RealToComplex<f32>>,
+    inverse: Arc<dyn ComplexToReal<f32>>,
+    window: Vec<f32>,
+    /// Lowest gain applied to a bin (linear).
+    floor: f32,
+    /// Noise power per bin.
+    profile: Vec<f32>,
+    /// Bin power smoothed over recent frames.
+    smoothed: Vec<f32>,
+    /// Frames analysed so far (counts up to LEARN_FRAMES).
+    frames: usize,
+    /// Input not yet covered by a full frame.
+    input: Vec<f32>,
+    /// Overlap-add accumulator.
+    output: Vec<f32>,
+}
+
+impl NoiseSuppressor {
+    pub fn new(reduction_db: f32) -> Self {
+        let mut planner = RealFftPlanner::<f32>::new();
+        let window = (0..FRAME_SIZE)
+            .map(|i| {
+                let hann =
+                    0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FRAME_SIZE as f32).cos();
+                hann.sqrt()
+            })
+            .collect();
+        Self {
+            forward: planner.plan_fft_forward(FRAME_SIZE),
+            inverse: planner.plan_fft_inverse(FRAME_SIZE),
+            window,
+            floor: 10f32.powf(-reduction_db / 20.0),
+            profile: vec![0.0; FRAME_SIZE / 2 + 1],
+            smoothed: vec![0.0; FRAME_SIZE / 2 + 1],
+            frames: 0,
+            // Leading silence aligns the first frame so output starts complete
+            input: vec![0.0; LATENCY],
+            output: vec![0.0; FRAME_SIZE],
+        }
+    }
+
+    /// Whether the initial noise profile has been learned.
+    pub fn profile_learned(&self) -> bool {
+        self.frames >= LEARN_FRAMES
+    }
+
+    /// Suppress noise in the next chunk. Output is delayed by `LATENCY`
+    /// samples and produced in whole hops.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        self.input.extend_from_slice(input);
+        let mut result = Vec::with_capacity(input.len() + HOP_SIZE);
+
+        while self.input.len() >= FRAME_SIZE {
+            let mut frame: Vec<f32> = self.input[..FRAME_SIZE]
+                .iter()
+                .zip(&self.window)
+                .map(|(x, w)| x * w)
+                .collect();
+            let mut spectrum = self.forward.make_output_vec();
+            if let Err(e) = self.forward.process(&mut frame, &mut spectrum) {
+                log::error!("Noise suppression FFT failed: {}", e);
+                spectrum.fill(Complex::new(0.0, 0.0));
+            }
+
+            self.suppress(&mut spectrum);
+
+            // Real signals have no imaginary part at DC and Nyquist
+            spectrum[0].im = 0.0;
+            spectrum[FRAME_SIZE / 2].im = 0.0;
+            if let Err(e) = self.inverse.process(&mut spectrum, &mut frame) {
+                log::error!("Noise suppression inverse FFT failed: {}", e);
+            }
+
+            let scale = 1.0 / FRAME_SIZE as f32;
+            for ((out, x), w) in self.output.iter_mut().zip(&frame).zip(&self.window) {
+                *out += x * w * scale;
+            }
+            result.extend_from_slice(&self.output[..HOP_SIZE]);
+            self.output.copy_within(HOP_SIZE.., 0);
+            self.output[FRAME_SIZE - HOP_SIZE..].fill(0.0);
+            self.input.drain(..HOP_SIZE);
+        }
+        result
+    }
+
+    /// Process the input still held back, padded with silence, so the output
+    /// catches up with the input. The noise profile is kept.
+    pub fn flush(&mut self) -> Vec<f32> {
+        let pending = self.input.len();
+        let mut result = self.process(&[0.0; FRAME_SIZE]);
+        result.truncate(pending);
+        self.input = vec![0.0; LATENCY];
+        self.output.fill(0.0);
+        result
+    }
+
+    fn suppress(&mut self, spectrum: &mut [Complex<f32>]) {
+        let power: Vec<f32> = spectrum.iter().map(|c| c.norm_sqr()).collect();
+        for (smoothed, p) in self.smoothed.iter_mut().zip(&power) {
+            *smoothed += (p - *smoothed) * POWER_SMOOTHING;
+        }
+
+        if self.frames < LEARN_FRAMES {
+            // Running mean over the learning frames
+            self.frames += 1;
+            let weight = 1.0 / self.frames as f32;
+            for (noise, p) in self.profile.iter_mut().zip(&power) {
+                *noise += (p - *noise) * weight;
+            }
+        } else {
+            let frame_power: f32 = power.iter().sum();
+            let noise_power: f32 = self.profile.iter().sum();
+            if frame_power < noise_power * NOISE_FRAME_RATIO {
+                for (noise, p) in self.profile.iter_mut().zip(&self.smoothed) {
+                    let rate = if *p < *noise {
+                        PROFILE_FALL_RATE
+                    } else {
+                        PROFILE_RISE_RATE
+                    };
+                    *noise += (p - *noise) * rate;
+                }
+            }
+        }
+
+        let floor_sq = self.floor * self.floor;
+        for ((bin, p), noise) in spectrum.iter_mut().zip(&self.smoothed).zip(&self.profile) {
+            let gain_sq = if *p > 0.0 {
+                (1.0 - OVER_SUBTRACTION * noise / p).max(floor_sq)
+            } else {
+                floor_sq
+            };
+            *bin *= gain_sq.sqrt();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
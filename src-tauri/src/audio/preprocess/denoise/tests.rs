@@ -0,0 +1,116 @@
+use super::*;
+use crate::audio::preprocess::tests::{level_db, magnitude, mix, noise, tone};
+
+fn apply(suppressor: &mut NoiseSuppressor, input: &[f32]) -> Vec<f32> {
+    input
+        .chunks(160)
+        .flat_map(|chunk| suppressor.process(chunk))
+        .collect()
+}
+
+/// Mean amplitude over 1.5-7.5 kHz; averaging many bins steadies the estimate.
+fn noise_level(signal: &[f32]) -> f32 {
+    let freqs: Vec<f32> = (0..48).map(|i| 1_500.0 + 125.0 * i as f32).collect();
+    freqs.iter().map(|&f| magnitude(signal, f)).sum::<f32>() / freqs.len() as f32
+}
+
+#[test]
+fn output_trails_input_by_latency() {
+    let mut suppressor = NoiseSuppressor::new(0.0);
+    let output = suppressor.process(&[0.0; 1_000]);
+    assert_eq!(output.len(), 768);
+    // The next 100 samples complete another hop
+    let output = suppressor.process(&[0.0; 100]);
+    assert_eq!(output.len(), 256);
+}
+
+#[test]
+fn reconstructs_perfectly_without_reduction() {
+    // With 0 dB reduction the floor is 1 and every bin passes unchanged
+    let input = mix(&tone(440.0, 0.3, 8_000), &noise(0.1, 8_000, 5));
+    let mut suppressor = NoiseSuppressor::new(0.0);
+    let output = apply(&mut suppressor, &input);
+    for (i, out) in output.iter().enumerate().skip(LATENCY) {
+        let expected = input[i - LATENCY];
+        assert!(
+            (out - expected).abs() < 1e-4,
+            "sample {}: {} vs {}",
+            i,
+            out,
+            expected
+        );
+    }
+}
+
+#[test]
+fn flush_releases_the_held_back_input() {
+    let input = mix(&tone(440.0, 0.3, 1_000), &noise(0.1, 1_000, 7));
+    let mut suppressor = NoiseSuppressor::new(0.0);
+    let mut output = apply(&mut suppressor, &input);
+    output.extend(suppressor.flush());
+
+    assert_eq!(output.len(), input.len() + LATENCY);
+    for (out, expected) in output[LATENCY..].iter().zip(&input) {
+        assert!((out - expected).abs() < 1e-4, "{} vs {}", out, expected);
+    }
+}
+
+#[test]
+fn learns_noise_profile_from_first_frames() {
+    let mut suppressor = NoiseSuppressor::new(12.0);
+    assert!(!suppressor.profile_learned());
+    apply(&mut suppressor, &noise(0.05, 16 * 200, 3));
+    assert!(suppressor.profile_learned());
+}
+
+#[test]
+fn attenuates_steady_noise() {
+    let input = noise(0.05, 32_000, 7);
+    let mut suppressor = NoiseSuppressor::new(12.0);
+    let output = apply(&mut suppressor, &input);
+    let reduction = level_db(&input[16_000..24_000]) - level_db(&output[16_000..24_000]);
+    // Bounded by the configured maximum
+    assert!(
+        reduction > 8.0 && reduction < 12.5,
+        "reduced by {:.1} dB",
+        reduction
+    );
+}
+
+#[test]
+fn keeps_speech_over_learned_noise() {
+    let len = 32_000;
+    let background = noise(0.02, len, 11);
+    let mut voice = vec![0.0; 8_000];
+    voice.extend(tone(700.0, 0.2, len - 8_000));
+    let input = mix(&background, &voice);
+
+    let mut suppressor = NoiseSuppressor::new(15.0);
+    let output = apply(&mut suppressor, &input);
+    let tail = &output[output.len() - 8_000..];
+    let source = &input[input.len() - 8_000 - LATENCY..input.len() - LATENCY];
+
+    // The tone keeps its level, the noise elsewhere in the spectrum drops
+    let voice_change = magnitude(tail, 700.0) / magnitude(source, 700.0);
+    assert!(
+        (voice_change - 1.0).abs() < 0.1,
+        "voice scaled by {}",
+        voice_change
+    );
+    let noise_change = noise_level(tail) / noise_level(source);
+    assert!(noise_change < 0.5, "noise scaled by {}", noise_change);
+}
+
+#[test]
+fn profile_follows_noise_down() {
+    // Speech from the first frame ends up in the profile, but the profile
+    // drops to the real noise once the speaker pauses
+    let mut input = tone(700.0, 0.2, 4_000);
+    input.extend(noise(0.01, 16_000, 13));
+    input.extend(tone(700.0, 0.2, 8_000));
+
+    let mut suppressor = NoiseSuppressor::new(15.0);
+    let output = apply(&mut suppressor, &input);
+    let tail = &output[output.len() - 6_000..];
+    assert!(magnitude(tail, 700.0) > 0.18, "{}", magnitude(tail, 700.0));
+}
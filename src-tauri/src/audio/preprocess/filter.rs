@@ -0,0 +1,48 @@
+// High-pass filter: second-order Butterworth biquad (RBJ cookbook).
+
+use crate::audio::capture::TARGET_SAMPLE_RATE;
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+/// Removes DC offset and rumble below the cutoff at 12 dB/octave.
+pub struct HighPass {
+    b: [f32; 3],
+    a: [f32; 2],
+    /// Previous two inputs and outputs.
+    x: [f32; 2],
+    y: [f32; 2],
+}
+
+impl HighPass {
+    pub fn new(cutoff_hz: f32) -> Self {
+        let w0 = 2.0 * PI * cutoff_hz / TARGET_SAMPLE_RATE as f32;
+        let alpha = w0.sin() / (2.0 * FRAC_1_SQRT_2);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Self {
+            b: [
+                (1.0 + cos) / 2.0 / a0,
+                -(1.0 + cos) / a0,
+                (1.0 + cos) / 2.0 / a0,
+            ],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Filter samples in place.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            let x = *sample;
+            let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+                - self.a[0] * self.y[0]
+                - self.a[1] * self.y[1];
+            self.x = [x, self.x[0]];
+            self.y = [y, self.y[0]];
+            *sample = y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+use crate::audio::preprocess::tests::{level_db, tone};
+
+fn filtered(input: &[f32]) -> Vec<f32> {
+    let mut output = input.to_vec();
+    HighPass::new(80.0).process(&mut output);
+    output
+}
+
+#[test]
+fn removes_dc_offset() {
+    let output = filtered(&vec![0.2; 16_000]);
+    // Settled after 100 ms
+    for sample in &output[1_600..] {
+        assert!(sample.abs() < 1e-3, "residual {}", sample);
+    }
+}
+
+#[test]
+fn attenuates_rumble() {
+    let input = tone(20.0, 0.5, 32_000);
+    let output = filtered(&input);
+    let attenuation = level_db(&input[16_000..]) - level_db(&output[16_000..]);
+    assert!(
+        attenuation > 20.0,
+        "20 Hz attenuated by {:.1} dB",
+        attenuation
+    );
+}
+
+#[test]
+fn cutoff_is_3db_down() {
+    let input = tone(80.0, 0.5, 32_000);
+    let output = filtered(&input);
+    let attenuation = level_db(&input[16_000..]) - level_db(&output[16_000..]);
+    assert!(
+        (attenuation - 3.0).abs() < 0.3,
+        "{:.2} dB at cutoff",
+        attenuation
+    );
+}
+
+#[test]
+fn passes_voice_band() {
+    for freq in [300.0, 1_000.0, 4_000.0] {
+        let input = tone(freq, 0.5, 16_000);
+        let output = filtered(&input);
+        let change = level_db(&output[1_600..]) - level_db(&input[1_600..]);
+        assert!(
+            change.abs() < 0.2,
+            "{} Hz changed by {:.2} dB",
+            freq,
+            change
+        );
+    }
+}
+
+#[test]
+fn chunked_matches_whole() {
+    let input = tone(150.0, 0.5, 4_000);
+    let whole = filtered(&input);
+    let mut filter = HighPass::new(80.0);
+    let mut chunked = Vec::new();
+    for chunk in input.chunks(333) {
+        let mut chunk = chunk.to_vec();
+        filter.process(&mut chunk);
+        chunked.extend(chunk);
+    }
+    assert_eq!(whole, chunked);
+}
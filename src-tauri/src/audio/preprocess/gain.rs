@@ -0,0 +1,96 @@
+// Automatic gain control with a peak limiter.
+//
+// The level is measured over 10 ms blocks. Blocks above the gate pull the
+// gain towards the one that brings the level to the target; quieter blocks
+// leave it alone, so background noise between words is not pumped up. The
+// gain moves smoothly per sample, and a sample that would exceed the limit
+// drops the gain at once.
+
+use crate::audio::vad::energy::energy_db;
+
+/// Samples per level measurement (10 ms).
+const BLOCK_SIZE: usize = 160;
+
+/// Blocks below this level (dBFS) do not change the gain.
+const GATE_DB: f32 = -50.0;
+
+/// Lowest gain; loud input is turned down at most this far (dB).
+const MIN_GAIN_DB: f32 = -20.0;
+
+/// Per-block smoothing of the gain target when turning down (fast) and up (slow).
+const ATTACK_RATE: f32 = 0.3;
+const RELEASE_RATE: f32 = 0.02;
+
+/// Per-sample smoothing of the applied gain towards the target.
+const GAIN_SMOOTHING: f32 = 0.002;
+
+/// Output peak limit.
+const PEAK_LIMIT: f32 = 0.95;
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+pub struct AutoGain {
+    target_level_db: f32,
+    max_gain_db: f32,
+    /// Gain the level measurements call for (dB).
+    target_gain_db: f32,
+    /// Linear gain applied to the current sample.
+    gain: f32,
+    /// Input of the block being measured.
+    block: Vec<f32>,
+}
+
+impl AutoGain {
+    pub fn new(target_level_db: f32, max_gain_db: f32) -> Self {
+        Self {
+            target_level_db,
+            max_gain_db,
+            target_gain_db: 0.0,
+            gain: 1.0,
+            block: Vec::with_capacity(BLOCK_SIZE),
+        }
+    }
+
+    /// Current gain (dB).
+    pub fn gain_db(&self) -> f32 {
+        20.0 * self.gain.log10()
+    }
+
+    /// Apply gain in place.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            self.block.push(*sample);
+            if self.block.len() == BLOCK_SIZE {
+                self.measure_block();
+            }
+
+            let target = db_to_gain(self.target_gain_db);
+            self.gain += (target - self.gain) * GAIN_SMOOTHING;
+            if (*sample * self.gain).abs() > PEAK_LIMIT {
+                self.gain = PEAK_LIMIT / sample.abs();
+                self.target_gain_db = self.target_gain_db.min(self.gain_db());
+            }
+            *sample *= self.gain;
+        }
+    }
+
+    fn measure_block(&mut self) {
+        let level = energy_db(&self.block);
+        self.block.clear();
+        if level < GATE_DB {
+            return;
+        }
+        let wanted = (self.target_level_db - level).clamp(MIN_GAIN_DB, self.max_gain_db);
+        let rate = if wanted < self.target_gain_db {
+            ATTACK_RATE
+        } else {
+            RELEASE_RATE
+        };
+        self.target_gain_db += (wanted - self.target_gain_db) * rate;
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,77 @@
+use super::*;
+use crate::audio::preprocess::tests::{level_db, tone};
+
+fn apply(agc: &mut AutoGain, input: &[f32]) -> Vec<f32> {
+    let mut output = input.to_vec();
+    for chunk in output.chunks_mut(160) {
+        agc.process(chunk);
+    }
+    output
+}
+
+#[test]
+fn brings_quiet_input_up_to_target() {
+    let mut agc = AutoGain::new(-20.0, 30.0);
+    // -43 dBFS RMS
+    let output = apply(&mut agc, &tone(300.0, 0.01, 48_000));
+    let level = level_db(&output[40_000..]);
+    assert!((level + 20.0).abs() < 1.0, "settled at {:.1} dBFS", level);
+}
+
+#[test]
+fn gain_is_capped() {
+    let mut agc = AutoGain::new(-20.0, 10.0);
+    let input = tone(300.0, 0.01, 48_000);
+    let output = apply(&mut agc, &input);
+    let boost = level_db(&output[40_000..]) - level_db(&input[40_000..]);
+    assert!((boost - 10.0).abs() < 0.5, "boost {:.1} dB", boost);
+}
+
+#[test]
+fn turns_loud_input_down() {
+    let mut agc = AutoGain::new(-20.0, 20.0);
+    // -9 dBFS RMS
+    let output = apply(&mut agc, &tone(300.0, 0.5, 16_000));
+    let level = level_db(&output[8_000..]);
+    assert!((level + 20.0).abs() < 1.0, "settled at {:.1} dBFS", level);
+}
+
+#[test]
+fn does_not_amplify_silence() {
+    let mut agc = AutoGain::new(-20.0, 30.0);
+    // -63 dBFS: below the gate
+    let input = tone(300.0, 0.001, 32_000);
+    let output = apply(&mut agc, &input);
+    assert!(agc.gain_db().abs() < 0.01);
+    assert_eq!(output, input);
+}
+
+#[test]
+fn peaks_never_exceed_limit() {
+    let mut agc = AutoGain::new(-10.0, 30.0);
+    let mut input = tone(300.0, 0.02, 16_000);
+    // A sudden loud burst after the gain has gone up
+    input.extend(tone(300.0, 0.9, 1_600));
+    let output = apply(&mut agc, &input);
+    let peak = output.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!(peak <= PEAK_LIMIT + 1e-6, "peak {}", peak);
+}
+
+#[test]
+fn gain_changes_smoothly() {
+    let mut agc = AutoGain::new(-20.0, 30.0);
+    let output = apply(&mut agc, &tone(300.0, 0.01, 16_000));
+    // No sample-to-sample jumps beyond what the tone itself does
+    let max_step = output
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .fold(0.0f32, f32::max);
+    let peak = output.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let tone_step = 2.0 * std::f32::consts::PI * 300.0 / 16_000.0 * peak;
+    assert!(
+        max_step <= tone_step * 1.05,
+        "step {} vs {}",
+        max_step,
+        tone_step
+    );
+}
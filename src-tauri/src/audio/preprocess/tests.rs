@@ -0,0 +1,207 @@
+use super::*;
+use crate::audio::capture::TARGET_SAMPLE_RATE;
+use crate::audio::vad::energy::energy_db;
+use std::f32::consts::PI;
+
+// --------------- Synthetic signals (shared with the stage tests) ---------------
+
+/// Sine of `freq_hz` at 16 kHz.
+pub(super) fn tone(freq_hz: f32, amplitude: f32, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| amplitude * (2.0 * PI * freq_hz * i as f32 / TARGET_SAMPLE_RATE as f32).sin())
+        .collect()
+}
+
+/// Deterministic white noise (LCG) with the given peak amplitude.
+pub(super) fn noise(amplitude: f32, len: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            amplitude * ((state >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0)
+        })
+        .collect()
+}
+
+pub(super) fn mix(a: &[f32], b: &[f32]) -> Vec<f32> {
+    a.iter().zip(b).map(|(x, y)| x + y).collect()
+}
+
+/// Level in dBFS RMS.
+pub(super) fn level_db(samples: &[f32]) -> f32 {
+    energy_db(samples)
+}
+
+/// Amplitude of the `freq_hz` component (single-bin DFT, Hann window).
+pub(super) fn magnitude(signal: &[f32], freq_hz: f32) -> f32 {
+    let n = signal.len();
+    let (mut re, mut im, mut gain) = (0.0f64, 0.0f64, 0.0f64);
+    for (i, &x) in signal.iter().enumerate() {
+        let window = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos();
+        let phase =
+            2.0 * std::f64::consts::PI * freq_hz as f64 * i as f64 / TARGET_SAMPLE_RATE as f64;
+        re += x as f64 * window * phase.cos();
+        im -= x as f64 * window * phase.sin();
+        gain += window;
+    }
+    (2.0 * (re * re + im * im).sqrt() / gain) as f32
+}
+
+/// Power of the `freq_hz` tone over the power of everything else (dB).
+fn snr_db(signal: &[f32], freq_hz: f32) -> f32 {
+    let mean = signal.iter().sum::<f32>() / signal.len() as f32;
+    let total: f32 =
+        signal.iter().map(|s| (s - mean) * (s - mean)).sum::<f32>() / signal.len() as f32;
+    let tone = magnitude(signal, freq_hz).powi(2) / 2.0;
+    10.0 * (tone / (total - tone)).log10()
+}
+
+/// Process `input` in 10 ms chunks, as the recording thread does.
+fn run(preprocessor: &mut Preprocessor, input: &[f32]) -> Vec<f32> {
+    input
+        .chunks(160)
+        .flat_map(|chunk| preprocessor.process(chunk))
+        .collect()
+}
+
+fn all_off() -> PreprocessConfig {
+    PreprocessConfig {
+        high_pass: false,
+        noise_suppression: false,
+        auto_gain: false,
+        ..Default::default()
+    }
+}
+
+// --------------- PreprocessConfig tests ---------------
+
+#[test]
+fn default_config_is_valid() {
+    assert!(PreprocessConfig::default().validate().is_ok());
+}
+
+#[test]
+fn config_rejects_out_of_range_values() {
+    let cases = [
+        PreprocessConfig {
+            high_pass_cutoff_hz: 5.0,
+            ..Default::default()
+        },
+        PreprocessConfig {
+            noise_reduction_db: -1.0,
+            ..Default::default()
+        },
+        PreprocessConfig {
+            target_level_db: 0.0,
+            ..Default::default()
+        },
+        PreprocessConfig {
+            max_gain_db: 60.0,
+            ..Default::default()
+        },
+        PreprocessConfig {
+            high_pass_cutoff_hz: f32::NAN,
+            ..Default::default()
+        },
+    ];
+    for config in cases {
+        assert!(config.validate().is_err(), "{:?}", config);
+    }
+}
+
+#[test]
+fn config_deserializes_partial_json_with_defaults() {
+    let config: PreprocessConfig = serde_json::from_str(r#"{"noiseSuppression":true}"#).unwrap();
+    assert!(config.noise_suppression);
+    assert!(config.high_pass);
+    assert_eq!(config.max_gain_db, 20.0);
+}
+
+// --------------- Preprocessor tests ---------------
+
+#[test]
+fn all_stages_off_is_identity() {
+    let input = mix(&tone(440.0, 0.3, 4_800), &noise(0.05, 4_800, 1));
+    let mut preprocessor = Preprocessor::new(&all_off());
+    assert_eq!(run(&mut preprocessor, &input), input);
+}
+
+#[test]
+fn default_chain_keeps_length() {
+    let input = tone(440.0, 0.3, 4_800);
+    let mut preprocessor = Preprocessor::new(&PreprocessConfig::default());
+    assert_eq!(run(&mut preprocessor, &input).len(), input.len());
+}
+
+#[test]
+fn default_chain_keeps_quiet_input_quiet() {
+    // Silence gates downstream compare against the microphone's own level
+    let input = tone(500.0, 0.01, 16_000);
+    let mut preprocessor = Preprocessor::new(&PreprocessConfig::default());
+    let output = run(&mut preprocessor, &input);
+    let change = level_db(&output[8_000..]) - level_db(&input[8_000..]);
+    assert!(change.abs() < 1.0, "level changed by {} dB", change);
+}
+
+#[test]
+fn denoising_chain_delays_by_latency() {
+    let config = PreprocessConfig {
+        noise_suppression: true,
+        ..all_off()
+    };
+    let mut preprocessor = Preprocessor::new(&config);
+    let output = run(&mut preprocessor, &vec![0.0; 4_800]);
+    // Output comes in whole hops, at most the latency behind the input
+    assert!(output.len() <= 4_800);
+    assert!(output.len() + denoise::LATENCY > 4_800);
+}
+
+#[test]
+fn flush_completes_the_delayed_output() {
+    let config = PreprocessConfig {
+        noise_suppression: true,
+        ..all_off()
+    };
+    let mut preprocessor = Preprocessor::new(&config);
+    let mut output = run(&mut preprocessor, &vec![0.0; 4_850]);
+    output.extend(preprocessor.flush());
+    assert_eq!(output.len(), 4_850 + denoise::LATENCY);
+
+    // Without noise suppression nothing is held back
+    assert!(Preprocessor::new(&all_off()).flush().is_empty());
+}
+
+#[test]
+fn full_chain_cleans_up_a_quiet_noisy_recording() {
+    // Quiet voice-band tone with DC offset and fan noise, after half a
+    // second of noise only
+    let len = 32_000;
+    let offset = vec![0.05; len];
+    let background = noise(0.01, len, 9);
+    let mut voice = vec![0.0; 8_000];
+    voice.extend(tone(500.0, 0.02, len - 8_000));
+    let input = mix(&mix(&offset, &background), &voice);
+
+    let config = PreprocessConfig {
+        noise_suppression: true,
+        auto_gain: true,
+        ..Default::default()
+    };
+    let mut preprocessor = Preprocessor::new(&config);
+    let output = run(&mut preprocessor, &input);
+    let tail = &output[output.len() - 8_000..];
+
+    // DC gone, voice brought up, noise down relative to the voice
+    let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+    assert!(mean.abs() < 0.005, "mean {}", mean);
+    let voice_level = magnitude(tail, 500.0);
+    assert!(voice_level > 0.05, "voice amplitude {}", voice_level);
+    let input_snr = snr_db(&input[input.len() - 8_000..], 500.0);
+    let output_snr = snr_db(tail, 500.0);
+    assert!(
+        output_snr > input_snr + 5.0,
+        "SNR {:.1} -> {:.1} dB",
+        input_snr,
+        output_snr
+    );
+}
@@ -1,24 +1,163 @@
+pub mod energy;
+
 use ort::session::Session;
 use ort::value::Tensor;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::time::Instant;
 
 /// Silero VAD processes 512 samples at 16kHz (32ms per frame).
 pub const FRAME_SIZE: usize = 512;
 
-/// Speech probability threshold.
-const SPEECH_THRESHOLD: f32 = 0.5;
+/// Number of elements in each LSTM state tensor [2, 1, 64].
+const STATE_SIZE: usize = 2 * 1 * 64;
+
+/// Shortest accepted trailing silence (ms); shorter values split words.
+const MIN_TRAILING_SILENCE_MS: u64 = 100;
+
+/// Shortest accepted no-speech timeout (ms).
+const MIN_NO_SPEECH_TIMEOUT_MS: u64 = 1_000;
+
+/// Longest accepted pre-roll or post-roll (ms).
+const MAX_ROLL_MS: u64 = 2_000;
+
+/// Samples per millisecond of 16kHz audio.
+const SAMPLES_PER_MS: usize = 16;
+
+/// VAD thresholds and timeouts. Persisted as part of the user settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VadConfig {
+    /// Speech probability at which silence turns into speech.
+    pub start_threshold: f32,
+    /// Speech probability below which speech turns back into silence.
+    /// Lower than `start_threshold` so speech hovering around one value
+    /// does not flap.
+    pub stop_threshold: f32,
+    /// Trailing silence before declaring speech end (ms).
+    pub trailing_silence_ms: u64,
+    /// Emit `NoSpeech` after this many ms with no speech detected.
+    pub no_speech_timeout_ms: u64,
+    /// Auto-stop recording after this many ms with no speech detected.
+    pub auto_stop_timeout_ms: u64,
+    /// End the recording once the user stops talking (toggle mode only).
+    pub stop_on_silence: bool,
+    /// Silence after speech that ends the recording when `stop_on_silence`
+    /// is on (ms). Counted from the last speech frame.
+    pub stop_silence_ms: u64,
+    /// Audio kept from before speech is detected, so quiet onsets are not clipped (ms).
+    pub pre_roll_ms: u64,
+    /// Audio kept after speech ends, on top of the trailing silence (ms).
+    pub post_roll_ms: u64,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            start_threshold: 0.5,
+            stop_threshold: 0.35,
+            trailing_silence_ms: 500,
+            no_speech_timeout_ms: 5_000,
+            auto_stop_timeout_ms: 10_000,
+            stop_on_silence: false,
+            stop_silence_ms: 1_500,
+            pre_roll_ms: 300,
+            post_roll_ms: 100,
+        }
+    }
+}
 
-/// Trailing silence buffer before declaring speech end (ms).
-const TRAILING_SILENCE_MS: u64 = 500;
+impl VadConfig {
+    /// Reject thresholds and timeouts the detector would misbehave with.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.start_threshold > 0.0 && self.start_threshold <= 1.0) {
+            return Err("Start threshold must be between 0.0 and 1.0".to_string());
+        }
+        if !(self.stop_threshold > 0.0 && self.stop_threshold <= self.start_threshold) {
+            return Err(
+                "Stop threshold must be above 0.0 and at most the start threshold".to_string(),
+            );
+        }
+        if self.trailing_silence_ms < MIN_TRAILING_SILENCE_MS {
+            return Err(format!(
+                "Trailing silence must be at least {} ms",
+                MIN_TRAILING_SILENCE_MS
+            ));
+        }
+        if self.no_speech_timeout_ms < MIN_NO_SPEECH_TIMEOUT_MS {
+            return Err(format!(
+                "No-speech timeout must be at least {} ms",
+                MIN_NO_SPEECH_TIMEOUT_MS
+            ));
+        }
+        if self.auto_stop_timeout_ms < self.no_speech_timeout_ms {
+            return Err("Auto-stop timeout must be at least the no-speech timeout".to_string());
+        }
+        if self.stop_silence_ms < self.trailing_silence_ms {
+            return Err("Stop-on-silence delay must be at least the trailing silence".to_string());
+        }
+        if self.pre_roll_ms > MAX_ROLL_MS || self.post_roll_ms > MAX_ROLL_MS {
+            return Err(format!(
+                "Pre-roll and post-roll must be at most {} ms",
+                MAX_ROLL_MS
+            ));
+        }
+        Ok(())
+    }
+}
 
-/// Emit "noSpeech" event after this many ms with no speech detected.
-const NO_SPEECH_TIMEOUT_MS: u64 = 5_000;
+/// Turns speech probabilities into speech/silence decisions with hysteresis:
+/// speech starts at the start threshold and lasts until the probability
+/// drops below the stop threshold.
+#[derive(Debug, Clone)]
+pub struct SpeechGate {
+    start_threshold: f32,
+    stop_threshold: f32,
+    speaking: bool,
+}
 
-/// Auto-stop recording after this many ms with no speech detected.
-const AUTO_STOP_TIMEOUT_MS: u64 = 10_000;
+impl SpeechGate {
+    pub fn new(config: &VadConfig) -> Self {
+        Self {
+            start_threshold: config.start_threshold,
+            stop_threshold: config.stop_threshold,
+            speaking: false,
+        }
+    }
 
-/// Number of elements in each LSTM state tensor [2, 1, 64].
-const STATE_SIZE: usize = 2 * 1 * 64;
+    /// Classify one frame's speech probability.
+    pub fn is_speech(&mut self, prob: f32) -> bool {
+        let threshold = if self.speaking {
+            self.stop_threshold
+        } else {
+            self.start_threshold
+        };
+        self.speaking = prob >= threshold;
+        self.speaking
+    }
+
+    /// Decision for the last classified frame.
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    pub fn reset(&mut self) {
+        self.speaking = false;
+    }
+}
+
+/// A voice activity detector: turns 16kHz audio frames into speech decisions.
+/// Implemented by `SileroVad` and by the `EnergyVad` fallback.
+pub trait VoiceActivity {
+    /// Process a single 512-sample audio frame. Returns speech probability [0.0, 1.0].
+    fn process_frame(&mut self, audio: &[f32]) -> Result<f32, String>;
+
+    /// Classify a frame's speech probability using the configured thresholds.
+    fn is_speech(&mut self, prob: f32) -> bool;
+
+    /// Reset internal state (call between recording sessions).
+    fn reset(&mut self);
+}
 
 /// Silero VAD wrapper using ONNX Runtime.
 pub struct SileroVad {
@@ -27,11 +166,12 @@ pub struct SileroVad {
     h_state: Vec<f32>,
     /// LSTM cell state — flat [2, 1, 64].
     c_state: Vec<f32>,
+    gate: SpeechGate,
 }
 
 impl SileroVad {
     /// Load the Silero VAD ONNX model from the given path.
-    pub fn new(model_path: &str) -> Result<Self, String> {
+    pub fn new(model_path: &str, config: &VadConfig) -> Result<Self, String> {
         let session = Session::builder()
             .map_err(|e| format!("Failed to create ORT session builder: {}", e))?
             .with_intra_threads(1)
@@ -45,11 +185,13 @@ impl SileroVad {
             session,
             h_state: vec![0.0f32; STATE_SIZE],
             c_state: vec![0.0f32; STATE_SIZE],
+            gate: SpeechGate::new(config),
         })
     }
+}
 
-    /// Process a single 512-sample audio frame. Returns speech probability [0.0, 1.0].
-    pub fn process_frame(&mut self, audio: &[f32]) -> Result<f32, String> {
+impl VoiceActivity for SileroVad {
+    fn process_frame(&mut self, audio: &[f32]) -> Result<f32, String> {
         if audio.len() != FRAME_SIZE {
             return Err(format!(
                 "VAD frame must be {} samples, got {}",
@@ -112,15 +254,14 @@ impl SileroVad {
         Ok(probability)
     }
 
-    /// Returns true if the probability exceeds the speech threshold.
-    pub fn is_speech(prob: f32) -> bool {
-        prob >= SPEECH_THRESHOLD
+    fn is_speech(&mut self, prob: f32) -> bool {
+        self.gate.is_speech(prob)
     }
 
-    /// Reset LSTM states (call between recording sessions).
-    pub fn reset(&mut self) {
+    fn reset(&mut self) {
         self.h_state = vec![0.0f32; STATE_SIZE];
         self.c_state = vec![0.0f32; STATE_SIZE];
+        self.gate.reset();
     }
 }
 
@@ -144,26 +285,35 @@ pub enum SpeechEvent {
     SpeechStart,
     /// User stopped speaking (after trailing silence buffer).
     SpeechEnd,
-    /// 5 seconds elapsed with no speech.
+    /// The no-speech timeout elapsed without any speech.
     NoSpeech,
-    /// 10 seconds elapsed with no speech — auto-stop.
+    /// The auto-stop timeout elapsed without any speech — auto-stop.
     Timeout,
+    /// Speech was followed by `stop_silence_ms` of silence and
+    /// `stop_on_silence` is on — end the recording.
+    UtteranceEnd,
 }
 
 /// Tracks speech boundaries and silence timeouts.
 pub struct SpeechDetector {
+    config: VadConfig,
     state: SpeechState,
     trailing_silence_start: Option<Instant>,
+    /// When the last speech ended (start of its trailing silence), while
+    /// waiting for `stop_silence_ms` in stop-on-silence mode.
+    speech_end: Option<Instant>,
     recording_start: Option<Instant>,
     first_speech_detected: bool,
     no_speech_emitted: bool,
 }
 
 impl SpeechDetector {
-    pub fn new() -> Self {
+    pub fn new(config: &VadConfig) -> Self {
         Self {
+            config: config.clone(),
             state: SpeechState::Silence,
             trailing_silence_start: None,
+            speech_end: None,
             recording_start: None,
             first_speech_detected: false,
             no_speech_emitted: false,
@@ -174,6 +324,7 @@ impl SpeechDetector {
     pub fn start(&mut self) {
         self.state = SpeechState::Silence;
         self.trailing_silence_start = None;
+        self.speech_end = None;
         self.recording_start = Some(Instant::now());
         self.first_speech_detected = false;
         self.no_speech_emitted = false;
@@ -188,13 +339,22 @@ impl SpeechDetector {
                 if is_speech {
                     self.state = SpeechState::Speech;
                     self.first_speech_detected = true;
+                    self.speech_end = None;
                     events.push(SpeechEvent::SpeechStart);
+                } else if let Some(end) = self.speech_end {
+                    if end.elapsed().as_millis() as u64 >= self.config.stop_silence_ms {
+                        // Emit once
+                        self.speech_end = None;
+                        events.push(SpeechEvent::UtteranceEnd);
+                    }
                 } else if let Some(start) = self.recording_start {
                     let elapsed_ms = start.elapsed().as_millis() as u64;
 
-                    if elapsed_ms >= AUTO_STOP_TIMEOUT_MS && !self.first_speech_detected {
+                    if elapsed_ms >= self.config.auto_stop_timeout_ms
+                        && !self.first_speech_detected
+                    {
                         events.push(SpeechEvent::Timeout);
-                    } else if elapsed_ms >= NO_SPEECH_TIMEOUT_MS
+                    } else if elapsed_ms >= self.config.no_speech_timeout_ms
                         && !self.first_speech_detected
                         && !self.no_speech_emitted
                     {
@@ -217,9 +377,12 @@ impl SpeechDetector {
                     self.state = SpeechState::Speech;
                     self.trailing_silence_start = None;
                 } else if let Some(start) = self.trailing_silence_start {
-                    if start.elapsed().as_millis() as u64 >= TRAILING_SILENCE_MS {
+                    if start.elapsed().as_millis() as u64 >= self.config.trailing_silence_ms {
                         self.state = SpeechState::Silence;
                         self.trailing_silence_start = None;
+                        if self.config.stop_on_silence {
+                            self.speech_end = Some(start);
+                        }
                         events.push(SpeechEvent::SpeechEnd);
                     }
                 }
@@ -234,5 +397,62 @@ impl SpeechDetector {
     }
 }
 
+// --------------- Speech audio collection ---------------
+
+/// Picks the audio that goes into the speech buffer: frames while speech is
+/// active, the last `pre_roll_ms` before speech starts (the onset that
+/// triggered detection and any quiet lead-in), and `post_roll_ms` after it ends.
+pub struct SpeechCollector {
+    pre_roll: VecDeque<f32>,
+    pre_roll_samples: usize,
+    post_roll_samples: usize,
+    /// Post-roll samples still to be kept after the last speech end.
+    post_roll_remaining: usize,
+}
+
+impl SpeechCollector {
+    pub fn new(config: &VadConfig) -> Self {
+        let pre_roll_samples = config.pre_roll_ms as usize * SAMPLES_PER_MS;
+        Self {
+            pre_roll: VecDeque::with_capacity(pre_roll_samples),
+            pre_roll_samples,
+            post_roll_samples: config.post_roll_ms as usize * SAMPLES_PER_MS,
+            post_roll_remaining: 0,
+        }
+    }
+
+    /// Feed a frame along with the detector state and events it produced.
+    /// Audio to keep is appended to `speech`.
+    pub fn push(
+        &mut self,
+        frame: &[f32],
+        state: SpeechState,
+        events: &[SpeechEvent],
+        speech: &mut Vec<f32>,
+    ) {
+        if events.contains(&SpeechEvent::SpeechStart) {
+            speech.extend(self.pre_roll.drain(..));
+            self.post_roll_remaining = 0;
+        }
+        if events.contains(&SpeechEvent::SpeechEnd) {
+            self.post_roll_remaining = self.post_roll_samples;
+        }
+
+        if state != SpeechState::Silence {
+            speech.extend_from_slice(frame);
+            return;
+        }
+
+        // Silence: post-roll first, the rest becomes pre-roll for the next onset
+        let post = self.post_roll_remaining.min(frame.len());
+        speech.extend_from_slice(&frame[..post]);
+        self.post_roll_remaining -= post;
+
+        self.pre_roll.extend(&frame[post..]);
+        let excess = self.pre_roll.len().saturating_sub(self.pre_roll_samples);
+        self.pre_roll.drain(..excess);
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,136 @@
+// Energy-based fallback VAD.
+//
+// Used when the Silero model is missing or fails to load, so silence is still
+// trimmed before it reaches Whisper. A frame's speech probability comes from
+// its energy above an adaptive noise floor; frames with a noise-like
+// zero-crossing rate are discounted. Much cruder than Silero, but pure Rust.
+
+use super::{SpeechGate, VadConfig, VoiceActivity, FRAME_SIZE};
+
+/// Energy (dBFS) below which a frame is never speech, whatever the noise floor.
+const MIN_SPEECH_DB: f32 = -55.0;
+
+/// Energy assigned to digital silence, so the log stays finite.
+const SILENCE_DB: f32 = -100.0;
+
+/// SNR (dB) at and below which a frame has speech probability 0.
+const SILENCE_SNR_DB: f32 = 3.0;
+
+/// SNR (dB) at and above which a frame has speech probability 1.
+const SPEECH_SNR_DB: f32 = 12.0;
+
+/// Zero-crossing rate (crossings per sample) above which a frame sounds
+/// like broadband noise rather than voiced speech.
+const NOISE_ZCR: f32 = 0.35;
+
+/// Probability multiplier for noise-like frames. Keeps hiss below the
+/// default start threshold but above the stop threshold, so fricatives
+/// inside an utterance do not end it.
+const NOISE_ZCR_PENALTY: f32 = 0.4;
+
+/// Noise floor smoothing per frame when the level drops (tracks quickly).
+const FLOOR_FALL_RATE: f32 = 0.2;
+
+/// Noise floor smoothing per frame when the level rises outside speech.
+const FLOOR_RISE_RATE: f32 = 0.05;
+
+/// Noise floor smoothing per frame when the level rises during speech, slow
+/// enough that a sentence does not become the floor but a new fan noise does.
+const FLOOR_RISE_RATE_SPEECH: f32 = 0.005;
+
+/// Energy/zero-crossing voice activity detector with an adaptive noise floor.
+pub struct EnergyVad {
+    /// Estimated background level (dBFS), None until the first frame.
+    noise_floor_db: Option<f32>,
+    gate: SpeechGate,
+}
+
+impl EnergyVad {
+    pub fn new(config: &VadConfig) -> Self {
+        Self {
+            noise_floor_db: None,
+            gate: SpeechGate::new(config),
+        }
+    }
+
+    /// Current noise floor estimate (dBFS).
+    pub fn noise_floor_db(&self) -> Option<f32> {
+        self.noise_floor_db
+    }
+
+    fn update_noise_floor(&mut self, energy_db: f32, speaking: bool) {
+        let floor = self.noise_floor_db.get_or_insert(energy_db);
+        let rate = if energy_db < *floor {
+            FLOOR_FALL_RATE
+        } else if speaking {
+            FLOOR_RISE_RATE_SPEECH
+        } else {
+            FLOOR_RISE_RATE
+        };
+        *floor += (energy_db - *floor) * rate;
+    }
+}
+
+/// Frame energy in dBFS.
+pub fn energy_db(frame: &[f32]) -> f32 {
+    let rms = crate::audio::capture::compute_rms(frame);
+    if rms > 0.0 {
+        (20.0 * rms.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+/// Fraction of adjacent sample pairs that change sign.
+pub fn zero_crossing_rate(frame: &[f32]) -> f32 {
+    if frame.len() < 2 {
+        return 0.0;
+    }
+    let crossings = frame
+        .windows(2)
+        .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+        .count();
+    crossings as f32 / (frame.len() - 1) as f32
+}
+
+impl VoiceActivity for EnergyVad {
+    fn process_frame(&mut self, audio: &[f32]) -> Result<f32, String> {
+        if audio.len() != FRAME_SIZE {
+            return Err(format!(
+                "VAD frame must be {} samples, got {}",
+                FRAME_SIZE,
+                audio.len()
+            ));
+        }
+
+        let energy = energy_db(audio);
+        let floor = self.noise_floor_db.unwrap_or(energy);
+
+        let mut probability = if energy < MIN_SPEECH_DB {
+            0.0
+        } else {
+            ((energy - floor - SILENCE_SNR_DB) / (SPEECH_SNR_DB - SILENCE_SNR_DB)).clamp(0.0, 1.0)
+        };
+        if zero_crossing_rate(audio) > NOISE_ZCR {
+            probability *= NOISE_ZCR_PENALTY;
+        }
+
+        // The floor follows the level, slowly while the gate says speech
+        let speaking = self.gate.is_speaking();
+        self.update_noise_floor(energy, speaking);
+
+        Ok(probability)
+    }
+
+    fn is_speech(&mut self, prob: f32) -> bool {
+        self.gate.is_speech(prob)
+    }
+
+    fn reset(&mut self) {
+        self.noise_floor_db = None;
+        self.gate.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,160 @@
+use super::*;
+
+/// A 16 kHz sine frame of the given frequency and amplitude.
+fn tone(freq_hz: f32, amplitude: f32) -> Vec<f32> {
+    (0..FRAME_SIZE)
+        .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq_hz * i as f32 / 16000.0).sin())
+        .collect()
+}
+
+/// Deterministic white-ish noise frame (LCG), so tests are repeatable.
+fn noise(amplitude: f32, seed: &mut u32) -> Vec<f32> {
+    (0..FRAME_SIZE)
+        .map(|_| {
+            *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            amplitude * ((*seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0)
+        })
+        .collect()
+}
+
+fn vad() -> EnergyVad {
+    EnergyVad::new(&VadConfig::default())
+}
+
+// --------------- Feature tests ---------------
+
+#[test]
+fn energy_db_of_full_scale_and_silence() {
+    let full_scale = vec![1.0; FRAME_SIZE];
+    assert!(energy_db(&full_scale).abs() < 0.01);
+    assert_eq!(energy_db(&[0.0; FRAME_SIZE]), SILENCE_DB);
+    assert!((energy_db(&vec![0.1; FRAME_SIZE]) + 20.0).abs() < 0.01);
+}
+
+#[test]
+fn zero_crossing_rate_values() {
+    assert_eq!(zero_crossing_rate(&[0.5; 10]), 0.0);
+    let alternating: Vec<f32> = (0..10)
+        .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+        .collect();
+    assert_eq!(zero_crossing_rate(&alternating), 1.0);
+    assert_eq!(zero_crossing_rate(&[1.0]), 0.0);
+    // 200 Hz tone crosses zero 400 times per second
+    let zcr = zero_crossing_rate(&tone(200.0, 0.5));
+    assert!((zcr - 400.0 / 16000.0).abs() < 0.01, "zcr {}", zcr);
+}
+
+// --------------- EnergyVad tests ---------------
+
+#[test]
+fn rejects_wrong_frame_size() {
+    let mut vad = vad();
+    assert!(vad.process_frame(&[0.0; 100]).is_err());
+}
+
+#[test]
+fn digital_silence_is_not_speech() {
+    let mut vad = vad();
+    for _ in 0..20 {
+        let prob = vad.process_frame(&[0.0; FRAME_SIZE]).unwrap();
+        assert_eq!(prob, 0.0);
+        assert!(!vad.is_speech(prob));
+    }
+}
+
+#[test]
+fn voice_over_quiet_room_is_speech() {
+    let mut vad = vad();
+    let mut seed = 1;
+    for _ in 0..50 {
+        let prob = vad.process_frame(&noise(0.001, &mut seed)).unwrap();
+        assert!(!vad.is_speech(prob));
+    }
+
+    let prob = vad.process_frame(&tone(200.0, 0.2)).unwrap();
+    assert_eq!(prob, 1.0);
+    assert!(vad.is_speech(prob));
+}
+
+#[test]
+fn quiet_tone_below_absolute_minimum_is_not_speech() {
+    let mut vad = vad();
+    for _ in 0..10 {
+        vad.process_frame(&[0.0; FRAME_SIZE]).unwrap();
+    }
+    // -66 dBFS: far above the floor but below MIN_SPEECH_DB
+    let prob = vad.process_frame(&tone(200.0, 0.0007)).unwrap();
+    assert_eq!(prob, 0.0);
+}
+
+#[test]
+fn noise_floor_adapts_to_steady_noise() {
+    let mut vad = vad();
+    let mut seed = 7;
+    for _ in 0..20 {
+        vad.process_frame(&noise(0.001, &mut seed)).unwrap();
+    }
+    let quiet_floor = vad.noise_floor_db().unwrap();
+
+    // A fan switches on: taken for speech at first, background after ~15s
+    let mut last = 1.0;
+    for _ in 0..500 {
+        last = vad.process_frame(&tone(120.0, 0.05)).unwrap();
+        vad.is_speech(last);
+    }
+    assert!(vad.noise_floor_db().unwrap() > quiet_floor + 20.0);
+    assert_eq!(last, 0.0);
+}
+
+#[test]
+fn floor_rises_slowly_during_speech() {
+    let mut vad = vad();
+    let mut seed = 3;
+    for _ in 0..20 {
+        vad.process_frame(&noise(0.001, &mut seed)).unwrap();
+    }
+
+    // One second of speech keeps its probability
+    for _ in 0..31 {
+        let prob = vad.process_frame(&tone(200.0, 0.2)).unwrap();
+        assert!(vad.is_speech(prob));
+    }
+    let prob = vad.process_frame(&tone(200.0, 0.2)).unwrap();
+    assert_eq!(prob, 1.0);
+}
+
+#[test]
+fn noise_like_frames_are_discounted() {
+    let mut vad = vad();
+    for _ in 0..10 {
+        vad.process_frame(&[0.0; FRAME_SIZE]).unwrap();
+    }
+    let mut seed = 11;
+    let hiss = noise(0.2, &mut seed);
+    assert!(zero_crossing_rate(&hiss) > NOISE_ZCR);
+    let prob = vad.process_frame(&hiss).unwrap();
+    assert_eq!(prob, NOISE_ZCR_PENALTY);
+    assert!(!vad.is_speech(prob));
+}
+
+#[test]
+fn floor_falls_quickly_when_noise_stops() {
+    let mut vad = vad();
+    for _ in 0..50 {
+        vad.process_frame(&tone(120.0, 0.05)).unwrap();
+    }
+    for _ in 0..30 {
+        vad.process_frame(&[0.0; FRAME_SIZE]).unwrap();
+    }
+    assert!(vad.noise_floor_db().unwrap() < -90.0);
+}
+
+#[test]
+fn reset_clears_noise_floor_and_gate() {
+    let mut vad = vad();
+    vad.process_frame(&tone(200.0, 0.2)).unwrap();
+    assert!(vad.is_speech(1.0));
+    vad.reset();
+    assert_eq!(vad.noise_floor_db(), None);
+    assert!(!vad.is_speech(0.4));
+}
@@ -4,13 +4,13 @@ use super::*;
 
 #[test]
 fn detector_starts_in_silence() {
-    let detector = SpeechDetector::new();
+    let detector = SpeechDetector::new(&VadConfig::default());
     assert_eq!(detector.state(), SpeechState::Silence);
 }
 
 #[test]
 fn detector_start_resets_state() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     // Move to speech
     detector.start();
     detector.update(true);
@@ -23,7 +23,7 @@ fn detector_start_resets_state() {
 
 #[test]
 fn silence_to_speech_transition() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     detector.start();
 
     let events = detector.update(true);
@@ -33,7 +33,7 @@ fn silence_to_speech_transition() {
 
 #[test]
 fn speech_to_trailing_silence() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     detector.start();
 
     // Enter speech
@@ -48,7 +48,7 @@ fn speech_to_trailing_silence() {
 
 #[test]
 fn trailing_silence_resumes_to_speech() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     detector.start();
 
     // Enter speech → trailing silence → back to speech
@@ -63,7 +63,7 @@ fn trailing_silence_resumes_to_speech() {
 
 #[test]
 fn trailing_silence_expires_to_speech_end() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     detector.start();
 
     // Enter speech
@@ -83,7 +83,7 @@ fn trailing_silence_expires_to_speech_end() {
 
 #[test]
 fn continuous_silence_stays_silent() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     detector.start();
 
     for _ in 0..10 {
@@ -96,7 +96,7 @@ fn continuous_silence_stays_silent() {
 
 #[test]
 fn continuous_speech_stays_in_speech() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     detector.start();
 
     // First update: SpeechStart
@@ -113,7 +113,7 @@ fn continuous_speech_stays_in_speech() {
 
 #[test]
 fn no_events_without_start() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     // Without calling start(), recording_start is None
     let events = detector.update(false);
     assert!(events.is_empty());
@@ -121,7 +121,7 @@ fn no_events_without_start() {
 
 #[test]
 fn speech_after_detection_prevents_timeout() {
-    let mut detector = SpeechDetector::new();
+    let mut detector = SpeechDetector::new(&VadConfig::default());
     detector.start();
 
     // Detect speech
@@ -139,13 +139,354 @@ fn speech_after_detection_prevents_timeout() {
     }
 }
 
-// --------------- SileroVad static method tests ---------------
+#[test]
+fn configured_trailing_silence_is_used() {
+    let config = VadConfig {
+        trailing_silence_ms: 100,
+        ..VadConfig::default()
+    };
+    let mut detector = SpeechDetector::new(&config);
+    detector.start();
+
+    detector.update(true);
+    detector.update(false);
+    std::thread::sleep(std::time::Duration::from_millis(150));
+
+    assert_eq!(detector.update(false), vec![SpeechEvent::SpeechEnd]);
+}
+
+#[test]
+fn configured_timeouts_are_used() {
+    let config = VadConfig {
+        no_speech_timeout_ms: 50,
+        auto_stop_timeout_ms: 150,
+        ..VadConfig::default()
+    };
+    let mut detector = SpeechDetector::new(&config);
+    detector.start();
+
+    std::thread::sleep(std::time::Duration::from_millis(60));
+    assert_eq!(detector.update(false), vec![SpeechEvent::NoSpeech]);
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(detector.update(false), vec![SpeechEvent::Timeout]);
+}
+
+// --------------- VadConfig tests ---------------
+
+#[test]
+fn default_config_matches_previous_constants() {
+    let config = VadConfig::default();
+    assert_eq!(config.start_threshold, 0.5);
+    assert_eq!(config.trailing_silence_ms, 500);
+    assert_eq!(config.no_speech_timeout_ms, 5_000);
+    assert_eq!(config.auto_stop_timeout_ms, 10_000);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn config_validation() {
+    let invalid = [
+        VadConfig {
+            start_threshold: 0.0,
+            ..VadConfig::default()
+        },
+        VadConfig {
+            start_threshold: 1.5,
+            ..VadConfig::default()
+        },
+        VadConfig {
+            stop_threshold: 0.6,
+            ..VadConfig::default()
+        },
+        VadConfig {
+            start_threshold: f32::NAN,
+            ..VadConfig::default()
+        },
+        VadConfig {
+            trailing_silence_ms: 10,
+            ..VadConfig::default()
+        },
+        VadConfig {
+            no_speech_timeout_ms: 20_000,
+            ..VadConfig::default()
+        },
+    ];
+    for config in &invalid {
+        assert!(config.validate().is_err(), "accepted {:?}", config);
+    }
+}
+
+#[test]
+fn partial_config_fills_defaults() {
+    let config: VadConfig = serde_json::from_str(r#"{ "startThreshold": 0.7 }"#).unwrap();
+    assert_eq!(config.start_threshold, 0.7);
+    assert_eq!(config.stop_threshold, VadConfig::default().stop_threshold);
+}
+
+// --------------- SpeechGate tests ---------------
 
 #[test]
 fn is_speech_threshold() {
-    assert!(!SileroVad::is_speech(0.0));
-    assert!(!SileroVad::is_speech(0.49));
-    assert!(SileroVad::is_speech(0.5));
-    assert!(SileroVad::is_speech(0.99));
-    assert!(SileroVad::is_speech(1.0));
+    let config = VadConfig::default();
+    // Entering speech uses the start threshold
+    assert!(!SpeechGate::new(&config).is_speech(0.0));
+    assert!(!SpeechGate::new(&config).is_speech(0.49));
+    assert!(SpeechGate::new(&config).is_speech(0.5));
+    assert!(SpeechGate::new(&config).is_speech(0.99));
+    assert!(SpeechGate::new(&config).is_speech(1.0));
+}
+
+#[test]
+fn gate_applies_hysteresis() {
+    let config = VadConfig {
+        start_threshold: 0.6,
+        stop_threshold: 0.3,
+        ..VadConfig::default()
+    };
+    let mut gate = SpeechGate::new(&config);
+
+    // Between the thresholds: stays silent until the start threshold...
+    assert!(!gate.is_speech(0.45));
+    assert!(gate.is_speech(0.65));
+    // ...then stays in speech until below the stop threshold
+    assert!(gate.is_speech(0.45));
+    assert!(gate.is_speech(0.3));
+    assert!(!gate.is_speech(0.29));
+    assert!(!gate.is_speech(0.45));
+}
+
+#[test]
+fn gate_reset_returns_to_silence() {
+    let mut gate = SpeechGate::new(&VadConfig::default());
+    assert!(gate.is_speech(0.9));
+    gate.reset();
+    assert!(!gate.is_speech(0.4));
+}
+
+// --------------- Stop-on-silence tests ---------------
+
+fn stop_on_silence_config() -> VadConfig {
+    VadConfig {
+        trailing_silence_ms: 100,
+        stop_on_silence: true,
+        stop_silence_ms: 200,
+        ..VadConfig::default()
+    }
+}
+
+#[test]
+fn utterance_end_after_post_speech_silence() {
+    let mut detector = SpeechDetector::new(&stop_on_silence_config());
+    detector.start();
+
+    detector.update(true);
+    detector.update(false);
+    std::thread::sleep(std::time::Duration::from_millis(120));
+    assert_eq!(detector.update(false), vec![SpeechEvent::SpeechEnd]);
+    // Trailing silence is over but the stop delay is not
+    assert!(detector.update(false).is_empty());
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(detector.update(false), vec![SpeechEvent::UtteranceEnd]);
+    // Only once
+    assert!(detector.update(false).is_empty());
+}
+
+#[test]
+fn resumed_speech_cancels_utterance_end() {
+    let mut detector = SpeechDetector::new(&stop_on_silence_config());
+    detector.start();
+
+    detector.update(true);
+    detector.update(false);
+    std::thread::sleep(std::time::Duration::from_millis(120));
+    assert_eq!(detector.update(false), vec![SpeechEvent::SpeechEnd]);
+
+    // The user keeps talking before the stop delay runs out
+    assert_eq!(detector.update(true), vec![SpeechEvent::SpeechStart]);
+    std::thread::sleep(std::time::Duration::from_millis(120));
+    assert!(detector.update(true).is_empty());
+}
+
+#[test]
+fn no_utterance_end_when_disabled() {
+    let config = VadConfig {
+        stop_on_silence: false,
+        ..stop_on_silence_config()
+    };
+    let mut detector = SpeechDetector::new(&config);
+    detector.start();
+
+    detector.update(true);
+    detector.update(false);
+    std::thread::sleep(std::time::Duration::from_millis(350));
+    assert_eq!(detector.update(false), vec![SpeechEvent::SpeechEnd]);
+    assert!(detector.update(false).is_empty());
+}
+
+#[test]
+fn no_utterance_end_before_any_speech() {
+    let mut detector = SpeechDetector::new(&stop_on_silence_config());
+    detector.start();
+
+    std::thread::sleep(std::time::Duration::from_millis(250));
+    assert!(detector.update(false).is_empty());
+}
+
+#[test]
+fn stop_delay_must_cover_trailing_silence() {
+    let config = VadConfig {
+        stop_silence_ms: 50,
+        ..stop_on_silence_config()
+    };
+    assert!(config.validate().is_err());
+}
+
+// --------------- SpeechCollector tests ---------------
+
+/// Frames whose samples count up from `start`, so gaps and duplicates show.
+fn numbered_frames(start: usize, count: usize) -> Vec<Vec<f32>> {
+    (0..count)
+        .map(|f| {
+            (0..FRAME_SIZE)
+                .map(|i| (start + f * FRAME_SIZE + i) as f32)
+                .collect()
+        })
+        .collect()
+}
+
+fn is_contiguous(samples: &[f32]) -> bool {
+    samples.windows(2).all(|w| w[1] == w[0] + 1.0)
+}
+
+#[test]
+fn pre_roll_keeps_onset_samples() {
+    let config = VadConfig::default();
+    let mut detector = SpeechDetector::new(&config);
+    let mut collector = SpeechCollector::new(&config);
+    detector.start();
+
+    // 20 frames of lead-in (640ms), then speech
+    let frames = numbered_frames(0, 30);
+    let mut speech = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let events = detector.update(i >= 20);
+        collector.push(frame, detector.state(), &events, &mut speech);
+    }
+
+    let pre_roll = 300 * 16;
+    let onset = 20 * FRAME_SIZE;
+    assert_eq!(speech.len(), pre_roll + 10 * FRAME_SIZE);
+    assert_eq!(speech[0], (onset - pre_roll) as f32);
+    assert!(is_contiguous(&speech));
+}
+
+#[test]
+fn short_lead_in_is_kept_entirely() {
+    let config = VadConfig::default();
+    let mut detector = SpeechDetector::new(&config);
+    let mut collector = SpeechCollector::new(&config);
+    detector.start();
+
+    // Speech starts on the third frame, before the pre-roll is full
+    let mut speech = Vec::new();
+    for (i, frame) in numbered_frames(0, 5).iter().enumerate() {
+        let events = detector.update(i >= 2);
+        collector.push(frame, detector.state(), &events, &mut speech);
+    }
+
+    assert_eq!(speech.len(), 5 * FRAME_SIZE);
+    assert_eq!(speech[0], 0.0);
+    assert!(is_contiguous(&speech));
+}
+
+#[test]
+fn nothing_is_kept_without_speech() {
+    let config = VadConfig::default();
+    let mut collector = SpeechCollector::new(&config);
+
+    let mut speech = Vec::new();
+    for frame in numbered_frames(0, 50) {
+        collector.push(&frame, SpeechState::Silence, &[], &mut speech);
+    }
+    assert!(speech.is_empty());
+}
+
+#[test]
+fn post_roll_follows_speech_end() {
+    let config = VadConfig {
+        pre_roll_ms: 0,
+        post_roll_ms: 48, // 1.5 frames
+        ..VadConfig::default()
+    };
+    let mut collector = SpeechCollector::new(&config);
+    let frames = numbered_frames(0, 6);
+
+    let mut speech = Vec::new();
+    collector.push(
+        &frames[0],
+        SpeechState::Speech,
+        &[SpeechEvent::SpeechStart],
+        &mut speech,
+    );
+    collector.push(&frames[1], SpeechState::TrailingSilence, &[], &mut speech);
+    collector.push(
+        &frames[2],
+        SpeechState::Silence,
+        &[SpeechEvent::SpeechEnd],
+        &mut speech,
+    );
+    for frame in &frames[3..] {
+        collector.push(frame, SpeechState::Silence, &[], &mut speech);
+    }
+
+    let post_roll = 48 * 16;
+    assert_eq!(speech.len(), 2 * FRAME_SIZE + post_roll);
+    assert!(is_contiguous(&speech));
+}
+
+#[test]
+fn resumed_speech_does_not_duplicate_samples() {
+    let config = VadConfig {
+        pre_roll_ms: 300,
+        post_roll_ms: 16, // half a frame
+        ..VadConfig::default()
+    };
+    let mut collector = SpeechCollector::new(&config);
+    let frames = numbered_frames(0, 5);
+
+    let mut speech = Vec::new();
+    collector.push(
+        &frames[0],
+        SpeechState::Speech,
+        &[SpeechEvent::SpeechStart],
+        &mut speech,
+    );
+    collector.push(
+        &frames[1],
+        SpeechState::Silence,
+        &[SpeechEvent::SpeechEnd],
+        &mut speech,
+    );
+    collector.push(&frames[2], SpeechState::Silence, &[], &mut speech);
+    collector.push(
+        &frames[3],
+        SpeechState::Speech,
+        &[SpeechEvent::SpeechStart],
+        &mut speech,
+    );
+
+    // Post-roll and pre-roll meet without overlap or gap
+    assert_eq!(speech.len(), 4 * FRAME_SIZE);
+    assert!(is_contiguous(&speech));
+}
+
+#[test]
+fn roll_lengths_are_bounded() {
+    let config = VadConfig {
+        pre_roll_ms: 5_000,
+        ..VadConfig::default()
+    };
+    assert!(config.validate().is_err());
 }
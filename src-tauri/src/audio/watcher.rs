@@ -0,0 +1,164 @@
+// Input device hot-plug monitoring.
+//
+// cpal has no device change notifications, so the input devices are listed
+// periodically, while a recording runs or the audio settings are open, and
+// changes are sent to the frontend as `audio://devices-changed`. A recording
+// whose device goes away reopens capture by itself (see `recording_thread`).
+
+use super::capture;
+use serde::Serialize;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// How often the device list is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Devices connected and disconnected since the last check.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DevicesChangedEvent {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Changes from `old` to `new` device IDs, or None if the set is the same.
+pub fn diff(old: &[String], new: &[String]) -> Option<DevicesChangedEvent> {
+    let added: Vec<String> = new.iter().filter(|id| !old.contains(id)).cloned().collect();
+    let removed: Vec<String> = old.iter().filter(|id| !new.contains(id)).cloned().collect();
+    if added.is_empty() && removed.is_empty() {
+        None
+    } else {
+        Some(DevicesChangedEvent { added, removed })
+    }
+}
+
+/// Why device changes are wanted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interest {
+    Recording,
+    Settings,
+}
+
+#[derive(Default)]
+struct WatchState {
+    recording: bool,
+    settings: bool,
+    stop: bool,
+}
+
+impl WatchState {
+    fn active(&self) -> bool {
+        self.recording || self.settings
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<WatchState>,
+    wake: Condvar,
+}
+
+/// Background thread polling the input devices while a recording runs or
+/// the audio settings are open, and sleeping otherwise.
+#[derive(Default)]
+pub struct DeviceWatcher {
+    shared: Arc<Shared>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DeviceWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the watcher thread. Changes are sent as `audio://devices-changed`.
+    pub fn start<R: Runtime>(&self, app: AppHandle<R>) {
+        self.start_with(
+            POLL_INTERVAL,
+            capture::connected_device_ids,
+            move |change| {
+                let _ = app.emit("audio://devices-changed", change);
+            },
+        );
+    }
+
+    fn start_with(
+        &self,
+        interval: Duration,
+        list: impl Fn() -> Result<Vec<String>, String> + Send + 'static,
+        on_change: impl Fn(DevicesChangedEvent) + Send + 'static,
+    ) {
+        let shared = self.shared.clone();
+        let handle = thread::spawn(move || watch(&shared, interval, list, on_change));
+        *self.handle.lock().unwrap() = Some(handle);
+    }
+
+    /// Turn polling on or off for one reason; it runs while any reason holds.
+    pub fn set(&self, interest: Interest, active: bool) {
+        let mut state = self.shared.state.lock().unwrap();
+        match interest {
+            Interest::Recording => state.recording = active,
+            Interest::Settings => state.settings = active,
+        }
+        self.shared.wake.notify_all();
+    }
+
+    /// Stop the watcher thread and wait for it to exit.
+    pub fn stop(&self) {
+        self.shared.state.lock().unwrap().stop = true;
+        self.shared.wake.notify_all();
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// The watcher thread. Devices changed while nobody was watching are
+/// reported on the next check.
+fn watch(
+    shared: &Shared,
+    interval: Duration,
+    list: impl Fn() -> Result<Vec<String>, String>,
+    on_change: impl Fn(DevicesChangedEvent),
+) {
+    let mut known: Option<Vec<String>> = None;
+    loop {
+        let state = shared.state.lock().unwrap();
+        let state = shared
+            .wake
+            .wait_while(state, |s| !s.stop && !s.active())
+            .unwrap();
+        if state.stop {
+            return;
+        }
+        drop(state);
+
+        match list() {
+            Ok(current) => {
+                if let Some(change) = known.as_deref().and_then(|k| diff(k, &current)) {
+                    log::info!(
+                        "Input devices changed: added {:?}, removed {:?}",
+                        change.added,
+                        change.removed
+                    );
+                    on_change(change);
+                }
+                known = Some(current);
+            }
+            Err(e) => log::debug!("Device watcher: {}", e),
+        }
+
+        let state = shared.state.lock().unwrap();
+        let (state, _) = shared
+            .wake
+            .wait_timeout_while(state, interval, |s| !s.stop)
+            .unwrap();
+        if state.stop {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,135 @@
+use super::*;
+use std::sync::mpsc;
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn no_change_is_none() {
+    let devices = ids(&["ALSA:Mic", "ALSA:Headset"]);
+    assert_eq!(diff(&devices, &devices), None);
+}
+
+#[test]
+fn order_changes_are_ignored() {
+    assert_eq!(
+        diff(
+            &ids(&["ALSA:Mic", "ALSA:Headset"]),
+            &ids(&["ALSA:Headset", "ALSA:Mic"])
+        ),
+        None
+    );
+}
+
+#[test]
+fn reports_plugged_and_unplugged_devices() {
+    let change = diff(
+        &ids(&["ALSA:Mic", "ALSA:Headset"]),
+        &ids(&["ALSA:Mic", "ALSA:USB Mic"]),
+    )
+    .unwrap();
+    assert_eq!(change.added, ids(&["ALSA:USB Mic"]));
+    assert_eq!(change.removed, ids(&["ALSA:Headset"]));
+}
+
+#[test]
+fn all_devices_gone() {
+    let change = diff(&ids(&["ALSA:Mic"]), &[]).unwrap();
+    assert!(change.added.is_empty());
+    assert_eq!(change.removed, ids(&["ALSA:Mic"]));
+}
+
+/// A watcher polling every 10 ms a device list the test controls.
+struct TestWatcher {
+    watcher: DeviceWatcher,
+    devices: Arc<Mutex<Vec<String>>>,
+    polls: Arc<Mutex<usize>>,
+    changes: mpsc::Receiver<DevicesChangedEvent>,
+}
+
+impl TestWatcher {
+    fn start(devices: &[&str]) -> Self {
+        let watcher = DeviceWatcher::new();
+        let devices = Arc::new(Mutex::new(ids(devices)));
+        let polls = Arc::new(Mutex::new(0));
+        let (tx, changes) = mpsc::channel();
+        let (list, count) = (devices.clone(), polls.clone());
+        watcher.start_with(
+            Duration::from_millis(10),
+            move || {
+                *count.lock().unwrap() += 1;
+                Ok(list.lock().unwrap().clone())
+            },
+            move |change| tx.send(change).unwrap(),
+        );
+        Self {
+            watcher,
+            devices,
+            polls,
+            changes,
+        }
+    }
+
+    fn polls(&self) -> usize {
+        *self.polls.lock().unwrap()
+    }
+
+    fn wait_for_poll(&self) {
+        while self.polls() == 0 {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn next_change(&self) -> DevicesChangedEvent {
+        self.changes.recv_timeout(Duration::from_secs(5)).unwrap()
+    }
+}
+
+#[test]
+fn idle_watcher_does_not_poll() {
+    let test = TestWatcher::start(&["ALSA:Mic"]);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(test.polls(), 0);
+    test.watcher.stop();
+}
+
+#[test]
+fn reports_changes_while_active() {
+    let test = TestWatcher::start(&["ALSA:Mic"]);
+    test.watcher.set(Interest::Settings, true);
+    test.wait_for_poll();
+
+    test.devices
+        .lock()
+        .unwrap()
+        .push("ALSA:Headset".to_string());
+    assert_eq!(test.next_change().added, ids(&["ALSA:Headset"]));
+    test.watcher.stop();
+}
+
+#[test]
+fn changes_while_idle_are_reported_when_watching_resumes() {
+    let test = TestWatcher::start(&["ALSA:Mic"]);
+    test.watcher.set(Interest::Recording, true);
+    test.wait_for_poll();
+    test.watcher.set(Interest::Recording, false);
+    thread::sleep(Duration::from_millis(50));
+    let polls_when_idle = test.polls();
+
+    test.devices.lock().unwrap().clear();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(test.polls(), polls_when_idle);
+
+    test.watcher.set(Interest::Settings, true);
+    assert_eq!(test.next_change().removed, ids(&["ALSA:Mic"]));
+    test.watcher.stop();
+}
+
+#[test]
+fn stop_ends_an_active_watcher() {
+    let test = TestWatcher::start(&["ALSA:Mic"]);
+    test.watcher.set(Interest::Settings, true);
+    test.watcher.stop();
+    assert!(test.watcher.handle.lock().unwrap().is_none());
+}
@@ -0,0 +1,412 @@
+// Model catalogs.
+//
+// The Whisper, LLM and VAD catalogs are described by a JSON manifest. A default
+// manifest is bundled with the app. Organizations that cannot reach
+// huggingface.co can publish their own manifest and point `manifestUrl` at
+// it; when a minisign public key is configured the manifest must come with a
+// valid `<manifestUrl>.minisig` signature. A fetched manifest is validated and
+// cached as `models/catalog.json`, which then replaces the bundled one. Its
+// signature is kept next to it so the cache can be re-checked whenever the
+// configuration changes.
+//
+// Entries are paths relative to the manifest's base URL. Setting a mirror
+// base URL pulls the same paths from an internal artifact server instead.
+
+use crate::download::{DownloadState, ModelKind};
+use crate::settings::config::{write_atomic, SettingsState};
+use crate::user_models;
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Newest manifest schema this build understands.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Manifest bundled with the app.
+const DEFAULT_MANIFEST: &str = include_str!("catalog/default_manifest.json");
+
+/// Cached remote manifest inside `<app data>/models`.
+const CACHED_MANIFEST_FILENAME: &str = "catalog.json";
+
+/// Extension appended to the manifest URL to fetch its signature.
+const SIGNATURE_EXTENSION: &str = ".minisig";
+
+/// Timeout for fetching a manifest (it is a few KB).
+const FETCH_TIMEOUT_SECS: u64 = 30;
+
+/// A model catalog manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub version: u32,
+    /// URL that entry paths are relative to.
+    pub base_url: String,
+    #[serde(default)]
+    pub whisper: Vec<CatalogEntry>,
+    #[serde(default)]
+    pub llm: Vec<CatalogEntry>,
+    #[serde(default)]
+    pub vad: Vec<CatalogEntry>,
+}
+
+/// One downloadable model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    /// File name in the local models directory.
+    pub filename: String,
+    /// Download path relative to the base URL.
+    pub path: String,
+    /// Base URL for this entry when it is not hosted at the manifest's base URL.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    pub size_bytes: u64,
+    /// SHA-256 of the file, checked after download (None = not pinned).
+    #[serde(default)]
+    pub sha256: Option<String>,
+    pub description: String,
+}
+
+/// Where catalogs and model files come from. Persisted as part of the user settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CatalogConfig {
+    /// Remote manifest replacing the bundled one (None = bundled manifest).
+    pub manifest_url: Option<String>,
+    /// Base URL replacing the manifest's base URL for downloads.
+    pub mirror_url: Option<String>,
+    /// Base64 minisign public key remote manifests must be signed with.
+    pub public_key: Option<String>,
+}
+
+impl CatalogConfig {
+    /// Reject malformed URLs and keys.
+    pub fn validate(&self) -> Result<(), String> {
+        for url in [&self.manifest_url, &self.mirror_url].into_iter().flatten() {
+            validate_http_url(url)?;
+        }
+        if let Some(key) = &self.public_key {
+            PublicKey::from_base64(key.trim())
+                .map_err(|e| format!("Invalid manifest public key: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Manifest {
+    /// Parse and validate a manifest.
+    pub fn parse(json: &str) -> Result<Manifest, String> {
+        let manifest: Manifest =
+            serde_json::from_str(json).map_err(|e| format!("Invalid model manifest: {}", e))?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Check the manifest is usable: known version, safe file names and
+    /// paths, unique IDs and well-formed checksums.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version == 0 || self.version > MANIFEST_VERSION {
+            return Err(format!(
+                "Unsupported model manifest version {} (this app supports up to {})",
+                self.version, MANIFEST_VERSION
+            ));
+        }
+        validate_http_url(&self.base_url)?;
+
+        for (kind, entries) in [
+            (ModelKind::Whisper, &self.whisper),
+            (ModelKind::Llm, &self.llm),
+            (ModelKind::Vad, &self.vad),
+        ] {
+            let mut ids = HashSet::new();
+            let mut filenames = HashSet::new();
+            for entry in entries {
+                entry.validate()?;
+                if !ids.insert(entry.id.as_str()) {
+                    return Err(format!("Duplicate {:?} model ID '{}'", kind, entry.id));
+                }
+                if !filenames.insert(entry.filename.as_str()) {
+                    return Err(format!(
+                        "Duplicate {:?} model file name '{}'",
+                        kind, entry.filename
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Catalog entries of one kind.
+    pub fn entries(&self, kind: ModelKind) -> &[CatalogEntry] {
+        match kind {
+            ModelKind::Whisper => &self.whisper,
+            ModelKind::Llm => &self.llm,
+            ModelKind::Vad => &self.vad,
+        }
+    }
+
+    /// Look up a catalog entry.
+    pub fn find(&self, kind: ModelKind, model_id: &str) -> Option<&CatalogEntry> {
+        self.entries(kind).iter().find(|e| e.id == model_id)
+    }
+
+    /// Download URL of an entry, using `mirror` instead of the base URL if set.
+    pub fn download_url(&self, entry: &CatalogEntry, mirror: Option<&str>) -> String {
+        let base = mirror
+            .or(entry.base_url.as_deref())
+            .unwrap_or(&self.base_url);
+        format!("{}/{}", base.trim_end_matches('/'), entry.path)
+    }
+}
+
+impl CatalogEntry {
+    fn validate(&self) -> Result<(), String> {
+        let invalid = |what: &str| Err(format!("Model '{}' has an invalid {}", self.id, what));
+
+        if self.id.trim().is_empty() || self.id.starts_with(user_models::ID_PREFIX) {
+            return invalid("ID");
+        }
+        let filename_ok = !self.filename.is_empty()
+            && !self.filename.starts_with('.')
+            && !self.filename.contains(['/', '\\']);
+        if !filename_ok {
+            return invalid("file name");
+        }
+        let path_ok = !self.path.is_empty()
+            && !self.path.starts_with('/')
+            && !self.path.contains("://")
+            && !self.path.split('/').any(|part| part == "..");
+        if !path_ok {
+            return invalid("download path");
+        }
+        if let Some(base_url) = &self.base_url {
+            if validate_http_url(base_url).is_err() {
+                return invalid("base URL");
+            }
+        }
+        if self.size_bytes == 0 {
+            return invalid("size");
+        }
+        if let Some(sha256) = &self.sha256 {
+            if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+                return invalid("SHA-256");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_http_url(url: &str) -> Result<(), String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"));
+    match rest {
+        Some(host) if !host.is_empty() && !host.starts_with('/') => Ok(()),
+        _ => Err(format!("Not an http(s) URL: {}", url)),
+    }
+}
+
+/// The manifest bundled with the app.
+pub fn default_manifest() -> Manifest {
+    Manifest::parse(DEFAULT_MANIFEST).expect("bundled model manifest is valid")
+}
+
+/// Path of the cached remote manifest inside the app data directory.
+pub fn cached_manifest_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("models").join(CACHED_MANIFEST_FILENAME)
+}
+
+/// Signature of the cached manifest, present when it was fetched with a
+/// public key configured.
+fn cached_signature_path(app_data_dir: &Path) -> PathBuf {
+    let mut path = cached_manifest_path(app_data_dir).into_os_string();
+    path.push(SIGNATURE_EXTENSION);
+    PathBuf::from(path)
+}
+
+fn remove_cache(app_data_dir: &Path) -> Result<(), String> {
+    for path in [
+        cached_manifest_path(app_data_dir),
+        cached_signature_path(app_data_dir),
+    ] {
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove cached manifest: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete the cached manifest if `config` would not have accepted it: no
+/// manifest URL is configured, or a public key is configured and the cache has
+/// no signature that key accepts. Call at startup and whenever the
+/// configuration changes, so `load` only ever sees an acceptable cache.
+pub fn check_cache(app_data_dir: &Path, config: &CatalogConfig) -> Result<(), String> {
+    let path = cached_manifest_path(app_data_dir);
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let accepted = match (&config.manifest_url, &config.public_key) {
+        (None, _) => Err("no manifest URL configured".to_string()),
+        (Some(_), None) => Ok(()),
+        (Some(_), Some(key)) => fs::read_to_string(cached_signature_path(app_data_dir))
+            .map_err(|_| "it is not signed".to_string())
+            .and_then(|signature| {
+                let json = fs::read(&path).map_err(|e| e.to_string())?;
+                verify_signature(&json, &signature, key)
+            }),
+    };
+    if let Err(e) = accepted {
+        log::warn!("Discarding cached model manifest: {}", e);
+        remove_cache(app_data_dir)?;
+    }
+    Ok(())
+}
+
+/// The active manifest: the cached remote one if present and valid,
+/// otherwise the bundled one.
+pub fn load(app_data_dir: &Path) -> Manifest {
+    let path = cached_manifest_path(app_data_dir);
+    if !path.exists() {
+        return default_manifest();
+    }
+
+    match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| Manifest::parse(&json))
+    {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            log::warn!(
+                "Cached model manifest unusable, using the bundled one: {}",
+                e
+            );
+            default_manifest()
+        }
+    }
+}
+
+/// Check a minisign signature (the contents of a `.minisig` file) over `data`.
+pub fn verify_signature(data: &[u8], signature: &str, public_key: &str) -> Result<(), String> {
+    let public_key = PublicKey::from_base64(public_key.trim())
+        .map_err(|e| format!("Invalid manifest public key: {}", e))?;
+    let signature =
+        Signature::decode(signature).map_err(|e| format!("Invalid manifest signature: {}", e))?;
+    public_key
+        .verify(data, &signature, false)
+        .map_err(|e| format!("Model manifest signature check failed: {}", e))
+}
+
+/// Fetch the configured remote manifest, check its signature and cache it.
+/// Without a manifest URL the cache is removed and the bundled manifest used.
+/// Runs synchronously — call from a background thread.
+pub fn refresh(app_data_dir: &Path, config: &CatalogConfig) -> Result<Manifest, String> {
+    let path = cached_manifest_path(app_data_dir);
+    let Some(url) = &config.manifest_url else {
+        remove_cache(app_data_dir)?;
+        return Ok(default_manifest());
+    };
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let fetch = |url: &str| -> Result<String, String> {
+        let response = client
+            .get(url)
+            .send()
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Failed to fetch {}: HTTP {}",
+                url,
+                response.status()
+            ));
+        }
+        response
+            .text()
+            .map_err(|e| format!("Failed to read {}: {}", url, e))
+    };
+
+    let json = fetch(url)?;
+    let signature = match &config.public_key {
+        Some(key) => {
+            let signature = fetch(&format!("{}{}", url, SIGNATURE_EXTENSION))?;
+            verify_signature(json.as_bytes(), &signature, key)?;
+            Some(signature)
+        }
+        None => {
+            log::warn!("No manifest public key configured, accepting unsigned manifest");
+            None
+        }
+    };
+    let manifest = Manifest::parse(&json)?;
+
+    // Old signature first: a crash in between leaves an unsigned cache, which
+    // `check_cache` discards while a key is configured
+    remove_cache(app_data_dir)?;
+    write_atomic(&path, json.as_bytes())?;
+    if let Some(signature) = signature {
+        write_atomic(&cached_signature_path(app_data_dir), signature.as_bytes())?;
+    }
+
+    log::info!(
+        "Model manifest refreshed from {} ({} Whisper, {} LLM, {} VAD models)",
+        url,
+        manifest.whisper.len(),
+        manifest.llm.len(),
+        manifest.vad.len()
+    );
+    Ok(manifest)
+}
+
+// --------------- Tauri commands ---------------
+
+#[tauri::command]
+pub fn get_model_catalog_config(downloads: tauri::State<'_, DownloadState>) -> CatalogConfig {
+    downloads.catalog_config.lock().unwrap().clone()
+}
+
+/// Change where catalogs come from. A cached manifest the new configuration
+/// would not accept (e.g. unsigned, once a public key is set) is discarded.
+#[tauri::command]
+pub fn set_model_catalog_config(
+    app: AppHandle,
+    config: CatalogConfig,
+    downloads: tauri::State<'_, DownloadState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    config.validate()?;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data directory unavailable: {}", e))?;
+    settings.update(|s| s.model_catalog = config.clone())?;
+    check_cache(&data_dir, &config)?;
+    *downloads.catalog_config.lock().unwrap() = config;
+    Ok(())
+}
+
+/// Re-fetch the remote manifest (or drop back to the bundled one).
+#[tauri::command(async)]
+pub fn refresh_model_catalog(
+    app: AppHandle,
+    downloads: tauri::State<'_, DownloadState>,
+) -> Result<Manifest, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data directory unavailable: {}", e))?;
+    let config = downloads.catalog_config.lock().unwrap().clone();
+    refresh(&data_dir, &config)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,344 @@
+use super::*;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+const SIGNED_MANIFEST: &str = include_str!("fixtures/signed_manifest.json");
+const SIGNED_MANIFEST_SIG: &str = include_str!("fixtures/signed_manifest.json.minisig");
+
+/// Public key the fixture manifest is signed with.
+const TEST_PUBLIC_KEY: &str = "RWQBI0VniavN78bmP8gxvu1xps9ICzJBUvOQiVYGGQn/mwCl+98PwDIU";
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("catalog")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// Serve `files` (path -> body) over HTTP on localhost for `requests`
+/// requests; unknown paths get a 404. Returns the base URL.
+fn serve(files: Vec<(&'static str, String)>, requests: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        for _ in 0..requests {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            let _ = reader.read_line(&mut request_line);
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or("");
+            let response = match files.iter().find(|(p, _)| *p == path) {
+                Some((_, body)) => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                ),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    base
+}
+
+fn valid_manifest() -> Manifest {
+    Manifest::parse(SIGNED_MANIFEST).unwrap()
+}
+
+#[test]
+fn bundled_manifest_is_valid() {
+    let manifest = default_manifest();
+    assert_eq!(manifest.version, MANIFEST_VERSION);
+    assert_eq!(manifest.whisper.len(), 6);
+    assert!(manifest.find(ModelKind::Whisper, "base.en").is_some());
+    assert!(manifest.find(ModelKind::Llm, "phi3-mini-q4").is_some());
+    // Every Whisper model is checksum-verified
+    assert!(manifest.whisper.iter().all(|e| e.sha256.is_some()));
+}
+
+#[test]
+fn bundled_manifest_keeps_huggingface_urls() {
+    let manifest = default_manifest();
+    let entry = manifest.find(ModelKind::Whisper, "tiny.en").unwrap();
+    assert_eq!(
+        manifest.download_url(entry, None),
+        "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin"
+    );
+}
+
+#[test]
+fn bundled_manifest_has_vad_model() {
+    let manifest = default_manifest();
+    let entry = manifest.find(ModelKind::Vad, "silero-vad").unwrap();
+    assert_eq!(entry.filename, "silero_vad.onnx");
+    // Hosted outside the manifest's base URL
+    assert_eq!(
+        manifest.download_url(entry, None),
+        "https://github.com/snakers4/silero-vad/raw/v4.0/files/silero_vad.onnx"
+    );
+    assert_eq!(
+        manifest.download_url(entry, Some("https://artifacts.corp.example/hf")),
+        "https://artifacts.corp.example/hf/snakers4/silero-vad/raw/v4.0/files/silero_vad.onnx"
+    );
+}
+
+#[test]
+fn mirror_replaces_base_url() {
+    let manifest = default_manifest();
+    let entry = manifest.find(ModelKind::Llm, "phi3-mini-q4").unwrap();
+    assert_eq!(
+        manifest.download_url(entry, Some("https://artifacts.corp.example/hf/")),
+        "https://artifacts.corp.example/hf/microsoft/Phi-3-mini-4k-instruct-gguf/resolve/main/Phi-3-mini-4k-instruct-q4.gguf"
+    );
+}
+
+#[test]
+fn validate_rejects_bad_manifests() {
+    let mut cases = Vec::new();
+
+    let mut m = valid_manifest();
+    m.version = MANIFEST_VERSION + 1;
+    cases.push(("future version", m));
+
+    let mut m = valid_manifest();
+    m.base_url = "ftp://mirror.example".to_string();
+    cases.push(("non-http base URL", m));
+
+    let mut m = valid_manifest();
+    m.whisper.push(m.whisper[0].clone());
+    cases.push(("duplicate ID", m));
+
+    let mut m = valid_manifest();
+    m.whisper[0].path = "../../etc/passwd".to_string();
+    cases.push(("path traversal", m));
+
+    let mut m = valid_manifest();
+    m.whisper[0].path = "https://evil.example/model.bin".to_string();
+    cases.push(("absolute path", m));
+
+    let mut m = valid_manifest();
+    m.whisper[0].filename = "../settings.json".to_string();
+    cases.push(("file name escaping the models dir", m));
+
+    let mut m = valid_manifest();
+    m.whisper[0].sha256 = Some("abc123".to_string());
+    cases.push(("short checksum", m));
+
+    let mut m = valid_manifest();
+    m.whisper[0].id = "user-base".to_string();
+    cases.push(("imported model ID prefix", m));
+
+    let mut m = valid_manifest();
+    m.whisper[0].size_bytes = 0;
+    cases.push(("zero size", m));
+
+    let mut m = valid_manifest();
+    m.whisper[0].base_url = Some("file:///etc".to_string());
+    cases.push(("non-http entry base URL", m));
+
+    for (what, manifest) in cases {
+        assert!(manifest.validate().is_err(), "accepted {}", what);
+    }
+}
+
+#[test]
+fn load_uses_bundled_manifest_without_cache() {
+    let dir = unique_dir("load_default");
+    assert_eq!(load(&dir), default_manifest());
+}
+
+#[test]
+fn load_prefers_cached_manifest() {
+    let dir = unique_dir("load_cached");
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(cached_manifest_path(&dir), SIGNED_MANIFEST).unwrap();
+    assert_eq!(load(&dir), valid_manifest());
+}
+
+#[test]
+fn load_ignores_invalid_cache() {
+    let dir = unique_dir("load_invalid");
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(
+        cached_manifest_path(&dir),
+        r#"{ "version": 99, "baseUrl": "https://x.example" }"#,
+    )
+    .unwrap();
+    assert_eq!(load(&dir), default_manifest());
+}
+
+#[test]
+fn verifies_minisign_signature() {
+    assert!(verify_signature(
+        SIGNED_MANIFEST.as_bytes(),
+        SIGNED_MANIFEST_SIG,
+        TEST_PUBLIC_KEY
+    )
+    .is_ok());
+
+    let tampered = SIGNED_MANIFEST.replace("artifacts.example.com", "evil.example.com");
+    assert!(verify_signature(tampered.as_bytes(), SIGNED_MANIFEST_SIG, TEST_PUBLIC_KEY).is_err());
+    assert!(verify_signature(SIGNED_MANIFEST.as_bytes(), "garbage", TEST_PUBLIC_KEY).is_err());
+}
+
+#[test]
+fn config_validation() {
+    assert!(CatalogConfig::default().validate().is_ok());
+    assert!(CatalogConfig {
+        manifest_url: Some("https://artifacts.example.com/vozr/manifest.json".to_string()),
+        mirror_url: Some("http://10.0.0.5:8081/hf".to_string()),
+        public_key: Some(TEST_PUBLIC_KEY.to_string()),
+    }
+    .validate()
+    .is_ok());
+
+    assert!(CatalogConfig {
+        mirror_url: Some("artifacts.example.com".to_string()),
+        ..Default::default()
+    }
+    .validate()
+    .is_err());
+    assert!(CatalogConfig {
+        public_key: Some("not a key".to_string()),
+        ..Default::default()
+    }
+    .validate()
+    .is_err());
+}
+
+#[test]
+fn refresh_fetches_verifies_and_caches() {
+    let dir = unique_dir("refresh_signed");
+    let base = serve(
+        vec![
+            ("/manifest.json", SIGNED_MANIFEST.to_string()),
+            ("/manifest.json.minisig", SIGNED_MANIFEST_SIG.to_string()),
+        ],
+        2,
+    );
+    let config = CatalogConfig {
+        manifest_url: Some(format!("{}/manifest.json", base)),
+        public_key: Some(TEST_PUBLIC_KEY.to_string()),
+        ..Default::default()
+    };
+
+    let manifest = refresh(&dir, &config).unwrap();
+    assert_eq!(manifest, valid_manifest());
+    assert_eq!(load(&dir), manifest);
+}
+
+#[test]
+fn refresh_rejects_bad_signature() {
+    let dir = unique_dir("refresh_bad_sig");
+    let tampered = SIGNED_MANIFEST.replace("147964211", "147964212");
+    let base = serve(
+        vec![
+            ("/manifest.json", tampered),
+            ("/manifest.json.minisig", SIGNED_MANIFEST_SIG.to_string()),
+        ],
+        2,
+    );
+    let config = CatalogConfig {
+        manifest_url: Some(format!("{}/manifest.json", base)),
+        public_key: Some(TEST_PUBLIC_KEY.to_string()),
+        ..Default::default()
+    };
+
+    assert!(refresh(&dir, &config).is_err());
+    assert!(!cached_manifest_path(&dir).exists());
+}
+
+#[test]
+fn refresh_without_url_drops_cache() {
+    let dir = unique_dir("refresh_reset");
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(cached_manifest_path(&dir), SIGNED_MANIFEST).unwrap();
+
+    let manifest = refresh(&dir, &CatalogConfig::default()).unwrap();
+    assert_eq!(manifest, default_manifest());
+    assert!(!cached_manifest_path(&dir).exists());
+}
+
+#[test]
+fn refresh_stores_signature_next_to_cache() {
+    let dir = unique_dir("refresh_signature_file");
+    let base = serve(
+        vec![
+            ("/manifest.json", SIGNED_MANIFEST.to_string()),
+            ("/manifest.json.minisig", SIGNED_MANIFEST_SIG.to_string()),
+        ],
+        2,
+    );
+    let config = CatalogConfig {
+        manifest_url: Some(format!("{}/manifest.json", base)),
+        public_key: Some(TEST_PUBLIC_KEY.to_string()),
+        ..Default::default()
+    };
+
+    refresh(&dir, &config).unwrap();
+    check_cache(&dir, &config).unwrap();
+    assert_eq!(load(&dir), valid_manifest());
+}
+
+#[test]
+fn unsigned_cache_is_discarded_once_a_key_is_set() {
+    let dir = unique_dir("check_cache_unsigned");
+    let base = serve(vec![("/manifest.json", SIGNED_MANIFEST.to_string())], 1);
+    let mut config = CatalogConfig {
+        manifest_url: Some(format!("{}/manifest.json", base)),
+        ..Default::default()
+    };
+    refresh(&dir, &config).unwrap();
+    check_cache(&dir, &config).unwrap();
+    assert_eq!(load(&dir), valid_manifest());
+
+    config.public_key = Some(TEST_PUBLIC_KEY.to_string());
+    check_cache(&dir, &config).unwrap();
+    assert!(!cached_manifest_path(&dir).exists());
+    assert_eq!(load(&dir), default_manifest());
+}
+
+#[test]
+fn tampered_cache_is_discarded() {
+    let dir = unique_dir("check_cache_tampered");
+    fs::create_dir_all(dir.join("models")).unwrap();
+    let tampered = SIGNED_MANIFEST.replace("artifacts.example.com", "evil.example.com");
+    fs::write(cached_manifest_path(&dir), tampered).unwrap();
+    fs::write(cached_signature_path(&dir), SIGNED_MANIFEST_SIG).unwrap();
+    let config = CatalogConfig {
+        manifest_url: Some("https://artifacts.example.com/manifest.json".to_string()),
+        public_key: Some(TEST_PUBLIC_KEY.to_string()),
+        ..Default::default()
+    };
+
+    check_cache(&dir, &config).unwrap();
+    assert!(!cached_manifest_path(&dir).exists());
+    assert!(!cached_signature_path(&dir).exists());
+}
+
+#[test]
+fn cache_is_discarded_without_manifest_url() {
+    let dir = unique_dir("check_cache_no_url");
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(cached_manifest_path(&dir), SIGNED_MANIFEST).unwrap();
+
+    check_cache(&dir, &CatalogConfig::default()).unwrap();
+    assert!(!cached_manifest_path(&dir).exists());
+}
@@ -1,9 +1,11 @@
 // Local LLM cleanup via llama.cpp bindings
 
 use super::TextCleaner;
+use crate::catalog;
+use crate::download::{self, Download, DownloadError, DownloadJob, ModelKind};
+use crate::user_models::{self, UserModel};
 use serde::Serialize;
 use std::fs;
-use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, Runtime};
@@ -22,29 +24,14 @@ pub struct LlmModelInfo {
     pub size_bytes: u64,
     pub description: String,
     pub downloaded: bool,
+    /// Imported by the user rather than part of the built-in catalog.
+    pub imported: bool,
 }
 
-struct LlmModelCatalogEntry {
-    id: &'static str,
-    name: &'static str,
-    filename: &'static str,
-    url: &'static str,
-    size_bytes: u64,
-    description: &'static str,
-}
-
-const LLM_MODEL_CATALOG: &[LlmModelCatalogEntry] = &[LlmModelCatalogEntry {
-    id: "phi3-mini-q4",
-    name: "Phi-3 Mini (Q4)",
-    filename: "Phi-3-mini-4k-instruct-q4.gguf",
-    url: "https://huggingface.co/microsoft/Phi-3-mini-4k-instruct-gguf/resolve/main/Phi-3-mini-4k-instruct-q4.gguf",
-    size_bytes: 2_318_377_216, // ~2.2GB
-    description: "Fast, good quality text cleanup (~2.2 GB)",
-}];
-
 /// Download progress event payload for LLM model downloads.
 #[derive(Clone, Serialize)]
 pub struct LlmDownloadProgressEvent {
+    pub job_id: u64,
     pub model_id: String,
     pub downloaded_bytes: u64,
     pub total_bytes: u64,
@@ -60,8 +47,10 @@ pub fn models_dir(app_data_dir: &Path) -> PathBuf {
 
 /// Get the file path for an LLM model by ID.
 pub fn model_path(app_data_dir: &Path, model_id: &str) -> Option<PathBuf> {
-    let entry = LLM_MODEL_CATALOG.iter().find(|m| m.id == model_id)?;
-    Some(models_dir(app_data_dir).join(entry.filename))
+    match catalog::load(app_data_dir).find(ModelKind::Llm, model_id) {
+        Some(entry) => Some(models_dir(app_data_dir).join(&entry.filename)),
+        None => user_models::find(app_data_dir, ModelKind::Llm, model_id).map(|m| m.path),
+    }
 }
 
 /// Check if an LLM model is downloaded.
@@ -73,21 +62,55 @@ pub fn is_downloaded(app_data_dir: &Path, model_id: &str) -> bool {
 
 /// List all LLM models with download status.
 pub fn list_models(app_data_dir: &Path) -> Vec<LlmModelInfo> {
-    LLM_MODEL_CATALOG
+    let dir = models_dir(app_data_dir);
+    catalog::load(app_data_dir)
+        .entries(ModelKind::Llm)
         .iter()
         .map(|entry| LlmModelInfo {
-            id: entry.id.to_string(),
-            name: entry.name.to_string(),
-            filename: entry.filename.to_string(),
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            filename: entry.filename.clone(),
             size_bytes: entry.size_bytes,
-            description: entry.description.to_string(),
-            downloaded: is_downloaded(app_data_dir, entry.id),
+            description: entry.description.clone(),
+            downloaded: dir.join(&entry.filename).exists(),
+            imported: false,
         })
+        .chain(
+            user_models::list(app_data_dir, ModelKind::Llm)
+                .into_iter()
+                .map(imported_model_info),
+        )
         .collect()
 }
 
+fn imported_model_info(model: UserModel) -> LlmModelInfo {
+    LlmModelInfo {
+        filename: model
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        description: if model.copied {
+            "Imported model".to_string()
+        } else {
+            format!("Imported from {}", model.path.display())
+        },
+        downloaded: model.path.exists(),
+        imported: true,
+        id: model.id,
+        name: model.name,
+        size_bytes: model.size_bytes,
+    }
+}
+
 /// Delete a downloaded LLM model.
+/// Imported models are unregistered; their file is only deleted if it was copied.
 pub fn delete_model(app_data_dir: &Path, model_id: &str) -> Result<(), String> {
+    if user_models::remove(app_data_dir, ModelKind::Llm, model_id)? {
+        return Ok(());
+    }
+
     let path = model_path(app_data_dir, model_id)
         .ok_or_else(|| format!("Unknown LLM model: {}", model_id))?;
 
@@ -98,19 +121,21 @@ pub fn delete_model(app_data_dir: &Path, model_id: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Download an LLM model from HuggingFace with progress events.
+/// Download an LLM model from the catalog (or `mirror`) with progress events.
 pub fn download_model<R: Runtime>(
     app: &AppHandle<R>,
     app_data_dir: &Path,
-    model_id: &str,
+    job: &DownloadJob,
+    mirror: Option<&str>,
 ) -> Result<PathBuf, String> {
-    let entry = LLM_MODEL_CATALOG
-        .iter()
-        .find(|m| m.id == model_id)
+    let model_id = job.model_id.as_str();
+    let manifest = catalog::load(app_data_dir);
+    let entry = manifest
+        .find(ModelKind::Llm, model_id)
         .ok_or_else(|| format!("Unknown LLM model: {}", model_id))?;
 
     let dir = models_dir(app_data_dir);
-    let dest = dir.join(entry.filename);
+    let dest = dir.join(&entry.filename);
 
     if dest.exists() {
         log::info!(
@@ -121,48 +146,32 @@ pub fn download_model<R: Runtime>(
         return Ok(dest);
     }
 
-    log::info!("Downloading LLM model {} from {}", model_id, entry.url);
-
-    let client = reqwest::blocking::Client::builder()
-        .timeout(None)
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let response = client
-        .get(entry.url)
-        .send()
-        .map_err(|e| format!("Download request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("Download failed: HTTP {}", response.status()));
-    }
-
-    let total_bytes = response.content_length().unwrap_or(entry.size_bytes);
-
-    let tmp_dest = dir.join(format!("{}.tmp", entry.filename));
-    let mut file =
-        fs::File::create(&tmp_dest).map_err(|e| format!("Failed to create file: {}", e))?;
-
-    let mut downloaded: u64 = 0;
-    let mut buf = vec![0u8; 64 * 1024];
-    let mut reader = response;
-
-    loop {
-        let bytes_read = reader
-            .read(&mut buf)
-            .map_err(|e| format!("Download read error: {}", e))?;
-
-        if bytes_read == 0 {
-            break;
-        }
-
-        file.write_all(&buf[..bytes_read])
-            .map_err(|e| format!("File write error: {}", e))?;
-
-        downloaded += bytes_read as u64;
-
+    let url = manifest.download_url(entry, mirror);
+    log::info!("Downloading LLM model {} from {}", model_id, url);
+
+    // Multi-GB files must not go unverified: without a pinned hash, check
+    // against the LFS object ID of the upstream file (mirrors may not report one)
+    let sha256 = entry
+        .sha256
+        .clone()
+        .or_else(|| download::lfs_sha256(&manifest.download_url(entry, None)))
+        .ok_or_else(|| {
+            format!(
+                "Cannot verify LLM model {}: no checksum is known and the server did not report one",
+                model_id
+            )
+        })?;
+
+    let download = Download {
+        url: &url,
+        dest: &dest,
+        sha256: Some(&sha256),
+        size_hint: entry.size_bytes,
+        cancel: Some(job.cancel_flag()),
+    };
+    let result = download::download_file(&download, |downloaded_bytes, total_bytes| {
         let percent = if total_bytes > 0 {
-            (downloaded as f32 / total_bytes as f32) * 100.0
+            (downloaded_bytes as f32 / total_bytes as f32) * 100.0
         } else {
             0.0
         };
@@ -170,26 +179,24 @@ pub fn download_model<R: Runtime>(
         let _ = app.emit(
             "llm://download-progress",
             LlmDownloadProgressEvent {
+                job_id: job.id,
                 model_id: model_id.to_string(),
-                downloaded_bytes: downloaded,
+                downloaded_bytes,
                 total_bytes,
                 percent,
             },
         );
-    }
+    });
 
-    file.flush()
-        .map_err(|e| format!("File flush error: {}", e))?;
-    drop(file);
-
-    fs::rename(&tmp_dest, &dest)
-        .map_err(|e| format!("Failed to finalize download: {}", e))?;
+    if let Err(e) = result {
+        if e == DownloadError::Cancelled {
+            log::info!("Download of LLM model {} cancelled", model_id);
+            let _ = app.emit("model://download-cancelled", job.info());
+        }
+        return Err(e.into());
+    }
 
-    log::info!(
-        "LLM model {} downloaded successfully ({} bytes)",
-        model_id,
-        downloaded
-    );
+    log::info!("LLM model {} downloaded successfully", model_id);
 
     Ok(dest)
 }
@@ -2,6 +2,8 @@ pub mod cloud_llm;
 pub mod local_llm;
 pub mod rules;
 
+use crate::download::{DownloadState, ModelKind};
+use crate::settings::config::SettingsState;
 use rules::RuleCleaner;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -162,9 +164,12 @@ pub fn get_cleanup_tier(state: tauri::State<'_, CleanupState>) -> CleanupTier {
 pub fn set_cleanup_tier(
     tier: CleanupTier,
     state: tauri::State<'_, CleanupState>,
-) {
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    settings.update(|s| s.cleanup_tier = tier)?;
     *state.tier.lock().unwrap() = tier;
     log::info!("Cleanup tier set to {:?}", tier);
+    Ok(())
 }
 
 #[tauri::command]
@@ -176,9 +181,12 @@ pub fn get_cloud_provider(state: tauri::State<'_, CleanupState>) -> CloudProvide
 pub fn set_cloud_provider(
     provider: CloudProvider,
     state: tauri::State<'_, CleanupState>,
-) {
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    settings.update(|s| s.cloud_provider = provider)?;
     *state.cloud_provider.lock().unwrap() = provider;
     log::info!("Cloud provider set to {:?}", provider);
+    Ok(())
 }
 
 #[tauri::command]
@@ -217,14 +225,17 @@ pub fn list_llm_models(
     Ok(local_llm::list_models(&data_dir))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn download_llm_model(
     app: AppHandle,
     model_id: String,
     state: tauri::State<'_, CleanupState>,
+    downloads: tauri::State<'_, DownloadState>,
 ) -> Result<String, String> {
     let data_dir = state.data_dir()?;
-    let path = local_llm::download_model(&app, &data_dir, &model_id)?;
+    let job = downloads.begin(ModelKind::Llm, &model_id)?;
+    let mirror = downloads.mirror_url();
+    let path = local_llm::download_model(&app, &data_dir, &job, mirror.as_deref())?;
     Ok(path.to_string_lossy().to_string())
 }
 
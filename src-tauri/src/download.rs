@@ -0,0 +1,506 @@
+// Shared model downloader.
+//
+// Downloads go to `<file>.tmp` next to the destination. If a partial file is
+// left behind (network drop, app quit), the next attempt resumes it with an
+// HTTP Range request instead of starting over. The finished file is checked
+// against its catalog SHA-256 before it is renamed into place.
+//
+// Running downloads are registered in `DownloadState` under a job ID so the
+// frontend can cancel them.
+
+use crate::catalog::CatalogConfig;
+use reqwest::blocking::Client;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Attempts per download; each retry resumes from the partial file.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before a retry, multiplied by the attempt number.
+const RETRY_DELAY_MS: u64 = 500;
+
+/// Connect timeout. There is no overall timeout — large models take a while.
+const CONNECT_TIMEOUT_SECS: u64 = 30;
+
+/// Longest wait for the next bytes of a response. Blocking reqwest applies
+/// its timeout to each read, so this bounds a stall rather than the whole
+/// download. A stalled attempt is retried (resuming), or abandoned if the
+/// download has been cancelled in the meantime.
+const STALL_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Partial files untouched for this long are deleted on startup.
+pub const STALE_PARTIAL_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Read buffer size (64KB chunks).
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Header in which Hugging Face reports an LFS file's SHA-256 (its LFS object
+/// ID) on the redirect from `/resolve/` to storage.
+const LINKED_ETAG: &str = "x-linked-etag";
+
+/// Why a download failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadError {
+    /// Connection failed or dropped. The partial file is kept for resuming.
+    Network(String),
+    /// The server answered with an error status.
+    Http(u16),
+    /// Reading or writing the local file failed.
+    Io(String),
+    /// The downloaded file does not match the catalog hash. It has been deleted.
+    ChecksumMismatch { expected: String, actual: String },
+    /// The download was cancelled. The partial file has been deleted.
+    Cancelled,
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Network(e) => write!(f, "Download interrupted: {}", e),
+            DownloadError::Http(status) => write!(f, "Download failed: HTTP {}", status),
+            DownloadError::Io(e) => write!(f, "File error: {}", e),
+            DownloadError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Downloaded file is corrupt (SHA-256 {}, expected {})",
+                actual, expected
+            ),
+            DownloadError::Cancelled => write!(f, "Download cancelled"),
+        }
+    }
+}
+
+impl From<DownloadError> for String {
+    fn from(e: DownloadError) -> Self {
+        e.to_string()
+    }
+}
+
+/// A file to download.
+pub struct Download<'a> {
+    pub url: &'a str,
+    /// Final location of the file.
+    pub dest: &'a Path,
+    /// Expected SHA-256 (hex). None skips verification.
+    pub sha256: Option<&'a str>,
+    /// Size to report for progress when the server sends no length.
+    pub size_hint: u64,
+    /// Set from another thread to abort the download.
+    pub cancel: Option<&'a AtomicBool>,
+}
+
+impl Download<'_> {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(|c| c.load(Ordering::Relaxed))
+    }
+}
+
+/// Path of the partial file for a destination.
+pub fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    dest.with_file_name(name)
+}
+
+/// Download a file, resuming any partial download and verifying its checksum.
+/// `on_progress` receives `(downloaded_bytes, total_bytes)` after every chunk.
+/// Runs synchronously — call from a background thread.
+pub fn download_file(
+    download: &Download,
+    on_progress: impl FnMut(u64, u64),
+) -> Result<(), DownloadError> {
+    download_with_stall_timeout(download, STALL_TIMEOUT, on_progress)
+}
+
+fn download_with_stall_timeout(
+    download: &Download,
+    stall_timeout: Duration,
+    mut on_progress: impl FnMut(u64, u64),
+) -> Result<(), DownloadError> {
+    let client = Client::builder()
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .timeout(stall_timeout)
+        .build()
+        .map_err(|e| DownloadError::Network(format!("Failed to create HTTP client: {}", e)))?;
+
+    if let Some(parent) = download.dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| DownloadError::Io(e.to_string()))?;
+    }
+    let tmp = partial_path(download.dest);
+
+    let mut attempt = 1;
+    loop {
+        let result = if download.is_cancelled() {
+            Err(DownloadError::Cancelled)
+        } else {
+            fetch_to_partial(&client, download, &tmp, &mut on_progress)
+        };
+
+        match result {
+            Ok(()) => break,
+            Err(DownloadError::Cancelled) => {
+                let _ = fs::remove_file(&tmp);
+                return Err(DownloadError::Cancelled);
+            }
+            Err(DownloadError::Network(e)) if attempt < MAX_ATTEMPTS => {
+                log::warn!(
+                    "Download of {} interrupted (attempt {}/{}): {}",
+                    download.url,
+                    attempt,
+                    MAX_ATTEMPTS,
+                    e
+                );
+                std::thread::sleep(Duration::from_millis(RETRY_DELAY_MS * attempt as u64));
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    if let Some(expected) = download.sha256 {
+        let actual = sha256_file(&tmp)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = fs::remove_file(&tmp);
+            return Err(DownloadError::ChecksumMismatch {
+                expected: expected.to_lowercase(),
+                actual,
+            });
+        }
+    }
+
+    fs::rename(&tmp, download.dest)
+        .map_err(|e| DownloadError::Io(format!("Failed to finalize download: {}", e)))
+}
+
+/// One HTTP request: resume (or start) the partial file and read to the end.
+fn fetch_to_partial(
+    client: &Client,
+    download: &Download,
+    tmp: &Path,
+    on_progress: &mut impl FnMut(u64, u64),
+) -> Result<(), DownloadError> {
+    let resume_from = fs::metadata(tmp).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(download.url);
+    if resume_from > 0 {
+        log::info!(
+            "Resuming download of {} at byte {}",
+            download.url,
+            resume_from
+        );
+        request = request.header(RANGE, format!("bytes={}-", resume_from));
+    }
+    let response = request
+        .send()
+        .map_err(|e| DownloadError::Network(e.to_string()))?;
+
+    let status = response.status();
+    let content_range = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_content_range);
+
+    let (mut file, mut downloaded, total) = match status {
+        StatusCode::PARTIAL_CONTENT if resume_from > 0 => {
+            let (start, total) = content_range.unwrap_or((None, None));
+            if start != Some(resume_from) {
+                // Can't trust the partial file against this server; start over
+                let _ = fs::remove_file(tmp);
+                return Err(DownloadError::Network(
+                    "Server resumed at an unexpected offset".to_string(),
+                ));
+            }
+            let file = fs::OpenOptions::new()
+                .append(true)
+                .open(tmp)
+                .map_err(|e| DownloadError::Io(e.to_string()))?;
+            let total = total
+                .or_else(|| response.content_length().map(|len| resume_from + len))
+                .unwrap_or(download.size_hint);
+            (file, resume_from, total)
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE if resume_from > 0 => {
+            // Nothing left to fetch if the partial file already has every byte
+            let total = content_range.and_then(|(_, total)| total);
+            if total == Some(resume_from) {
+                on_progress(resume_from, resume_from);
+                return Ok(());
+            }
+            let _ = fs::remove_file(tmp);
+            return Err(DownloadError::Network(
+                "Partial file does not match the remote file".to_string(),
+            ));
+        }
+        status if status.is_success() => {
+            // Fresh download (or the server ignored the Range header)
+            let file = fs::File::create(tmp).map_err(|e| DownloadError::Io(e.to_string()))?;
+            let total = response.content_length().unwrap_or(download.size_hint);
+            (file, 0, total)
+        }
+        status => return Err(DownloadError::Http(status.as_u16())),
+    };
+
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut reader = response;
+
+    loop {
+        if download.is_cancelled() {
+            return Err(DownloadError::Cancelled);
+        }
+
+        let bytes_read = reader
+            .read(&mut buf)
+            .map_err(|e| DownloadError::Network(e.to_string()))?;
+
+        if bytes_read == 0 {
+            break;
+        }
+
+        file.write_all(&buf[..bytes_read])
+            .map_err(|e| DownloadError::Io(e.to_string()))?;
+
+        downloaded += bytes_read as u64;
+        on_progress(downloaded, total);
+    }
+
+    file.flush().map_err(|e| DownloadError::Io(e.to_string()))
+}
+
+/// SHA-256 of a Hugging Face LFS file, read from its LFS object ID. Used to
+/// verify catalog entries that have no pinned hash. None if the server does
+/// not report one (e.g. a plain HTTP mirror).
+pub fn lfs_sha256(url: &str) -> Option<String> {
+    let client = Client::builder()
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .ok()?;
+    let response = client.head(url).send().ok()?;
+    parse_lfs_oid(response.headers().get(LINKED_ETAG)?.to_str().ok()?)
+}
+
+/// Extract a SHA-256 from an ETag value such as `"a1b2…"` or `W/"a1b2…"`.
+fn parse_lfs_oid(etag: &str) -> Option<String> {
+    let oid = etag.trim().trim_start_matches("W/").trim_matches('"');
+    let is_sha256 = oid.len() == 64 && oid.chars().all(|c| c.is_ascii_hexdigit());
+    is_sha256.then(|| oid.to_lowercase())
+}
+
+/// Parse a `Content-Range` header ("bytes 100-199/1000" or "bytes */1000")
+/// into (first byte, total length).
+fn parse_content_range(value: &str) -> Option<(Option<u64>, Option<u64>)> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (span, total) = range.split_once('/')?;
+    let start = span.split_once('-').and_then(|(s, _)| s.parse().ok());
+    Some((start, total.parse().ok()))
+}
+
+/// Hex-encoded SHA-256 of a file.
+pub fn sha256_file(path: &Path) -> Result<String, DownloadError> {
+    let mut file = fs::File::open(path).map_err(|e| DownloadError::Io(e.to_string()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| DownloadError::Io(e.to_string()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Delete `.tmp` partial downloads in `dir` that have not been touched for `max_age`.
+/// Returns the number of files removed.
+pub fn remove_stale_partials(dir: &Path, max_age: Duration) -> usize {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("tmp") {
+            continue;
+        }
+
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok());
+        if age.is_some_and(|age| age >= max_age) && fs::remove_file(&path).is_ok() {
+            log::info!("Removed stale partial download {}", path.display());
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Which catalog a model belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelKind {
+    Whisper,
+    Llm,
+    Vad,
+}
+
+/// A running model download.
+pub struct DownloadJob {
+    pub id: u64,
+    pub kind: ModelKind,
+    pub model_id: String,
+    cancelled: AtomicBool,
+}
+
+impl DownloadJob {
+    /// Flag to pass as `Download::cancel`.
+    pub fn cancel_flag(&self) -> &AtomicBool {
+        &self.cancelled
+    }
+
+    pub fn info(&self) -> DownloadJobInfo {
+        DownloadJobInfo {
+            job_id: self.id,
+            kind: self.kind,
+            model_id: self.model_id.clone(),
+        }
+    }
+}
+
+/// Download job payload for the frontend (also the `model://download-cancelled` event).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DownloadJobInfo {
+    pub job_id: u64,
+    pub kind: ModelKind,
+    pub model_id: String,
+}
+
+/// Tauri-managed registry of running downloads.
+pub struct DownloadState {
+    jobs: Mutex<Vec<Arc<DownloadJob>>>,
+    next_id: AtomicU64,
+    /// Manifest and mirror configuration.
+    pub catalog_config: Mutex<CatalogConfig>,
+}
+
+impl DownloadState {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            catalog_config: Mutex::new(CatalogConfig::default()),
+        }
+    }
+
+    /// Configured mirror base URL, if any.
+    pub fn mirror_url(&self) -> Option<String> {
+        self.catalog_config.lock().unwrap().mirror_url.clone()
+    }
+
+    /// Register a download. Fails if the same model is already downloading,
+    /// since both jobs would write the same partial file.
+    /// The job is unregistered when the returned guard is dropped.
+    pub fn begin(&self, kind: ModelKind, model_id: &str) -> Result<JobGuard<'_>, String> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs
+            .iter()
+            .any(|j| j.kind == kind && j.model_id == model_id)
+        {
+            return Err(format!("Model '{}' is already downloading", model_id));
+        }
+
+        let job = Arc::new(DownloadJob {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            kind,
+            model_id: model_id.to_string(),
+            cancelled: AtomicBool::new(false),
+        });
+        jobs.push(job.clone());
+        Ok(JobGuard { state: self, job })
+    }
+
+    /// Request cancellation of a job. Returns false if no such job is running.
+    pub fn cancel(&self, job_id: u64) -> bool {
+        match self.jobs.lock().unwrap().iter().find(|j| j.id == job_id) {
+            Some(job) => {
+                job.cancelled.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Running downloads.
+    pub fn list(&self) -> Vec<DownloadJobInfo> {
+        self.jobs.lock().unwrap().iter().map(|j| j.info()).collect()
+    }
+}
+
+/// A registered download job, removed from `DownloadState` on drop.
+pub struct JobGuard<'a> {
+    state: &'a DownloadState,
+    job: Arc<DownloadJob>,
+}
+
+impl Deref for JobGuard<'_> {
+    type Target = DownloadJob;
+
+    fn deref(&self) -> &DownloadJob {
+        &self.job
+    }
+}
+
+impl Drop for JobGuard<'_> {
+    fn drop(&mut self) {
+        self.state
+            .jobs
+            .lock()
+            .unwrap()
+            .retain(|j| j.id != self.job.id);
+    }
+}
+
+// --------------- Tauri commands ---------------
+
+#[tauri::command]
+pub fn list_model_downloads(state: tauri::State<'_, DownloadState>) -> Vec<DownloadJobInfo> {
+    state.list()
+}
+
+#[tauri::command]
+pub fn cancel_model_download(
+    job_id: u64,
+    state: tauri::State<'_, DownloadState>,
+) -> Result<(), String> {
+    if !state.cancel(job_id) {
+        return Err(format!("No running download with ID {}", job_id));
+    }
+    log::info!("Cancelling download job {}", job_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,568 @@
+use super::*;
+use std::io::BufRead;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("download")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// How the stand-in server answers one request.
+#[derive(Clone, Copy)]
+enum Reply {
+    /// Serve the body, honoring a Range header if present.
+    Normal,
+    /// Serve the whole body with 200, ignoring any Range header.
+    IgnoreRange,
+    /// Announce the full length but close the connection after this many bytes.
+    DropAfter(usize),
+    /// Answer with this status and an empty body.
+    Status(u16),
+    /// Announce the full length, send this many bytes, then hang without
+    /// closing the connection.
+    StallAfter(usize),
+}
+
+/// A minimal HTTP/1.1 server on localhost answering one request per entry in
+/// `replies`. Returns the URL and a log of the Range headers it received.
+fn serve(body: Vec<u8>, replies: Vec<Reply>) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+    let ranges = Arc::new(Mutex::new(Vec::new()));
+    let ranges_log = ranges.clone();
+
+    thread::spawn(move || {
+        let mut stalled = Vec::new();
+        for reply in replies {
+            let (mut stream, _) = match listener.accept() {
+                Ok(conn) => conn,
+                Err(_) => return,
+            };
+
+            // Read the request head and pick out the Range header
+            let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+            let mut range = None;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("range") {
+                        range = Some(value.trim().to_string());
+                    }
+                }
+            }
+            ranges_log.lock().unwrap().push(range.clone());
+
+            let start = range
+                .as_deref()
+                .and_then(|r| r.strip_prefix("bytes="))
+                .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+
+            let response = match (reply, start) {
+                (Reply::Status(code), _) => {
+                    format!("HTTP/1.1 {} Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", code)
+                        .into_bytes()
+                }
+                (Reply::Normal, Some(start)) if start >= body.len() => format!(
+                    "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .into_bytes(),
+                (Reply::Normal, Some(start)) => {
+                    let mut r = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        start,
+                        body.len() - 1,
+                        body.len(),
+                        body.len() - start
+                    )
+                    .into_bytes();
+                    r.extend_from_slice(&body[start..]);
+                    r
+                }
+                (Reply::DropAfter(n), _) | (Reply::StallAfter(n), _) => {
+                    let mut r = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .into_bytes();
+                    r.extend_from_slice(&body[..n]);
+                    r
+                }
+                (Reply::Normal, None) | (Reply::IgnoreRange, _) => {
+                    let mut r = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .into_bytes();
+                    r.extend_from_slice(&body);
+                    r
+                }
+            };
+
+            let _ = stream.write_all(&response);
+            let _ = stream.flush();
+            if let Reply::StallAfter(_) = reply {
+                stalled.push(stream);
+            }
+        }
+        // Keep stalled connections open until the client gives up on them
+        if !stalled.is_empty() {
+            thread::sleep(Duration::from_secs(5));
+        }
+    });
+
+    (url, ranges)
+}
+
+/// Deterministic test payload.
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[test]
+fn downloads_and_verifies_file() {
+    let dir = unique_dir("full");
+    let body = payload(200_000);
+    let (url, ranges) = serve(body.clone(), vec![Reply::Normal]);
+    let dest = dir.join("model.bin");
+    let hash = sha256_hex(&body);
+
+    let mut last_progress = (0, 0);
+    download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: Some(&hash),
+            size_hint: 0,
+            cancel: None,
+        },
+        |done, total| last_progress = (done, total),
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&dest).unwrap(), body);
+    assert!(!partial_path(&dest).exists());
+    assert_eq!(last_progress, (200_000, 200_000));
+    assert_eq!(*ranges.lock().unwrap(), vec![None]);
+}
+
+#[test]
+fn resumes_existing_partial_file() {
+    let dir = unique_dir("resume");
+    let body = payload(100_000);
+    let (url, ranges) = serve(body.clone(), vec![Reply::Normal]);
+    let dest = dir.join("model.bin");
+    fs::write(partial_path(&dest), &body[..40_000]).unwrap();
+
+    let mut first_progress = None;
+    download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: Some(&sha256_hex(&body)),
+            size_hint: 0,
+            cancel: None,
+        },
+        |done, total| {
+            first_progress.get_or_insert((done, total));
+        },
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&dest).unwrap(), body);
+    assert_eq!(
+        *ranges.lock().unwrap(),
+        vec![Some("bytes=40000-".to_string())]
+    );
+    // Progress continues from the resumed offset
+    assert!(first_progress.unwrap().0 > 40_000);
+    assert_eq!(first_progress.unwrap().1, 100_000);
+}
+
+#[test]
+fn restarts_when_server_ignores_range() {
+    let dir = unique_dir("ignore_range");
+    let body = payload(50_000);
+    let (url, _) = serve(body.clone(), vec![Reply::IgnoreRange]);
+    let dest = dir.join("model.bin");
+    fs::write(partial_path(&dest), b"stale bytes").unwrap();
+
+    download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: Some(&sha256_hex(&body)),
+            size_hint: 0,
+            cancel: None,
+        },
+        |_, _| {},
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&dest).unwrap(), body);
+}
+
+#[test]
+fn retries_dropped_connection_with_range() {
+    let dir = unique_dir("retry");
+    let body = payload(300_000);
+    let (url, ranges) = serve(body.clone(), vec![Reply::DropAfter(120_000), Reply::Normal]);
+    let dest = dir.join("model.bin");
+
+    download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: Some(&sha256_hex(&body)),
+            size_hint: 0,
+            cancel: None,
+        },
+        |_, _| {},
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&dest).unwrap(), body);
+    let ranges = ranges.lock().unwrap();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[0], None);
+    // The retry asked only for what was missing
+    let resumed_at: u64 = ranges[1]
+        .as_deref()
+        .unwrap()
+        .trim_start_matches("bytes=")
+        .trim_end_matches('-')
+        .parse()
+        .unwrap();
+    assert!(resumed_at > 0 && resumed_at <= 120_000);
+}
+
+#[test]
+fn stalled_connection_is_retried_with_range() {
+    let dir = unique_dir("stall");
+    let body = payload(300_000);
+    let (url, ranges) = serve(
+        body.clone(),
+        vec![Reply::StallAfter(100_000), Reply::Normal],
+    );
+    let dest = dir.join("model.bin");
+
+    download_with_stall_timeout(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: Some(&sha256_hex(&body)),
+            size_hint: 0,
+            cancel: None,
+        },
+        Duration::from_millis(300),
+        |_, _| {},
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&dest).unwrap(), body);
+    let ranges = ranges.lock().unwrap();
+    assert_eq!(ranges.len(), 2);
+    assert!(ranges[1].is_some());
+}
+
+#[test]
+fn cancel_during_stall_deletes_partial() {
+    let dir = unique_dir("cancel_stall");
+    let body = payload(300_000);
+    let (url, ranges) = serve(body, vec![Reply::StallAfter(100_000)]);
+    let dest = dir.join("model.bin");
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        flag.store(true, Ordering::Relaxed);
+    });
+
+    let result = download_with_stall_timeout(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: None,
+            size_hint: 0,
+            cancel: Some(&cancel),
+        },
+        Duration::from_millis(300),
+        |_, _| {},
+    );
+
+    assert_eq!(result, Err(DownloadError::Cancelled));
+    assert!(!partial_path(&dest).exists());
+    assert_eq!(ranges.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn complete_partial_file_is_accepted_on_416() {
+    let dir = unique_dir("complete_partial");
+    let body = payload(10_000);
+    let (url, _) = serve(body.clone(), vec![Reply::Normal]);
+    let dest = dir.join("model.bin");
+    fs::write(partial_path(&dest), &body).unwrap();
+
+    download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: Some(&sha256_hex(&body)),
+            size_hint: 0,
+            cancel: None,
+        },
+        |_, _| {},
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&dest).unwrap(), body);
+}
+
+#[test]
+fn checksum_mismatch_deletes_file() {
+    let dir = unique_dir("bad_hash");
+    let body = payload(20_000);
+    let (url, _) = serve(body, vec![Reply::Normal]);
+    let dest = dir.join("model.bin");
+    let wrong = "0".repeat(64);
+
+    let err = download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: Some(&wrong),
+            size_hint: 0,
+            cancel: None,
+        },
+        |_, _| {},
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, DownloadError::ChecksumMismatch { .. }));
+    assert!(!dest.exists());
+    assert!(!partial_path(&dest).exists());
+}
+
+#[test]
+fn http_error_is_reported() {
+    let dir = unique_dir("http_404");
+    let (url, _) = serve(Vec::new(), vec![Reply::Status(404)]);
+    let dest = dir.join("model.bin");
+
+    let err = download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: None,
+            size_hint: 0,
+            cancel: None,
+        },
+        |_, _| {},
+    )
+    .unwrap_err();
+
+    assert_eq!(err, DownloadError::Http(404));
+    assert!(!dest.exists());
+}
+
+#[test]
+fn connection_refused_is_network_error() {
+    let dir = unique_dir("refused");
+    // Bind and drop to get a port nobody listens on
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let url = format!("http://127.0.0.1:{}/model.bin", port);
+    let dest = dir.join("model.bin");
+
+    let err = download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: None,
+            size_hint: 0,
+            cancel: None,
+        },
+        |_, _| {},
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, DownloadError::Network(_)));
+}
+
+#[test]
+fn partial_path_appends_tmp() {
+    let dest = Path::new("/models/whisper/ggml-base.en.bin");
+    assert_eq!(
+        partial_path(dest),
+        Path::new("/models/whisper/ggml-base.en.bin.tmp")
+    );
+}
+
+#[test]
+fn parses_content_range() {
+    assert_eq!(
+        parse_content_range("bytes 100-199/1000"),
+        Some((Some(100), Some(1000)))
+    );
+    assert_eq!(
+        parse_content_range("bytes */1000"),
+        Some((None, Some(1000)))
+    );
+    assert_eq!(parse_content_range("bytes 0-9/*"), Some((Some(0), None)));
+    assert_eq!(parse_content_range("items 0-9/10"), None);
+}
+
+#[test]
+fn parses_lfs_object_id() {
+    let oid = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+    assert_eq!(
+        parse_lfs_oid(&format!("\"{}\"", oid)),
+        Some(oid.to_lowercase())
+    );
+    assert_eq!(
+        parse_lfs_oid(&format!("W/\"{}\"", oid)),
+        Some(oid.to_lowercase())
+    );
+    // Git blob ETags of non-LFS files are SHA-1s
+    assert_eq!(
+        parse_lfs_oid("\"3f786850e387550fdab836ed7e6dc881de23001b\""),
+        None
+    );
+    assert_eq!(parse_lfs_oid("not-a-hash"), None);
+}
+
+#[test]
+fn sha256_of_known_input() {
+    let dir = unique_dir("sha256");
+    let path = dir.join("abc.txt");
+    fs::write(&path, b"abc").unwrap();
+    assert_eq!(
+        sha256_file(&path).unwrap(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn removes_only_stale_partials() {
+    let dir = unique_dir("stale");
+    let stale = dir.join("old.bin.tmp");
+    let fresh = dir.join("new.bin.tmp");
+    let model = dir.join("model.bin");
+    for path in [&stale, &fresh, &model] {
+        fs::write(path, b"data").unwrap();
+    }
+    let old = SystemTime::now() - STALE_PARTIAL_AGE - Duration::from_secs(60);
+    fs::File::options()
+        .write(true)
+        .open(&stale)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+
+    assert_eq!(remove_stale_partials(&dir, STALE_PARTIAL_AGE), 1);
+    assert!(!stale.exists());
+    assert!(fresh.exists());
+    assert!(model.exists());
+}
+
+#[test]
+fn cancelled_before_start_does_not_connect() {
+    let dir = unique_dir("cancel_before");
+    let (url, ranges) = serve(payload(1_000), vec![Reply::Normal]);
+    let dest = dir.join("model.bin");
+    fs::write(partial_path(&dest), b"partial").unwrap();
+    let cancel = AtomicBool::new(true);
+
+    let err = download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: None,
+            size_hint: 0,
+            cancel: Some(&cancel),
+        },
+        |_, _| {},
+    )
+    .unwrap_err();
+
+    assert_eq!(err, DownloadError::Cancelled);
+    assert!(!partial_path(&dest).exists());
+    assert!(ranges.lock().unwrap().is_empty());
+}
+
+#[test]
+fn cancel_mid_download_deletes_partial() {
+    let dir = unique_dir("cancel_mid");
+    let (url, _) = serve(payload(500_000), vec![Reply::Normal]);
+    let dest = dir.join("model.bin");
+    let cancel = AtomicBool::new(false);
+
+    let err = download_file(
+        &Download {
+            url: &url,
+            dest: &dest,
+            sha256: None,
+            size_hint: 0,
+            cancel: Some(&cancel),
+        },
+        |_, _| cancel.store(true, Ordering::Relaxed),
+    )
+    .unwrap_err();
+
+    assert_eq!(err, DownloadError::Cancelled);
+    assert!(!dest.exists());
+    assert!(!partial_path(&dest).exists());
+}
+
+#[test]
+fn jobs_are_registered_until_dropped() {
+    let state = DownloadState::new();
+    let job = state.begin(ModelKind::Whisper, "base.en").unwrap();
+    let other = state.begin(ModelKind::Llm, "phi3-mini-q4").unwrap();
+    assert_ne!(job.id, other.id);
+    assert_eq!(state.list().len(), 2);
+    assert_eq!(state.list()[0], job.info());
+
+    drop(job);
+    assert_eq!(state.list(), vec![other.info()]);
+}
+
+#[test]
+fn same_model_cannot_download_twice() {
+    let state = DownloadState::new();
+    let _job = state.begin(ModelKind::Whisper, "base.en").unwrap();
+    assert!(state.begin(ModelKind::Whisper, "base.en").is_err());
+    assert!(state.begin(ModelKind::Whisper, "small").is_ok());
+}
+
+#[test]
+fn cancel_sets_job_flag() {
+    let state = DownloadState::new();
+    let job = state.begin(ModelKind::Whisper, "medium").unwrap();
+    assert!(!job.cancel_flag().load(Ordering::Relaxed));
+
+    assert!(state.cancel(job.id));
+    assert!(job.cancel_flag().load(Ordering::Relaxed));
+    assert!(!state.cancel(job.id + 100));
+}
@@ -1,3 +1,4 @@
+use crate::settings::config::SettingsState;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
@@ -23,6 +24,10 @@ pub struct HotkeyState {
     pub mode: Mutex<ActivationMode>,
     /// Current hotkey string (e.g. "ctrl+shift+space").
     pub hotkey: Mutex<String>,
+    /// Optional second hotkey that dictates with translation to English.
+    pub translate_hotkey: Mutex<Option<String>>,
+    /// Whether the current recording was started with the translate hotkey.
+    pub translate_recording: AtomicBool,
     /// Timestamp of last key-down, used for hold-mode threshold.
     press_start: Mutex<Option<Instant>>,
 }
@@ -40,6 +45,8 @@ impl HotkeyState {
             is_paused: AtomicBool::new(false),
             mode: Mutex::new(ActivationMode::Toggle),
             hotkey: Mutex::new(DEFAULT_HOTKEY.to_string()),
+            translate_hotkey: Mutex::new(None),
+            translate_recording: AtomicBool::new(false),
             press_start: Mutex::new(None),
         }
     }
@@ -48,11 +55,24 @@ impl HotkeyState {
 /// Register the global hotkey with the given shortcut string.
 /// Returns Ok(()) on success, or emits a conflict error event on failure.
 pub fn register<R: Runtime>(app: &AppHandle<R>, shortcut: &str) -> Result<(), String> {
+    register_shortcut(app, shortcut, false)
+}
+
+/// Register the translate hotkey: recordings it starts are translated to English.
+pub fn register_translate<R: Runtime>(app: &AppHandle<R>, shortcut: &str) -> Result<(), String> {
+    register_shortcut(app, shortcut, true)
+}
+
+fn register_shortcut<R: Runtime>(
+    app: &AppHandle<R>,
+    shortcut: &str,
+    translate: bool,
+) -> Result<(), String> {
     let app_for_handler = app.clone();
 
     app.global_shortcut()
         .on_shortcut(shortcut, move |_app, _shortcut, event| {
-            handle_shortcut_event(&app_for_handler, event.state);
+            handle_shortcut_event(&app_for_handler, event.state, translate);
         })
         .map_err(|e| {
             let msg = format!("Failed to register hotkey '{}': {}", shortcut, e);
@@ -71,19 +91,70 @@ pub fn unregister<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
-/// Change the hotkey binding at runtime.
+/// Change the hotkey binding at runtime. The old binding is kept if the new
+/// one cannot be registered.
 pub fn rebind<R: Runtime>(app: &AppHandle<R>, new_shortcut: &str) -> Result<(), String> {
-    // Unregister old
-    let _ = unregister(app);
-    // Register new
+    let state: tauri::State<'_, HotkeyState> = app.state();
+    if let Some(translate) = state.translate_hotkey.lock().unwrap().as_deref() {
+        if new_shortcut.eq_ignore_ascii_case(translate) {
+            return Err("The dictation hotkey must differ from the translate hotkey".to_string());
+        }
+    }
+    let current = state.hotkey.lock().unwrap().clone();
+    if new_shortcut.eq_ignore_ascii_case(&current) {
+        // Registration at startup may have failed (conflict), so retry it
+        if !app.global_shortcut().is_registered(current.as_str()) {
+            register(app, new_shortcut)?;
+        }
+        return Ok(());
+    }
+
+    // Register new, then unregister old
     register(app, new_shortcut)?;
+    let _ = unregister(app);
     // Update stored shortcut
-    let state: tauri::State<'_, HotkeyState> = app.state();
     *state.hotkey.lock().unwrap() = new_shortcut.to_string();
     Ok(())
 }
 
-fn handle_shortcut_event<R: Runtime>(app: &AppHandle<R>, key_state: ShortcutState) {
+/// Change (or remove, with None) the translate hotkey at runtime.
+pub fn rebind_translate<R: Runtime>(
+    app: &AppHandle<R>,
+    new_shortcut: Option<&str>,
+) -> Result<(), String> {
+    let state: tauri::State<'_, HotkeyState> = app.state();
+    if let Some(shortcut) = new_shortcut {
+        if shortcut.eq_ignore_ascii_case(&state.hotkey.lock().unwrap()) {
+            return Err("The translate hotkey must differ from the dictation hotkey".to_string());
+        }
+    }
+
+    let old = state.translate_hotkey.lock().unwrap().clone();
+    if let (Some(old), Some(new)) = (old.as_deref(), new_shortcut) {
+        if old.eq_ignore_ascii_case(new) {
+            if !app.global_shortcut().is_registered(old) {
+                register_translate(app, new)?;
+            }
+            return Ok(());
+        }
+    }
+
+    // The old binding stays active if the new one cannot be registered
+    if let Some(shortcut) = new_shortcut {
+        register_translate(app, shortcut)?;
+    }
+    if let Some(old) = old {
+        let _ = app.global_shortcut().unregister(old.as_str());
+    }
+    *state.translate_hotkey.lock().unwrap() = new_shortcut.map(str::to_string);
+    Ok(())
+}
+
+fn handle_shortcut_event<R: Runtime>(
+    app: &AppHandle<R>,
+    key_state: ShortcutState,
+    translate: bool,
+) {
     eprintln!(">>> HOTKEY PRESSED: {:?}", key_state);
     log::info!("Hotkey event: {:?}", key_state);
     let state: tauri::State<'_, HotkeyState> = app.state();
@@ -97,8 +168,8 @@ fn handle_shortcut_event<R: Runtime>(app: &AppHandle<R>, key_state: ShortcutStat
     let mode = *state.mode.lock().unwrap();
 
     match mode {
-        ActivationMode::Toggle => handle_toggle(app, &state, key_state),
-        ActivationMode::Hold => handle_hold(app, &state, key_state),
+        ActivationMode::Toggle => handle_toggle(app, &state, key_state, translate),
+        ActivationMode::Hold => handle_hold(app, &state, key_state, translate),
     }
 }
 
@@ -107,6 +178,7 @@ fn handle_toggle<R: Runtime>(
     app: &AppHandle<R>,
     state: &HotkeyState,
     key_state: ShortcutState,
+    translate: bool,
 ) {
     // Only act on key-down
     if key_state != ShortcutState::Pressed {
@@ -118,12 +190,11 @@ fn handle_toggle<R: Runtime>(
     if was_recording {
         // Stop recording → trigger processing
         log::info!("Hotkey: stopping recording, emitting vozr://stop");
-        state.is_recording.store(false, Ordering::Relaxed);
-        let _ = app.emit("vozr://stop", ());
-        crate::tray::set_state(app, crate::tray::TrayState::Processing);
+        emit_stop(app, state);
     } else {
         // Start recording
         log::info!("Hotkey: starting recording, emitting vozr://start");
+        state.translate_recording.store(translate, Ordering::Relaxed);
         state.is_recording.store(true, Ordering::Relaxed);
         let _ = app.emit("vozr://start", ());
         crate::tray::set_state(app, crate::tray::TrayState::Listening);
@@ -136,12 +207,14 @@ fn handle_hold<R: Runtime>(
     app: &AppHandle<R>,
     state: &HotkeyState,
     key_state: ShortcutState,
+    translate: bool,
 ) {
     match key_state {
         ShortcutState::Pressed => {
             *state.press_start.lock().unwrap() = Some(Instant::now());
 
             if !state.is_recording.load(Ordering::Relaxed) {
+                state.translate_recording.store(translate, Ordering::Relaxed);
                 state.is_recording.store(true, Ordering::Relaxed);
                 let _ = app.emit("vozr://start", ());
                 crate::tray::set_state(app, crate::tray::TrayState::Listening);
@@ -154,14 +227,29 @@ fn handle_hold<R: Runtime>(
                 .unwrap_or(true);
 
             if state.is_recording.load(Ordering::Relaxed) && held_long_enough {
-                state.is_recording.store(false, Ordering::Relaxed);
-                let _ = app.emit("vozr://stop", ());
-                crate::tray::set_state(app, crate::tray::TrayState::Processing);
+                emit_stop(app, state);
             }
         }
     }
 }
 
+/// End the current recording the same way a second toggle press does.
+/// Used when recording stops on its own (e.g. the user stopped talking).
+pub fn stop_recording<R: Runtime>(app: &AppHandle<R>) {
+    let state: tauri::State<'_, HotkeyState> = app.state();
+    if state.is_recording.load(Ordering::Relaxed) {
+        log::info!("Stopping recording, emitting vozr://stop");
+        emit_stop(app, &state);
+    }
+}
+
+/// Mark recording as stopped and trigger processing.
+fn emit_stop<R: Runtime>(app: &AppHandle<R>, state: &HotkeyState) {
+    state.is_recording.store(false, Ordering::Relaxed);
+    let _ = app.emit("vozr://stop", ());
+    crate::tray::set_state(app, crate::tray::TrayState::Processing);
+}
+
 // --------------- Tauri commands ---------------
 
 #[tauri::command]
@@ -177,12 +265,14 @@ pub fn get_activation_mode(state: tauri::State<'_, HotkeyState>) -> String {
 pub fn set_activation_mode(
     mode: String,
     state: tauri::State<'_, HotkeyState>,
+    settings: tauri::State<'_, SettingsState>,
 ) -> Result<(), String> {
     let new_mode = match mode.as_str() {
         "toggle" => ActivationMode::Toggle,
         "hold" => ActivationMode::Hold,
         other => return Err(format!("Unknown mode: {}", other)),
     };
+    settings.update(|s| s.activation_mode = new_mode)?;
     *state.mode.lock().unwrap() = new_mode;
     Ok(())
 }
@@ -193,8 +283,43 @@ pub fn get_hotkey(state: tauri::State<'_, HotkeyState>) -> String {
 }
 
 #[tauri::command]
-pub fn set_hotkey(app: AppHandle, shortcut: String) -> Result<(), String> {
-    rebind(&app, &shortcut)
+pub fn set_hotkey(
+    app: AppHandle,
+    shortcut: String,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    let previous = app.state::<HotkeyState>().hotkey.lock().unwrap().clone();
+    rebind(&app, &shortcut)?;
+    if let Err(e) = settings.update(|s| s.hotkey = shortcut) {
+        let _ = rebind(&app, &previous);
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_translate_hotkey(state: tauri::State<'_, HotkeyState>) -> Option<String> {
+    state.translate_hotkey.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_translate_hotkey(
+    app: AppHandle,
+    shortcut: Option<String>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    let previous = app
+        .state::<HotkeyState>()
+        .translate_hotkey
+        .lock()
+        .unwrap()
+        .clone();
+    rebind_translate(&app, shortcut.as_deref())?;
+    if let Err(e) = settings.update(|s| s.translate_hotkey = shortcut) {
+        let _ = rebind_translate(&app, previous.as_deref());
+        return Err(e);
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -7,6 +7,8 @@ fn hotkey_state_defaults() {
     assert!(!state.is_paused.load(Ordering::Relaxed));
     assert_eq!(*state.mode.lock().unwrap(), ActivationMode::Toggle);
     assert_eq!(*state.hotkey.lock().unwrap(), DEFAULT_HOTKEY);
+    assert!(state.translate_hotkey.lock().unwrap().is_none());
+    assert!(!state.translate_recording.load(Ordering::Relaxed));
     assert!(state.press_start.lock().unwrap().is_none());
 }
 
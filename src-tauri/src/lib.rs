@@ -1,19 +1,62 @@
 pub mod audio;
+pub mod catalog;
 pub mod cleanup;
+pub mod download;
 pub mod hotkey;
 pub mod injection;
+pub mod model_storage;
 mod pipeline;
 pub mod settings;
 pub mod stt;
 pub mod tray;
+pub mod user_models;
 
 use audio::AudioState;
 use cleanup::CleanupState;
+use download::DownloadState;
 use hotkey::HotkeyState;
 use injection::InjectionState;
+use settings::config::{Settings, SettingsState};
+use std::sync::atomic::Ordering;
 use stt::SttState;
 use tauri::{Emitter, Manager, WebviewWindow};
 
+/// Push persisted settings into each subsystem's managed state.
+fn apply_settings(app: &tauri::AppHandle, settings: &Settings) {
+    let hotkey_state: tauri::State<'_, HotkeyState> = app.state();
+    *hotkey_state.mode.lock().unwrap() = settings.activation_mode;
+    *hotkey_state.hotkey.lock().unwrap() = settings.hotkey.clone();
+
+    let audio_state: tauri::State<'_, AudioState> = app.state();
+    *audio_state.selected_device.lock().unwrap() = settings.audio_device.clone();
+    *audio_state.preferred_devices.lock().unwrap() = settings.preferred_devices.clone();
+    *audio_state.vad_config.lock().unwrap() = settings.vad.clone();
+    *audio_state.preprocess_config.lock().unwrap() = settings.preprocessing.clone();
+    *audio_state.archive_config.lock().unwrap() = settings.recording_archive.clone();
+
+    let stt_state: tauri::State<'_, SttState> = app.state();
+    *stt_state.gpu_backend.lock().unwrap() = settings.gpu_backend.clone();
+    *stt_state.language.lock().unwrap() = settings.language.clone();
+    stt_state.translate.store(settings.translate, Ordering::Relaxed);
+    *stt_state.decoding.lock().unwrap() = settings.decoding.clone();
+    stt_state
+        .streaming_enabled
+        .store(settings.streaming_transcription, Ordering::Relaxed);
+    *stt_state.filter_config.lock().unwrap() = settings.hallucination_filter.clone();
+
+    let cleanup_state: tauri::State<'_, CleanupState> = app.state();
+    *cleanup_state.tier.lock().unwrap() = settings.cleanup_tier;
+    *cleanup_state.cloud_provider.lock().unwrap() = settings.cloud_provider;
+
+    let download_state: tauri::State<'_, DownloadState> = app.state();
+    *download_state.catalog_config.lock().unwrap() = settings.model_catalog.clone();
+    if let Ok(data_dir) = app.path().app_data_dir() {
+        if let Err(e) = catalog::check_cache(&data_dir, &settings.model_catalog) {
+            log::warn!("{}", e);
+        }
+    }
+}
+
 // ---- Pill window commands ----
 
 #[tauri::command]
@@ -74,10 +117,12 @@ pub fn run() {
         // updater plugin requires signing keys — enable when release infrastructure is ready
         // .plugin(tauri_plugin_updater::Builder::new().build())
         // --- Managed state ---
+        .manage(SettingsState::new())
         .manage(HotkeyState::new())
         .manage(AudioState::new())
         .manage(SttState::new())
         .manage(CleanupState::new())
+        .manage(DownloadState::new())
         .manage(InjectionState::new())
         // --- Commands ---
         .invoke_handler(tauri::generate_handler![
@@ -85,13 +130,34 @@ pub fn run() {
             hotkey::set_activation_mode,
             hotkey::get_hotkey,
             hotkey::set_hotkey,
+            hotkey::get_translate_hotkey,
+            hotkey::set_translate_hotkey,
             hotkey::get_is_paused,
             hotkey::set_is_paused,
+            settings::config::get_settings,
             settings::store::get_autostart,
             settings::store::set_autostart,
             audio::list_audio_devices,
             audio::set_audio_device,
             audio::get_audio_device,
+            audio::get_preferred_devices,
+            audio::set_preferred_devices,
+            audio::get_vad_config,
+            audio::set_vad_config,
+            audio::get_preprocess_config,
+            audio::set_preprocess_config,
+            audio::start_mic_test,
+            audio::stop_mic_test,
+            audio::set_device_watch,
+            audio::get_archive_config,
+            audio::set_archive_config,
+            audio::list_recordings,
+            audio::get_recording_path,
+            audio::delete_recording,
+            audio::get_vad_model_status,
+            audio::download_vad_model,
+            audio::verify_vad_model,
+            audio::delete_vad_model,
             stt::list_whisper_models,
             stt::download_whisper_model,
             stt::delete_whisper_model,
@@ -99,9 +165,26 @@ pub fn run() {
             stt::unload_whisper_model,
             stt::get_current_whisper_model,
             stt::transcribe,
+            stt::transcribe_recording,
+            stt::transcribe_file,
             stt::get_gpu_backends,
             stt::set_gpu_backend,
             stt::get_gpu_backend,
+            stt::get_streaming_enabled,
+            stt::set_streaming_enabled,
+            stt::get_hallucination_filter,
+            stt::set_hallucination_filter,
+            stt::list_languages,
+            stt::get_language,
+            stt::set_language,
+            stt::get_translate,
+            stt::set_translate,
+            stt::get_vocabulary,
+            stt::add_vocabulary_term,
+            stt::remove_vocabulary_term,
+            stt::import_vocabulary,
+            stt::get_decoding_options,
+            stt::set_decoding_options,
             cleanup::cleanup_text,
             cleanup::get_cleanup_tier,
             cleanup::set_cleanup_tier,
@@ -117,6 +200,14 @@ pub fn run() {
             cleanup::load_llm_model,
             cleanup::unload_llm_model,
             cleanup::get_current_llm_model,
+            download::list_model_downloads,
+            download::cancel_model_download,
+            user_models::import_model,
+            catalog::get_model_catalog_config,
+            catalog::set_model_catalog_config,
+            catalog::refresh_model_catalog,
+            model_storage::get_models_storage_report,
+            model_storage::prune_models,
             injection::inject_text,
             injection::undo_last_injection,
             injection::get_last_injection_exists,
@@ -135,39 +226,69 @@ pub fn run() {
             let _tray = tray::setup(&handle)?;
             log::info!("System tray registered");
 
-            // 2. Register global hotkey
-            let hotkey_str = hotkey::DEFAULT_HOTKEY;
+            // 2. Load persisted settings and apply them to managed state
+            let app_data_dir = app.path().app_data_dir().ok();
+            let settings_state: tauri::State<'_, SettingsState> = handle.state();
+            let user_settings = match app_data_dir {
+                Some(ref dir) => settings_state.load(dir),
+                None => {
+                    log::warn!("App data directory unavailable, settings will not persist");
+                    settings_state.get()
+                }
+            };
+            apply_settings(&handle, &user_settings);
+            log::info!("Settings loaded (schema v{})", user_settings.version);
+
+            // 3. Register global hotkey
+            let hotkey_str = user_settings.hotkey.as_str();
             eprintln!(">>> Attempting to register hotkey: {}", hotkey_str);
             match hotkey::register(&handle, hotkey_str) {
                 Ok(()) => eprintln!(">>> Hotkey registered OK: {}", hotkey_str),
                 Err(e) => eprintln!(">>> Hotkey registration FAILED: {}", e),
             }
+            if let Some(ref translate_hotkey) = user_settings.translate_hotkey {
+                if let Err(e) = hotkey::rebind_translate(&handle, Some(translate_hotkey)) {
+                    log::warn!("Translate hotkey not registered: {}", e);
+                }
+            }
 
-            // 3. Configure app data directories
-            if let Ok(data_dir) = app.path().app_data_dir() {
+            // 4. Configure app data directories
+            if let Some(data_dir) = app_data_dir {
                 // VAD model path
-                let vad_path = data_dir.join("models").join("vad").join("silero_vad.onnx");
-                if vad_path.exists() {
+                let vad_path = audio::models::model_path(&data_dir);
+                if !vad_path.exists() {
+                    log::info!("VAD model not downloaded, will use energy-based VAD");
+                } else if let Err(e) = audio::models::verify_model(&data_dir) {
+                    log::warn!("VAD model unusable, will use energy-based VAD: {}", e);
+                } else {
                     let audio_state: tauri::State<'_, AudioState> = handle.state();
                     audio_state.set_vad_model_path(vad_path.to_string_lossy().to_string());
                     log::info!("VAD model found: {}", vad_path.display());
-                } else {
-                    log::info!("VAD model not found, will capture without VAD");
                 }
 
                 // STT data directory
                 let stt_state: tauri::State<'_, SttState> = handle.state();
                 *stt_state.app_data_dir.lock().unwrap() = Some(data_dir.clone());
+                *stt_state.vocabulary.lock().unwrap() = stt::vocabulary::load(&data_dir);
 
                 // Cleanup data directory
                 let cleanup_state: tauri::State<'_, CleanupState> = handle.state();
                 *cleanup_state.app_data_dir.lock().unwrap() = Some(data_dir.clone());
 
-                // 4. Preload Whisper model if available (default: base.en)
-                stt::preload_model(&handle, &stt_state, "base.en");
+                // Drop partial downloads abandoned long ago
+                for dir in [
+                    stt::models::models_dir(&data_dir),
+                    cleanup::local_llm::models_dir(&data_dir),
+                    audio::models::models_dir(&data_dir),
+                ] {
+                    download::remove_stale_partials(&dir, download::STALE_PARTIAL_AGE);
+                }
+
+                // 5. Preload the user's Whisper model if available (default: base.en)
+                stt::preload_model(&handle, &stt_state, &user_settings.whisper_model);
             }
 
-            // 5. Show onboarding if not completed
+            // 6. Show onboarding if not completed
             if !settings::store::get_onboarding_completed(handle.clone()) {
                 if let Some(win) = handle.get_webview_window("onboarding") {
                     let _ = win.show();
@@ -176,19 +297,30 @@ pub fn run() {
                 }
             }
 
-            // 6. Initialize pipeline event listeners
+            // 7. Initialize pipeline event listeners
             pipeline::init(&handle);
             log::info!("Pipeline orchestrator initialized");
 
-            // 7. Auto-update check — disabled until signing keys are configured
+            // Report microphones being plugged in and out
+            let audio_state: tauri::State<'_, AudioState> = handle.state();
+            audio_state.device_watcher.start(handle.clone());
+
+            // 8. Auto-update check — disabled until signing keys are configured
             // See tauri-plugin-updater docs for setup instructions.
 
-            // 8. Emit ready event
+            // 9. Emit ready event
             let _ = handle.emit("app://ready", ());
             log::info!("App initialization complete");
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                let audio_state: tauri::State<'_, AudioState> = app.state();
+                audio::end_mic_test(&audio_state);
+                audio_state.device_watcher.stop();
+            }
+        });
 }
@@ -0,0 +1,334 @@
+// Disk usage and integrity of the models directory.
+//
+// Lists every file under `models/whisper`, `models/llm` and `models/vad`,
+// matches it against the catalogs and imported models, checks catalog files
+// against their expected size (and optionally SHA-256), and flags partial
+// downloads and files nothing refers to. `prune_models` deletes the latter,
+// except the files of the models selected in the settings or loaded, which a
+// refreshed manifest may no longer list.
+
+use crate::download::{self, DownloadState, ModelKind};
+use crate::settings::config::SettingsState;
+use crate::{audio, catalog, cleanup, stt, user_models};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// What a file in a models directory is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileStatus {
+    /// Known model file with the expected size (hash not checked).
+    Ok,
+    /// Known model file whose SHA-256 matches the catalog.
+    Verified,
+    /// Known model file with the wrong size (truncated or replaced).
+    SizeMismatch,
+    /// Known model file whose SHA-256 does not match the catalog.
+    ChecksumMismatch,
+    /// Partial file of a download that is running right now.
+    Downloading,
+    /// Partial file left behind by an interrupted download.
+    Partial,
+    /// File no catalog entry refers to, but the selected or loaded model.
+    /// Never pruned.
+    InUse,
+    /// File no catalog entry or imported model refers to.
+    Orphan,
+}
+
+/// One file in a models directory.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Catalog or imported model the file belongs to.
+    pub model_id: Option<String>,
+    pub imported: bool,
+    /// Size the catalog expects (None for unknown files).
+    pub expected_size_bytes: Option<u64>,
+    pub status: FileStatus,
+}
+
+/// One models directory.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageDirectory {
+    /// "whisper", "llm" or "vad".
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub files: Vec<StorageFile>,
+}
+
+/// Disk usage of all models directories.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageReport {
+    pub size_bytes: u64,
+    pub directories: Vec<StorageDirectory>,
+}
+
+/// What `prune` removed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PruneResult {
+    pub removed: Vec<PathBuf>,
+    pub freed_bytes: u64,
+}
+
+/// A file a models directory is expected to contain.
+#[derive(Debug, Clone)]
+pub struct KnownFile {
+    pub model_id: String,
+    pub filename: String,
+    pub size_bytes: u64,
+    pub sha256: Option<String>,
+    pub imported: bool,
+}
+
+/// A models directory and the files it should contain.
+#[derive(Debug, Clone)]
+pub struct ModelDir {
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub known: Vec<KnownFile>,
+    /// Files of the selected or loaded models.
+    pub in_use: Vec<String>,
+}
+
+/// The models directories with the files the catalogs and imports expect in
+/// them. `selected` are the models in use, looked up in the bundled catalog
+/// too so that dropping them from a custom manifest does not orphan them.
+pub fn model_dirs(app_data_dir: &Path, selected: &[(ModelKind, String)]) -> Vec<ModelDir> {
+    let manifest = catalog::load(app_data_dir);
+    let bundled = catalog::default_manifest();
+    let in_use = |kind: ModelKind| -> Vec<String> {
+        selected
+            .iter()
+            .filter(|(k, _)| *k == kind)
+            .filter_map(|(_, id)| {
+                manifest
+                    .find(kind, id)
+                    .or_else(|| bundled.find(kind, id))
+                    .map(|entry| entry.filename.clone())
+            })
+            .collect()
+    };
+    let known = |kind: ModelKind, dir: &Path| -> Vec<KnownFile> {
+        let catalog_files = manifest.entries(kind).iter().map(|entry| KnownFile {
+            model_id: entry.id.clone(),
+            filename: entry.filename.clone(),
+            size_bytes: entry.size_bytes,
+            sha256: entry.sha256.clone(),
+            imported: false,
+        });
+        // Only copied imports live in the models directory
+        let imported_files = user_models::list(app_data_dir, kind)
+            .into_iter()
+            .filter(|m| m.copied && m.path.parent() == Some(dir))
+            .filter_map(|m| {
+                Some(KnownFile {
+                    filename: m.path.file_name()?.to_string_lossy().to_string(),
+                    model_id: m.id,
+                    size_bytes: m.size_bytes,
+                    sha256: None,
+                    imported: true,
+                })
+            });
+        catalog_files.chain(imported_files).collect()
+    };
+
+    let whisper_dir = stt::models::models_dir(app_data_dir);
+    let llm_dir = cleanup::local_llm::models_dir(app_data_dir);
+    let vad_dir = audio::models::models_dir(app_data_dir);
+    vec![
+        ModelDir {
+            name: "whisper",
+            known: known(ModelKind::Whisper, &whisper_dir),
+            in_use: in_use(ModelKind::Whisper),
+            path: whisper_dir,
+        },
+        ModelDir {
+            name: "llm",
+            known: known(ModelKind::Llm, &llm_dir),
+            in_use: in_use(ModelKind::Llm),
+            path: llm_dir,
+        },
+        ModelDir {
+            name: "vad",
+            known: known(ModelKind::Vad, &vad_dir),
+            in_use: in_use(ModelKind::Vad),
+            path: vad_dir,
+        },
+    ]
+}
+
+/// Scan the given directories. `active_partials` are partial files of running
+/// downloads. With `verify_hashes` every catalog file with a pinned SHA-256 is
+/// hashed, which reads the whole file.
+pub fn report(
+    dirs: &[ModelDir],
+    active_partials: &[PathBuf],
+    verify_hashes: bool,
+) -> StorageReport {
+    let directories: Vec<StorageDirectory> = dirs
+        .iter()
+        .map(|dir| scan_dir(dir, active_partials, verify_hashes))
+        .collect();
+    StorageReport {
+        size_bytes: directories.iter().map(|d| d.size_bytes).sum(),
+        directories,
+    }
+}
+
+fn scan_dir(dir: &ModelDir, active_partials: &[PathBuf], verify_hashes: bool) -> StorageDirectory {
+    let mut files = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&dir.path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let metadata = match entry.metadata() {
+                Ok(m) if m.is_file() => m,
+                _ => continue,
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let size_bytes = metadata.len();
+
+            let known = dir.known.iter().find(|k| k.filename == name);
+            let status = match known {
+                Some(known) => check_file(&path, size_bytes, known, verify_hashes),
+                None if active_partials.contains(&path) => FileStatus::Downloading,
+                None if path.extension().and_then(|e| e.to_str()) == Some("tmp") => {
+                    FileStatus::Partial
+                }
+                None if dir.in_use.contains(&name) => FileStatus::InUse,
+                None => FileStatus::Orphan,
+            };
+
+            files.push(StorageFile {
+                name,
+                path,
+                size_bytes,
+                model_id: known.map(|k| k.model_id.clone()),
+                imported: known.is_some_and(|k| k.imported),
+                expected_size_bytes: known.map(|k| k.size_bytes),
+                status,
+            });
+        }
+    }
+
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    StorageDirectory {
+        name: dir.name.to_string(),
+        path: dir.path.clone(),
+        size_bytes: files.iter().map(|f| f.size_bytes).sum(),
+        files,
+    }
+}
+
+/// Catalog sizes are approximate, so only files without a pinned hash are
+/// judged by size; otherwise the hash decides.
+fn check_file(path: &Path, size_bytes: u64, known: &KnownFile, verify_hash: bool) -> FileStatus {
+    match (&known.sha256, verify_hash) {
+        (None, _) if known.size_bytes != size_bytes => FileStatus::SizeMismatch,
+        (Some(expected), true) => match download::sha256_file(path) {
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => FileStatus::Verified,
+            Ok(_) => FileStatus::ChecksumMismatch,
+            Err(e) => {
+                log::warn!("Failed to hash {}: {}", path.display(), e);
+                FileStatus::Ok
+            }
+        },
+        _ => FileStatus::Ok,
+    }
+}
+
+/// Delete orphaned files and leftover partial downloads listed in `report`.
+pub fn prune(report: &StorageReport) -> PruneResult {
+    let mut result = PruneResult::default();
+
+    let prunable = report
+        .directories
+        .iter()
+        .flat_map(|d| &d.files)
+        .filter(|f| matches!(f.status, FileStatus::Orphan | FileStatus::Partial));
+    for file in prunable {
+        match fs::remove_file(&file.path) {
+            Ok(()) => {
+                log::info!("Pruned {}", file.path.display());
+                result.freed_bytes += file.size_bytes;
+                result.removed.push(file.path.clone());
+            }
+            Err(e) => log::warn!("Failed to prune {}: {}", file.path.display(), e),
+        }
+    }
+    result
+}
+
+/// Partial files of the downloads running right now.
+fn active_partials(app_data_dir: &Path, downloads: &DownloadState) -> Vec<PathBuf> {
+    downloads
+        .list()
+        .iter()
+        .filter_map(|job| match job.kind {
+            ModelKind::Whisper => stt::models::model_path(app_data_dir, &job.model_id),
+            ModelKind::Llm => cleanup::local_llm::model_path(app_data_dir, &job.model_id),
+            ModelKind::Vad => Some(audio::models::model_path(app_data_dir)),
+        })
+        .map(|dest| download::partial_path(&dest))
+        .collect()
+}
+
+/// The Whisper model chosen in the settings plus the loaded models.
+fn selected_models(app: &AppHandle) -> Vec<(ModelKind, String)> {
+    let settings = app.state::<SettingsState>().get();
+    let stt_state = app.state::<stt::SttState>();
+    let cleanup_state = app.state::<cleanup::CleanupState>();
+
+    let mut selected = vec![(ModelKind::Whisper, settings.whisper_model)];
+    if let Some(id) = stt_state.engine.current_model_id() {
+        selected.push((ModelKind::Whisper, id));
+    }
+    if let Some(id) = cleanup_state.llm_engine.current_model_id() {
+        selected.push((ModelKind::Llm, id));
+    }
+    selected
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("App data directory unavailable: {}", e))
+}
+
+// --------------- Tauri commands ---------------
+
+/// Disk usage of the models directories. `verify_hashes` also checks the
+/// SHA-256 of catalog files, which takes a while for large models.
+#[tauri::command(async)]
+pub fn get_models_storage_report(
+    app: AppHandle,
+    verify_hashes: bool,
+    downloads: tauri::State<'_, DownloadState>,
+) -> Result<StorageReport, String> {
+    let data_dir = app_data_dir(&app)?;
+    let active = active_partials(&data_dir, &downloads);
+    let dirs = model_dirs(&data_dir, &selected_models(&app));
+    Ok(report(&dirs, &active, verify_hashes))
+}
+
+/// Delete orphaned files and leftover partial downloads. Files of the
+/// selected and loaded models are kept.
+#[tauri::command(async)]
+pub fn prune_models(
+    app: AppHandle,
+    downloads: tauri::State<'_, DownloadState>,
+) -> Result<PruneResult, String> {
+    let data_dir = app_data_dir(&app)?;
+    let active = active_partials(&data_dir, &downloads);
+    let dirs = model_dirs(&data_dir, &selected_models(&app));
+    Ok(prune(&report(&dirs, &active, false)))
+}
+
+#[cfg(test)]
+mod tests;
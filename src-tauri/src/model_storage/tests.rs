@@ -0,0 +1,214 @@
+use super::*;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("model_storage")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// SHA-256 of b"hello".
+const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+fn known(model_id: &str, filename: &str, size_bytes: u64, sha256: Option<&str>) -> KnownFile {
+    KnownFile {
+        model_id: model_id.to_string(),
+        filename: filename.to_string(),
+        size_bytes,
+        sha256: sha256.map(str::to_string),
+        imported: false,
+    }
+}
+
+/// A directory with one file of every status.
+fn populated_dir(name: &str) -> ModelDir {
+    let path = unique_dir(name);
+    fs::write(path.join("good.bin"), b"hello").unwrap();
+    fs::write(path.join("corrupt.bin"), b"jello").unwrap();
+    fs::write(path.join("truncated.bin"), b"hel").unwrap();
+    fs::write(path.join("imported.gguf"), b"gguf!").unwrap();
+    fs::write(path.join("active.bin.tmp"), b"he").unwrap();
+    fs::write(path.join("stale.bin.tmp"), b"hell").unwrap();
+    fs::write(path.join("old-model.bin"), b"whatever").unwrap();
+    fs::create_dir_all(path.join("subdir")).unwrap();
+
+    let mut imported = known("user-mine", "imported.gguf", 5, None);
+    imported.imported = true;
+    ModelDir {
+        name: "whisper",
+        path,
+        known: vec![
+            // Catalog sizes are approximate; a pinned hash takes precedence
+            known("good", "good.bin", 6, Some(HELLO_SHA256)),
+            known("corrupt", "corrupt.bin", 5, Some(HELLO_SHA256)),
+            known("truncated", "truncated.bin", 5, None),
+            known("missing", "missing.bin", 5, None),
+            imported,
+        ],
+        in_use: Vec::new(),
+    }
+}
+
+fn status_of(dir: &StorageDirectory, name: &str) -> FileStatus {
+    dir.files.iter().find(|f| f.name == name).unwrap().status
+}
+
+#[test]
+fn classifies_every_file() {
+    let dir = populated_dir("classify");
+    let active = vec![dir.path.join("active.bin.tmp")];
+    let report = report(&[dir], &active, false);
+
+    let scanned = &report.directories[0];
+    let names: Vec<&str> = scanned.files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "active.bin.tmp",
+            "corrupt.bin",
+            "good.bin",
+            "imported.gguf",
+            "old-model.bin",
+            "stale.bin.tmp",
+            "truncated.bin",
+        ]
+    );
+    assert_eq!(status_of(scanned, "good.bin"), FileStatus::Ok);
+    // Without hashing a same-size corrupt file looks fine
+    assert_eq!(status_of(scanned, "corrupt.bin"), FileStatus::Ok);
+    assert_eq!(
+        status_of(scanned, "truncated.bin"),
+        FileStatus::SizeMismatch
+    );
+    assert_eq!(status_of(scanned, "imported.gguf"), FileStatus::Ok);
+    assert_eq!(
+        status_of(scanned, "active.bin.tmp"),
+        FileStatus::Downloading
+    );
+    assert_eq!(status_of(scanned, "stale.bin.tmp"), FileStatus::Partial);
+    assert_eq!(status_of(scanned, "old-model.bin"), FileStatus::Orphan);
+
+    let imported = scanned
+        .files
+        .iter()
+        .find(|f| f.name == "imported.gguf")
+        .unwrap();
+    assert!(imported.imported);
+    assert_eq!(imported.model_id.as_deref(), Some("user-mine"));
+
+    assert_eq!(scanned.size_bytes, 5 + 5 + 3 + 5 + 2 + 4 + 8);
+    assert_eq!(report.size_bytes, scanned.size_bytes);
+}
+
+#[test]
+fn verify_hashes_detects_corruption() {
+    let dir = populated_dir("verify");
+    let report = report(&[dir], &[], true);
+
+    let scanned = &report.directories[0];
+    assert_eq!(status_of(scanned, "good.bin"), FileStatus::Verified);
+    assert_eq!(
+        status_of(scanned, "corrupt.bin"),
+        FileStatus::ChecksumMismatch
+    );
+    // Without a pinned hash the size decides
+    assert_eq!(
+        status_of(scanned, "truncated.bin"),
+        FileStatus::SizeMismatch
+    );
+    // Imported models have no pinned hash
+    assert_eq!(status_of(scanned, "imported.gguf"), FileStatus::Ok);
+}
+
+#[test]
+fn missing_directory_is_empty() {
+    let dir = ModelDir {
+        name: "vad",
+        path: unique_dir("missing").join("vad"),
+        known: Vec::new(),
+        in_use: Vec::new(),
+    };
+    let report = report(&[dir], &[], false);
+    assert!(report.directories[0].files.is_empty());
+    assert_eq!(report.size_bytes, 0);
+}
+
+#[test]
+fn prune_removes_orphans_and_stale_partials_only() {
+    let dir = populated_dir("prune");
+    let path = dir.path.clone();
+    let active = vec![path.join("active.bin.tmp")];
+
+    let result = prune(&report(std::slice::from_ref(&dir), &active, false));
+    assert_eq!(
+        result.removed,
+        vec![path.join("old-model.bin"), path.join("stale.bin.tmp")]
+    );
+    assert_eq!(result.freed_bytes, 8 + 4);
+
+    for kept in [
+        "good.bin",
+        "corrupt.bin",
+        "truncated.bin",
+        "imported.gguf",
+        "active.bin.tmp",
+    ] {
+        assert!(path.join(kept).exists(), "{} was pruned", kept);
+    }
+
+    // Nothing left to prune
+    assert_eq!(
+        prune(&report(&[dir], &active, false)),
+        PruneResult::default()
+    );
+}
+
+#[test]
+fn selected_model_dropped_from_catalog_is_in_use_and_kept() {
+    let mut dir = populated_dir("in_use");
+    let path = dir.path.clone();
+    dir.in_use = vec!["old-model.bin".to_string()];
+    let active = vec![path.join("active.bin.tmp")];
+
+    let report = report(std::slice::from_ref(&dir), &active, false);
+    assert_eq!(
+        status_of(&report.directories[0], "old-model.bin"),
+        FileStatus::InUse
+    );
+
+    let result = prune(&report);
+    assert_eq!(result.removed, vec![path.join("stale.bin.tmp")]);
+    assert!(path.join("old-model.bin").exists());
+}
+
+#[test]
+fn selected_models_resolve_through_the_bundled_catalog() {
+    let data_dir = unique_dir("selected_bundled");
+    let mut manifest = catalog::default_manifest();
+    let filename = manifest
+        .find(ModelKind::Whisper, "base.en")
+        .unwrap()
+        .filename
+        .clone();
+    manifest.whisper.retain(|e| e.id != "base.en");
+    fs::create_dir_all(data_dir.join("models")).unwrap();
+    fs::write(
+        catalog::cached_manifest_path(&data_dir),
+        serde_json::to_string(&manifest).unwrap(),
+    )
+    .unwrap();
+
+    let selected = vec![
+        (ModelKind::Whisper, "base.en".to_string()),
+        (ModelKind::Llm, "no-such-model".to_string()),
+    ];
+    let dirs = model_dirs(&data_dir, &selected);
+    assert!(dirs[0].known.iter().all(|k| k.filename != filename));
+    assert_eq!(dirs[0].in_use, vec![filename]);
+    assert!(dirs[1].in_use.is_empty());
+    assert!(dirs[2].in_use.is_empty());
+}
@@ -6,8 +6,9 @@
 
 use crate::audio::{self, AudioState};
 use crate::cleanup::{self, CleanupState};
+use crate::hotkey::HotkeyState;
 use crate::injection::{self, InjectionState};
-use crate::stt::SttState;
+use crate::stt::{self, filter, whisper::streaming::StreamingSession, SttState};
 use crate::tray;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -55,7 +56,17 @@ fn on_vozr_start<R: Runtime>(app: &AppHandle<R>) {
         log::error!("Failed to start recording: {}", e);
         let _ = app.emit("pill://error", format!("Microphone error: {}", e));
         tray::set_state(app, tray::TrayState::Error);
+        return;
     }
+
+    // Start transcribing incrementally while the user speaks (if enabled)
+    let stt_state: tauri::State<'_, SttState> = app.state();
+    stt::start_streaming(
+        app,
+        &stt_state,
+        audio_state.speech_buffer.clone(),
+        translate_requested(app),
+    );
 }
 
 /// Called when recording stops: stop audio, run STT → cleanup → inject.
@@ -66,12 +77,19 @@ fn on_vozr_stop<R: Runtime>(app: &AppHandle<R>) {
     audio::stop_recording(&audio_state);
     let audio_buffer = audio::take_speech_buffer(&audio_state);
 
+    // Hand over the streaming session (if any) so its partial work is reused
+    let stt_state: tauri::State<'_, SttState> = app.state();
+    let streaming = stt::take_streaming_session(&stt_state);
+
     let duration_secs = audio_buffer.len() as f32 / 16000.0;
     log::info!("Audio buffer: {} samples ({:.1}s)", audio_buffer.len(), duration_secs);
 
     // Check for empty audio
     if audio_buffer.is_empty() {
         log::info!("No audio captured, nothing to transcribe");
+        if let Some(session) = streaming {
+            session.cancel();
+        }
         let _ = app.emit("audio://no-speech", ());
         tray::set_state(app, tray::TrayState::Idle);
         return;
@@ -82,19 +100,47 @@ fn on_vozr_stop<R: Runtime>(app: &AppHandle<R>) {
 
     // Run the rest of the pipeline on a background thread to avoid blocking the event loop
     let app_handle = app.clone();
+    let translate = translate_requested(app);
 
     std::thread::spawn(move || {
-        run_processing_pipeline(app_handle, audio_buffer);
+        run_processing_pipeline(app_handle, audio_buffer, streaming, translate);
     });
 }
 
+/// Whether the current recording was started with the translate hotkey.
+fn translate_requested<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let hotkey_state: tauri::State<'_, HotkeyState> = app.state();
+    hotkey_state.translate_recording.load(Ordering::Relaxed)
+}
+
 /// The processing pipeline: STT → cleanup → inject → emit result.
 /// Runs on a background thread.
-fn run_processing_pipeline<R: Runtime>(app: AppHandle<R>, audio_buffer: Vec<f32>) {
+fn run_processing_pipeline<R: Runtime>(
+    app: AppHandle<R>,
+    audio_buffer: Vec<f32>,
+    streaming: Option<StreamingSession>,
+    translate: bool,
+) {
+    // Keep the audio for re-transcription if the user opted in
+    audio::archive_recording(&app, &audio_buffer);
+
     // --- Step 1: Speech-to-text ---
     let stt_state: tauri::State<'_, SttState> = app.state();
 
-    let transcription = match stt_state.engine.transcribe(&audio_buffer, Some("en")) {
+    // A streaming session has already transcribed most of the audio; only the tail is left
+    let stt_result = match streaming {
+        Some(session) => session.finish(&stt_state.engine, &audio_buffer),
+        None => {
+            let options = stt_state.transcribe_options(translate);
+            stt_state.engine.transcribe(&audio_buffer, &options)
+        }
+    };
+
+    // Drop segments Whisper invented from silence or noise
+    let filter_config = stt_state.filter_config.lock().unwrap().clone();
+    let stt_result = stt_result.map(|result| filter::apply(result, &audio_buffer, &filter_config));
+
+    let transcription = match stt_result {
         Ok(result) => {
             if result.text.trim().is_empty() {
                 log::info!("Transcription returned empty text");
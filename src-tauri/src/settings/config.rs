@@ -0,0 +1,337 @@
+// Persistent user settings.
+//
+// All user-facing preferences live in a single versioned JSON file under the
+// app data directory. The file is loaded once during app setup and written
+// back whenever a `set_*` command changes a value.
+
+use crate::audio::archive::ArchiveConfig;
+use crate::audio::preprocess::PreprocessConfig;
+use crate::audio::vad::VadConfig;
+use crate::catalog::CatalogConfig;
+use crate::cleanup::{CleanupTier, CloudProvider};
+use crate::hotkey::{ActivationMode, DEFAULT_HOTKEY};
+use crate::stt::filter::HallucinationFilterConfig;
+use crate::stt::language::ENGLISH;
+use crate::stt::whisper::decoding::DecodingOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Current on-disk schema version. Bump this and append a step to
+/// `MIGRATIONS` whenever the shape of `Settings` changes incompatibly.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Settings file name inside the app data directory.
+const SETTINGS_FILENAME: &str = "settings.json";
+
+/// Marker file used by earlier builds to record onboarding completion.
+const LEGACY_ONBOARDING_FLAG: &str = "onboarding_completed";
+
+/// Default Whisper model preloaded on startup.
+pub const DEFAULT_WHISPER_MODEL: &str = "base.en";
+
+/// All persisted user settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// Schema version of the file this was loaded from.
+    pub version: u32,
+    /// Global hotkey string (e.g. "F9").
+    pub hotkey: String,
+    /// Optional second hotkey that dictates with translation to English.
+    pub translate_hotkey: Option<String>,
+    /// Toggle or hold-to-talk.
+    pub activation_mode: ActivationMode,
+    /// Selected input device ID (None = system default).
+    pub audio_device: Option<String>,
+    /// Input devices to fall back to, most preferred first.
+    pub preferred_devices: Vec<String>,
+    /// VAD thresholds and timeouts.
+    pub vad: VadConfig,
+    /// High-pass, noise suppression and gain control.
+    pub preprocessing: PreprocessConfig,
+    /// Whether and how long to keep recordings on disk.
+    pub recording_archive: ArchiveConfig,
+    /// Preferred GPU backend ("cpu", "cuda", "vulkan").
+    pub gpu_backend: String,
+    /// Whisper model to preload on startup.
+    pub whisper_model: String,
+    /// Transcription language code, or "auto" to detect it per recording.
+    pub language: String,
+    /// Translate every recording into English (multilingual models only).
+    pub translate: bool,
+    /// Transcribe incrementally while the user is still speaking.
+    pub streaming_transcription: bool,
+    /// Whisper decoding parameters.
+    pub decoding: DecodingOptions,
+    /// Thresholds for dropping hallucinated segments.
+    pub hallucination_filter: HallucinationFilterConfig,
+    /// Selected cleanup tier.
+    pub cleanup_tier: CleanupTier,
+    /// Selected cloud LLM provider.
+    pub cloud_provider: CloudProvider,
+    /// Model manifest and download mirror.
+    pub model_catalog: CatalogConfig,
+    /// Whether the onboarding flow has been completed.
+    pub onboarding_completed: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            hotkey: DEFAULT_HOTKEY.to_string(),
+            translate_hotkey: None,
+            activation_mode: ActivationMode::Toggle,
+            audio_device: None,
+            preferred_devices: Vec::new(),
+            vad: VadConfig::default(),
+            preprocessing: PreprocessConfig::default(),
+            recording_archive: ArchiveConfig::default(),
+            gpu_backend: "cpu".to_string(),
+            whisper_model: DEFAULT_WHISPER_MODEL.to_string(),
+            language: ENGLISH.to_string(),
+            translate: false,
+            streaming_transcription: false,
+            decoding: DecodingOptions::default(),
+            hallucination_filter: HallucinationFilterConfig::default(),
+            cleanup_tier: CleanupTier::Rules,
+            cloud_provider: CloudProvider::OpenAi,
+            model_catalog: CatalogConfig::default(),
+            onboarding_completed: false,
+        }
+    }
+}
+
+/// Tauri-managed state holding the live settings and where to persist them.
+pub struct SettingsState {
+    settings: Mutex<Settings>,
+    /// Path to the settings file (None until `load` is called).
+    path: Mutex<Option<PathBuf>>,
+}
+
+impl SettingsState {
+    pub fn new() -> Self {
+        Self {
+            settings: Mutex::new(Settings::default()),
+            path: Mutex::new(None),
+        }
+    }
+
+    /// Load settings from the app data directory. Call once during app setup.
+    /// Never fails — missing or unreadable files fall back to defaults.
+    pub fn load(&self, app_data_dir: &Path) -> Settings {
+        let settings = load_from_dir(app_data_dir);
+        *self.path.lock().unwrap() = Some(settings_path(app_data_dir));
+        *self.settings.lock().unwrap() = settings.clone();
+        settings
+    }
+
+    /// Snapshot of the current settings.
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Apply a change and write the result to disk. The live settings only
+    /// change once the write has succeeded, so callers should update
+    /// subsystem state after this returns `Ok`.
+    /// Before `load` has been called the change is kept in memory only.
+    pub fn update<F: FnOnce(&mut Settings)>(&self, f: F) -> Result<(), String> {
+        let mut settings = self.settings.lock().unwrap();
+        let mut updated = settings.clone();
+        f(&mut updated);
+        if let Some(path) = self.path.lock().unwrap().as_deref() {
+            save(path, &updated)?;
+        }
+        *settings = updated;
+        Ok(())
+    }
+}
+
+/// Path of the settings file inside the app data directory.
+pub fn settings_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(SETTINGS_FILENAME)
+}
+
+/// Read, migrate and return the settings stored in `app_data_dir`.
+/// Any migration result is written back so the file is always current.
+pub fn load_from_dir(app_data_dir: &Path) -> Settings {
+    let path = settings_path(app_data_dir);
+
+    let (mut settings, mut needs_save) = if path.exists() {
+        match read_settings(&path) {
+            Ok((settings, migrated)) => (settings, migrated),
+            Err(e) => {
+                log::warn!("Settings file unreadable, using defaults: {}", e);
+                backup_corrupt_file(&path);
+                (Settings::default(), true)
+            }
+        }
+    } else {
+        log::info!("No settings file found, using defaults");
+        (Settings::default(), true)
+    };
+    settings.reset_invalid_sections();
+
+    // Fold the pre-settings onboarding marker file into the settings file
+    let legacy_flag = app_data_dir.join(LEGACY_ONBOARDING_FLAG);
+    let import_legacy_flag = legacy_flag.is_file();
+    if import_legacy_flag {
+        settings.onboarding_completed = true;
+        needs_save = true;
+    }
+
+    if needs_save {
+        match save(&path, &settings) {
+            Ok(()) if import_legacy_flag => {
+                let _ = fs::remove_file(&legacy_flag);
+                log::info!("Imported legacy onboarding flag into settings");
+            }
+            Ok(()) => {}
+            Err(e) => log::warn!("Failed to write settings: {}", e),
+        }
+    }
+
+    settings
+}
+
+impl Settings {
+    /// Replace sections that fail validation (e.g. edited by hand) with their
+    /// defaults. The file keeps the original values.
+    fn reset_invalid_sections(&mut self) {
+        reset_if_invalid("VAD", &mut self.vad, VadConfig::validate);
+        reset_if_invalid("decoding", &mut self.decoding, DecodingOptions::validate);
+        reset_if_invalid(
+            "preprocessing",
+            &mut self.preprocessing,
+            PreprocessConfig::validate,
+        );
+        reset_if_invalid(
+            "hallucination filter",
+            &mut self.hallucination_filter,
+            HallucinationFilterConfig::validate,
+        );
+        reset_if_invalid(
+            "recording archive",
+            &mut self.recording_archive,
+            ArchiveConfig::validate,
+        );
+    }
+}
+
+fn reset_if_invalid<T: Default>(
+    section: &str,
+    config: &mut T,
+    validate: fn(&T) -> Result<(), String>,
+) {
+    if let Err(e) = validate(config) {
+        log::warn!("Invalid {} settings, using defaults: {}", section, e);
+        *config = T::default();
+    }
+}
+
+/// Parse and migrate a settings file. Returns the settings and whether the
+/// on-disk copy is out of date.
+fn read_settings(path: &Path) -> Result<(Settings, bool), String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read settings: {}", e))?;
+    let value: Value =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid settings JSON: {}", e))?;
+
+    let file_version = schema_version(&value);
+    let settings = migrate(value)?;
+    Ok((settings, file_version < SETTINGS_VERSION))
+}
+
+/// Write settings atomically.
+pub fn save(path: &Path, settings: &Settings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    write_atomic(path, json.as_bytes())
+}
+
+/// Write a file atomically (temp file + rename), creating its directory if
+/// needed. Readers see either the old or the new contents, never a partial file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    fs::write(&tmp_path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to finalize {}: {}", path.display(), e)
+    })
+}
+
+// ---- Migration ----
+
+/// A single schema upgrade, from version N to N + 1.
+type Migration = fn(&mut Value);
+
+/// Migrations indexed by source version: `MIGRATIONS[n]` upgrades vN → vN+1.
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+/// v0 files predate schema versioning; they already use the v1 field names.
+fn migrate_v0_to_v1(_value: &mut Value) {}
+
+/// Schema version recorded in a raw settings value (missing = 0).
+fn schema_version(value: &Value) -> u32 {
+    value
+        .get("version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// Upgrade a raw settings value to the current schema and deserialize it.
+/// Files written by a newer build are read best-effort; unknown fields are ignored.
+pub fn migrate(mut value: Value) -> Result<Settings, String> {
+    if !value.is_object() {
+        return Err("Settings file is not a JSON object".to_string());
+    }
+
+    let from = schema_version(&value);
+    if from > SETTINGS_VERSION {
+        log::warn!(
+            "Settings file version {} is newer than supported version {}",
+            from,
+            SETTINGS_VERSION
+        );
+    } else {
+        for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+            step(&mut value);
+            log::info!("Migrated settings from v{} to v{}", version, version + 1);
+        }
+    }
+
+    let mut settings: Settings =
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse settings: {}", e))?;
+    settings.version = SETTINGS_VERSION;
+    Ok(settings)
+}
+
+/// Keep a copy of an unreadable settings file so the user's values are not lost silently.
+fn backup_corrupt_file(path: &Path) {
+    let backup = path.with_extension("json.bak");
+    match fs::rename(path, &backup) {
+        Ok(()) => log::warn!("Moved unreadable settings to {}", backup.display()),
+        Err(e) => log::warn!("Failed to back up unreadable settings: {}", e),
+    }
+}
+
+// ---- Tauri commands ----
+
+#[tauri::command]
+pub fn get_settings(state: tauri::State<'_, SettingsState>) -> Settings {
+    state.get()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,213 @@
+use super::*;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("settings")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn defaults_match_previous_hardcoded_values() {
+    let settings = Settings::default();
+    assert_eq!(settings.version, SETTINGS_VERSION);
+    assert_eq!(settings.hotkey, DEFAULT_HOTKEY);
+    assert_eq!(settings.activation_mode, ActivationMode::Toggle);
+    assert!(settings.audio_device.is_none());
+    assert!(settings.preferred_devices.is_empty());
+    assert_eq!(settings.gpu_backend, "cpu");
+    assert_eq!(settings.whisper_model, "base.en");
+    assert_eq!(settings.cleanup_tier, CleanupTier::Rules);
+    assert_eq!(settings.cloud_provider, CloudProvider::OpenAi);
+    assert!(!settings.onboarding_completed);
+}
+
+#[test]
+fn missing_file_creates_defaults() {
+    let dir = unique_dir("missing_file");
+    let settings = load_from_dir(&dir);
+    assert_eq!(settings, Settings::default());
+    assert!(settings_path(&dir).exists());
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let dir = unique_dir("round_trip");
+    let settings = Settings {
+        hotkey: "ctrl+shift+d".to_string(),
+        activation_mode: ActivationMode::Hold,
+        audio_device: Some("USB Mic".to_string()),
+        preferred_devices: vec!["ALSA:Headset".to_string(), "ALSA:Webcam".to_string()],
+        recording_archive: ArchiveConfig {
+            enabled: true,
+            max_recordings: 10,
+            ..ArchiveConfig::default()
+        },
+        cleanup_tier: CleanupTier::CloudLlm,
+        cloud_provider: CloudProvider::Anthropic,
+        ..Settings::default()
+    };
+    save(&settings_path(&dir), &settings).unwrap();
+
+    assert_eq!(load_from_dir(&dir), settings);
+}
+
+#[test]
+fn state_update_persists_to_disk() {
+    let dir = unique_dir("state_update");
+    let state = SettingsState::new();
+    state.load(&dir);
+
+    state
+        .update(|s| s.gpu_backend = "vulkan".to_string())
+        .unwrap();
+
+    assert_eq!(state.get().gpu_backend, "vulkan");
+    assert_eq!(load_from_dir(&dir).gpu_backend, "vulkan");
+}
+
+#[test]
+fn failed_state_update_leaves_settings_unchanged() {
+    let dir = unique_dir("state_update_fails");
+    let state = SettingsState::new();
+    state.load(&dir);
+
+    // A directory in the way makes the final rename fail
+    let path = settings_path(&dir);
+    fs::remove_file(&path).unwrap();
+    fs::create_dir(&path).unwrap();
+
+    let result = state.update(|s| s.gpu_backend = "vulkan".to_string());
+    assert!(result.is_err());
+    assert_eq!(state.get().gpu_backend, "cpu");
+}
+
+#[test]
+fn state_update_before_load_is_in_memory_only() {
+    let state = SettingsState::new();
+    state
+        .update(|s| s.whisper_model = "tiny.en".to_string())
+        .unwrap();
+    assert_eq!(state.get().whisper_model, "tiny.en");
+}
+
+#[test]
+fn write_atomic_replaces_contents_without_leftovers() {
+    let dir = unique_dir("write_atomic");
+    let path = dir.join("nested").join("data.json");
+
+    write_atomic(&path, b"first").unwrap();
+    write_atomic(&path, b"second").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+    let names: Vec<_> = fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(names, vec!["data.json"]);
+}
+
+#[test]
+fn partial_file_fills_missing_fields_with_defaults() {
+    let dir = unique_dir("partial");
+    fs::write(
+        settings_path(&dir),
+        r#"{ "version": 1, "activationMode": "hold" }"#,
+    )
+    .unwrap();
+
+    let settings = load_from_dir(&dir);
+    assert_eq!(settings.activation_mode, ActivationMode::Hold);
+    assert_eq!(settings.hotkey, DEFAULT_HOTKEY);
+}
+
+#[test]
+fn invalid_sections_fall_back_to_defaults() {
+    let dir = unique_dir("invalid_sections");
+    let contents = r#"{
+        "version": 1,
+        "hotkey": "F8",
+        "vad": { "startThreshold": 5.0 },
+        "decoding": { "temperature": -1.0 },
+        "preprocessing": { "maxGainDb": 100.0 },
+        "recordingArchive": { "enabled": true, "maxRecordings": 0 },
+        "hallucinationFilter": { "silenceRms": -1.0 }
+    }"#;
+    fs::write(settings_path(&dir), contents).unwrap();
+
+    let settings = load_from_dir(&dir);
+    assert_eq!(settings.hotkey, "F8");
+    assert_eq!(settings.vad, VadConfig::default());
+    assert_eq!(settings.decoding, DecodingOptions::default());
+    assert_eq!(settings.preprocessing, PreprocessConfig::default());
+    // Keeping no recordings at all would delete the whole archive
+    assert_eq!(settings.recording_archive, ArchiveConfig::default());
+    assert_eq!(
+        settings.hallucination_filter,
+        HallucinationFilterConfig::default()
+    );
+    assert_eq!(fs::read_to_string(settings_path(&dir)).unwrap(), contents);
+}
+
+#[test]
+fn unversioned_file_is_migrated_and_rewritten() {
+    let dir = unique_dir("unversioned");
+    fs::write(settings_path(&dir), r#"{ "hotkey": "F8" }"#).unwrap();
+
+    let settings = load_from_dir(&dir);
+    assert_eq!(settings.hotkey, "F8");
+    assert_eq!(settings.version, SETTINGS_VERSION);
+
+    let on_disk: Value =
+        serde_json::from_str(&fs::read_to_string(settings_path(&dir)).unwrap()).unwrap();
+    assert_eq!(on_disk["version"], SETTINGS_VERSION);
+}
+
+#[test]
+fn newer_file_is_read_without_being_rewritten() {
+    let dir = unique_dir("newer");
+    let contents = r#"{ "version": 99, "hotkey": "F7", "futureField": true }"#;
+    fs::write(settings_path(&dir), contents).unwrap();
+
+    let settings = load_from_dir(&dir);
+    assert_eq!(settings.hotkey, "F7");
+    assert_eq!(fs::read_to_string(settings_path(&dir)).unwrap(), contents);
+}
+
+#[test]
+fn corrupt_file_falls_back_to_defaults_and_is_backed_up() {
+    let dir = unique_dir("corrupt");
+    fs::write(settings_path(&dir), "{ not json").unwrap();
+
+    let settings = load_from_dir(&dir);
+    assert_eq!(settings, Settings::default());
+    assert!(dir.join("settings.json.bak").exists());
+}
+
+#[test]
+fn legacy_onboarding_flag_is_imported() {
+    let dir = unique_dir("legacy_onboarding");
+    fs::write(dir.join(LEGACY_ONBOARDING_FLAG), "1").unwrap();
+
+    let settings = load_from_dir(&dir);
+    assert!(settings.onboarding_completed);
+    assert!(!dir.join(LEGACY_ONBOARDING_FLAG).exists());
+    assert!(load_from_dir(&dir).onboarding_completed);
+}
+
+#[test]
+fn migrate_rejects_non_object() {
+    assert!(migrate(serde_json::json!([1, 2, 3])).is_err());
+}
+
+#[test]
+fn settings_serialize_camel_case() {
+    let json = serde_json::to_string(&Settings::default()).unwrap();
+    assert!(json.contains("\"activationMode\":\"toggle\""));
+    assert!(json.contains("\"cleanupTier\":\"rules\""));
+    assert!(json.contains("\"onboardingCompleted\":false"));
+}
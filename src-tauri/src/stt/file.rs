@@ -0,0 +1,236 @@
+// Transcription of audio files from disk (voice memos, meeting recordings).
+//
+// A file is decoded to 16kHz mono and transcribed in chunks of about a
+// minute, each cut at the quietest moment shortly before the boundary so
+// words are rarely split. Every chunk reports progress as an
+// `stt://file-progress` event. Segments are shifted to file time and joined
+// into one result; with auto-detection the language of the first chunk is
+// kept for the rest of the file. Cleanup, when requested, runs on each
+// chunk's text separately: the LLM tiers only return a few hundred tokens.
+
+use super::filter::{self, HallucinationFilterConfig};
+use super::whisper::{
+    pad_to_min_input, segments_text, TranscribeOptions, TranscriptionResult, WhisperEngine,
+};
+use crate::audio::capture::TARGET_SAMPLE_RATE;
+use crate::audio::decode;
+use crate::cleanup::{CleanupResult, CleanupTier};
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Target chunk length (seconds).
+const CHUNK_SECS: usize = 60;
+
+/// How far before the chunk boundary to look for a quiet cut (seconds).
+const SEARCH_SECS: usize = 5;
+
+/// Energy is compared over 10 ms frames when looking for the cut.
+const FRAME_SIZE: usize = 160;
+
+/// Longest text sent to cleanup at once (bytes), well inside the local LLM's
+/// 512-token answer at about four characters per token. A minute of speech
+/// is usually shorter.
+const CLEANUP_MAX_CHARS: usize = 1_200;
+
+/// Progress event payload, sent after each chunk.
+#[derive(Clone, Serialize)]
+pub struct FileProgressEvent {
+    pub path: String,
+    /// Chunks done so far (1-based) and in total.
+    pub chunk: usize,
+    pub chunks: usize,
+    pub processed_ms: u64,
+    pub total_ms: u64,
+    pub percent: f32,
+    /// Text of the chunk just transcribed.
+    pub text: String,
+}
+
+/// Result of transcribing a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTranscription {
+    pub transcription: TranscriptionResult,
+    /// Text after the cleanup tier, when cleanup was requested.
+    pub cleaned_text: Option<String>,
+    pub cleanup_tier: Option<CleanupTier>,
+}
+
+/// Split `samples` into chunks of at most `chunk_len` samples. Each cut is
+/// placed in the quietest `FRAME_SIZE` frame of the `search_len` samples
+/// before the boundary.
+pub fn chunk_ranges(samples: &[f32], chunk_len: usize, search_len: usize) -> Vec<Range<usize>> {
+    let energy = |at: usize| -> f32 { samples[at..at + FRAME_SIZE].iter().map(|s| s * s).sum() };
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while samples.len() - start > chunk_len {
+        let boundary = start + chunk_len;
+        let search_start = boundary - search_len.min(chunk_len - FRAME_SIZE);
+        let quietest = (search_start..boundary - FRAME_SIZE + 1)
+            .step_by(FRAME_SIZE)
+            .min_by(|&a, &b| energy(a).total_cmp(&energy(b)))
+            .unwrap_or(boundary - FRAME_SIZE);
+        let cut = quietest + FRAME_SIZE / 2;
+        ranges.push(start..cut);
+        start = cut;
+    }
+    if start < samples.len() {
+        ranges.push(start..samples.len());
+    }
+    ranges
+}
+
+/// Split `text` into pieces of at most `max_chars` bytes for cleanup, cut
+/// after the last sentence end that fits (or the last space for run-on text).
+pub fn cleanup_pieces(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text.trim();
+    while rest.len() > max_chars {
+        let mut end = max_chars;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let window = &rest[..end];
+        let cut = window
+            .rfind(['.', '?', '!'])
+            .map(|i| i + 1)
+            .or_else(|| window.rfind(' '))
+            .filter(|&cut| cut > 0)
+            .unwrap_or(end);
+        pieces.push(rest[..cut].trim_end());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// Clean the text of each chunk piece by piece with `clean` and join the
+/// results. The tier reported is the lowest any piece fell back to.
+pub fn clean_chunks(
+    texts: &[String],
+    max_chars: usize,
+    mut clean: impl FnMut(&str) -> CleanupResult,
+) -> Option<(String, CleanupTier)> {
+    let rank = |tier: CleanupTier| match tier {
+        CleanupTier::Rules => 0,
+        CleanupTier::LocalLlm => 1,
+        CleanupTier::CloudLlm => 2,
+    };
+    let mut cleaned: Vec<String> = Vec::new();
+    let mut tier: Option<CleanupTier> = None;
+    for piece in texts
+        .iter()
+        .flat_map(|text| cleanup_pieces(text, max_chars))
+    {
+        let result = clean(piece);
+        if tier.is_none_or(|t| rank(result.tier_used) < rank(t)) {
+            tier = Some(result.tier_used);
+        }
+        if !result.text.trim().is_empty() {
+            cleaned.push(result.text.trim().to_string());
+        }
+    }
+    tier.map(|tier| (cleaned.join(" "), tier))
+}
+
+fn samples_to_ms(samples: usize) -> u64 {
+    samples as u64 * 1000 / TARGET_SAMPLE_RATE as u64
+}
+
+/// Decode and transcribe the audio file at `path`, then run `cleanup` (if
+/// given) on the text of every chunk.
+pub fn transcribe_file<R: Runtime>(
+    app: &AppHandle<R>,
+    engine: &WhisperEngine,
+    path: &Path,
+    options: &TranscribeOptions,
+    filter_config: &HallucinationFilterConfig,
+    cleanup: Option<impl FnMut(&str) -> CleanupResult>,
+) -> Result<FileTranscription, String> {
+    if !decode::is_supported(path) {
+        return Err(format!(
+            "Unsupported audio file (supported: {})",
+            decode::SUPPORTED_EXTENSIONS.join(", ")
+        ));
+    }
+    // Fail before spending time on decoding
+    if !engine.is_loaded() {
+        return Err("No Whisper model loaded".to_string());
+    }
+
+    let start = Instant::now();
+    let audio = decode::decode_file(path)?;
+    let samples = audio.samples;
+    if samples.is_empty() {
+        return Err("Audio file contains no audio".to_string());
+    }
+    let total_ms = samples_to_ms(samples.len());
+    log::info!(
+        "Transcribing {} ({}Hz, {} ch, {:.1}s)",
+        path.display(),
+        audio.sample_rate,
+        audio.channels,
+        total_ms as f32 / 1000.0
+    );
+
+    let rate = TARGET_SAMPLE_RATE as usize;
+    let ranges = chunk_ranges(&samples, CHUNK_SECS * rate, SEARCH_SECS * rate);
+    let mut options = options.clone();
+    let mut segments = Vec::new();
+    let mut texts = Vec::new();
+    let mut first: Option<TranscriptionResult> = None;
+
+    for (index, range) in ranges.iter().enumerate() {
+        let chunk = &samples[range.clone()];
+        // The last chunk can be shorter than whisper's minimum input
+        let decoded = engine.transcribe(&pad_to_min_input(chunk), &options)?;
+        let result = filter::apply(decoded, chunk, filter_config);
+
+        let offset_ms = samples_to_ms(range.start);
+        segments.extend(result.segments.iter().map(|s| s.shifted(offset_ms)));
+        texts.push(result.text.clone());
+
+        let _ = app.emit(
+            "stt://file-progress",
+            FileProgressEvent {
+                path: path.to_string_lossy().to_string(),
+                chunk: index + 1,
+                chunks: ranges.len(),
+                processed_ms: samples_to_ms(range.end),
+                total_ms,
+                percent: range.end as f32 / samples.len() as f32 * 100.0,
+                text: result.text.clone(),
+            },
+        );
+
+        if first.is_none() {
+            if options.language.is_none() {
+                options.language = Some(result.language.clone());
+            }
+            first = Some(result);
+        }
+    }
+
+    let cleaned = cleanup.and_then(|clean| clean_chunks(&texts, CLEANUP_MAX_CHARS, clean));
+
+    let first = first.expect("at least one chunk");
+    let transcription = TranscriptionResult {
+        text: segments_text(&segments),
+        segments,
+        duration_ms: start.elapsed().as_millis() as u64,
+        audio_duration_ms: total_ms,
+        ..first
+    };
+    Ok(FileTranscription {
+        transcription,
+        cleaned_text: cleaned.as_ref().map(|(text, _)| text.clone()),
+        cleanup_tier: cleaned.map(|(_, tier)| tier),
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,183 @@
+use super::*;
+use crate::stt::whisper::MIN_INPUT_SAMPLES;
+
+/// Loud "speech" everywhere except silent gaps at the given sample ranges.
+fn speech_with_gaps(len: usize, gaps: &[Range<usize>]) -> Vec<f32> {
+    (0..len)
+        .map(|i| {
+            if gaps.iter().any(|gap| gap.contains(&i)) {
+                0.0
+            } else {
+                0.5 * (i as f32 * 0.3).sin()
+            }
+        })
+        .collect()
+}
+
+fn assert_covers(ranges: &[Range<usize>], len: usize, chunk_len: usize) {
+    assert_eq!(ranges.first().unwrap().start, 0);
+    assert_eq!(ranges.last().unwrap().end, len);
+    for pair in ranges.windows(2) {
+        assert_eq!(pair[0].end, pair[1].start);
+    }
+    for range in ranges {
+        assert!(!range.is_empty());
+        assert!(range.len() <= chunk_len, "{:?}", range);
+    }
+}
+
+#[test]
+fn empty_audio_has_no_chunks() {
+    assert!(chunk_ranges(&[], 16_000, 1_600).is_empty());
+}
+
+#[test]
+fn short_audio_is_one_chunk() {
+    let samples = speech_with_gaps(10_000, &[]);
+    assert_eq!(chunk_ranges(&samples, 16_000, 1_600), vec![0..10_000]);
+    let exact = speech_with_gaps(16_000, &[]);
+    assert_eq!(chunk_ranges(&exact, 16_000, 1_600), vec![0..16_000]);
+}
+
+#[test]
+fn long_audio_is_covered_without_gaps_or_overlap() {
+    let samples = speech_with_gaps(100_000, &[]);
+    let ranges = chunk_ranges(&samples, 16_000, 3_200);
+    assert_covers(&ranges, samples.len(), 16_000);
+    assert_eq!(ranges.len(), 7);
+}
+
+#[test]
+fn cuts_in_the_pause_before_the_boundary() {
+    // Pauses 0.5 s before the first boundary and right at the second one
+    let samples = speech_with_gaps(40_000, &[7_000..7_600, 14_400..15_000]);
+    let ranges = chunk_ranges(&samples, 8_000, 1_600);
+    assert_covers(&ranges, samples.len(), 8_000);
+    assert!((7_000..7_600).contains(&ranges[0].end), "{:?}", ranges);
+    assert!((14_400..15_000).contains(&ranges[1].end), "{:?}", ranges);
+}
+
+#[test]
+fn short_last_chunk_is_padded_for_whisper() {
+    // 60.5 s with a pause just before 60 s: the last chunk is about 0.75 s
+    let rate = TARGET_SAMPLE_RATE as usize;
+    let pause = 59 * rate + 3 * rate / 4..60 * rate;
+    let samples = speech_with_gaps(60 * rate + rate / 2, std::slice::from_ref(&pause));
+    let ranges = chunk_ranges(&samples, CHUNK_SECS * rate, SEARCH_SECS * rate);
+    assert_eq!(ranges.len(), 2);
+
+    let last = &samples[ranges[1].clone()];
+    assert!(last.len() < MIN_INPUT_SAMPLES, "{}", last.len());
+    let padded = pad_to_min_input(last);
+    assert_eq!(padded.len(), MIN_INPUT_SAMPLES);
+    assert_eq!(&padded[..last.len()], last);
+    assert!(padded[last.len()..].iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn pause_outside_the_search_window_is_ignored() {
+    let samples = speech_with_gaps(20_000, &[2_000..3_000, 18_000..18_500]);
+    let ranges = chunk_ranges(&samples, 8_000, 1_600);
+    assert!(ranges[0].end >= 8_000 - 1_600);
+}
+
+// ---- Cleanup ----
+
+/// A cleanup tier that upper-cases its input and records what it was sent.
+fn recording_cleaner(
+    sent: &mut Vec<String>,
+    tier: CleanupTier,
+) -> impl FnMut(&str) -> CleanupResult + '_ {
+    move |text| {
+        sent.push(text.to_string());
+        CleanupResult {
+            text: text.to_uppercase(),
+            tier_used: tier,
+            duration_ms: 0,
+        }
+    }
+}
+
+#[test]
+fn short_text_is_one_piece() {
+    assert_eq!(
+        cleanup_pieces("  Hello there.  ", 100),
+        vec!["Hello there."]
+    );
+    assert!(cleanup_pieces("   ", 100).is_empty());
+}
+
+#[test]
+fn pieces_end_at_sentences() {
+    let text = "One two three. Four five six. Seven eight nine.";
+    assert_eq!(
+        cleanup_pieces(text, 30),
+        vec!["One two three. Four five six.", "Seven eight nine."]
+    );
+}
+
+#[test]
+fn run_on_text_is_cut_at_spaces() {
+    let pieces = cleanup_pieces("alpha beta gamma delta épsilon", 12);
+    assert_eq!(pieces, vec!["alpha beta", "gamma delta", "épsilon"]);
+    // A word longer than the limit is cut on a character boundary
+    assert_eq!(cleanup_pieces("ééééé", 3), vec!["é", "é", "é", "é", "é"]);
+}
+
+#[test]
+fn long_transcript_is_cleaned_in_bounded_pieces() {
+    // Ten minutes of fast speech: ten chunks of about 300 words each, more
+    // than one cleanup request can take
+    let sentence =
+        "So we went over the quarterly numbers again and agreed to revisit them next week.";
+    let texts = vec![vec![sentence; 20].join(" "); 10];
+    assert!(texts[0].len() > CLEANUP_MAX_CHARS);
+
+    let mut sent = Vec::new();
+    let (cleaned, tier) = clean_chunks(
+        &texts,
+        CLEANUP_MAX_CHARS,
+        recording_cleaner(&mut sent, CleanupTier::LocalLlm),
+    )
+    .unwrap();
+
+    assert_eq!(tier, CleanupTier::LocalLlm);
+    assert!(sent.len() > texts.len());
+    assert!(sent.iter().all(|piece| piece.len() <= CLEANUP_MAX_CHARS));
+    assert!(sent.iter().all(|piece| piece.ends_with('.')));
+    // Nothing lost or duplicated
+    assert_eq!(cleaned, texts.join(" ").to_uppercase());
+}
+
+#[test]
+fn lowest_fallback_tier_is_reported() {
+    let texts = vec!["First chunk.".to_string(), "Second chunk.".to_string()];
+    let mut calls = 0;
+    let (_, tier) = clean_chunks(&texts, 100, |text| {
+        calls += 1;
+        CleanupResult {
+            text: text.to_string(),
+            tier_used: if calls == 2 {
+                CleanupTier::Rules
+            } else {
+                CleanupTier::CloudLlm
+            },
+            duration_ms: 0,
+        }
+    })
+    .unwrap();
+    assert_eq!(tier, CleanupTier::Rules);
+}
+
+#[test]
+fn nothing_to_clean_gives_none() {
+    let mut sent = Vec::new();
+    let texts = vec![String::new(), " ".to_string()];
+    assert!(clean_chunks(
+        &texts,
+        100,
+        recording_cleaner(&mut sent, CleanupTier::Rules)
+    )
+    .is_none());
+    assert!(sent.is_empty());
+}
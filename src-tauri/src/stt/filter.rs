@@ -0,0 +1,232 @@
+// Post-STT hallucination filter.
+//
+// Whisper tends to "hear" stock phrases ("Thank you for watching.") or emit
+// annotations ("[BLANK_AUDIO]") on silent or noisy buffers. This filter drops
+// such segments using the segment's confidence, how repetitive its text is,
+// a blocklist of known phantom phrases, and the loudness of the audio under it.
+// Confidence and blocklist matches only drop segments over quiet audio: whisper
+// does not give us a real no-speech probability to tell them apart from speech.
+
+use super::whisper::{segments_text, Segment, TranscriptionResult, SAMPLES_PER_MS};
+use crate::audio::capture::compute_rms;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Phrases Whisper commonly produces on silence or background noise. Short
+/// phrases people actually dictate ("thank you", "bye") are left out.
+pub const DEFAULT_BLOCKLIST: &[&str] = &[
+    "thank you for watching",
+    "thanks for watching",
+    "thank you so much for watching",
+    "please subscribe",
+    "like and subscribe",
+    "subtitles by the amara org community",
+];
+
+/// Filter thresholds. Persisted as part of the user settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HallucinationFilterConfig {
+    /// Master switch.
+    pub enabled: bool,
+    /// Segments over quiet audio with a lower average token probability are
+    /// dropped.
+    pub min_avg_token_prob: f32,
+    /// Text compressing better than this is a repetition loop. Same scale as
+    /// Whisper's own check (zlib).
+    pub compression_ratio_threshold: f32,
+    /// Segments over audio quieter than this RMS are dropped outright.
+    pub silence_rms: f32,
+    /// Low-confidence segments and blocklisted phrases are only dropped over
+    /// audio quieter than this RMS, so a spoken "thank you" survives.
+    #[serde(alias = "blocklistMaxRms")]
+    pub quiet_rms: f32,
+    /// Known phantom phrases (compared case- and punctuation-insensitively).
+    pub blocklist: Vec<String>,
+}
+
+impl Default for HallucinationFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_avg_token_prob: 0.4,
+            compression_ratio_threshold: 2.4,
+            silence_rms: 0.002,
+            quiet_rms: 0.02,
+            blocklist: DEFAULT_BLOCKLIST.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl HallucinationFilterConfig {
+    /// Reject thresholds outside the range of what they measure.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.min_avg_token_prob) {
+            return Err("Minimum token probability must be between 0.0 and 1.0".to_string());
+        }
+        if !(1.0..=10.0).contains(&self.compression_ratio_threshold) {
+            return Err("Compression ratio threshold must be between 1.0 and 10.0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.silence_rms) {
+            return Err("Silence level must be between 0.0 and 1.0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.quiet_rms) {
+            return Err("Quiet level must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Why a segment was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DropReason {
+    /// Bracketed annotation such as "[BLANK_AUDIO]" or "(music)".
+    Annotation,
+    /// The audio under the segment is effectively silent.
+    Silence,
+    /// Whisper was unsure of the text and the audio is quiet.
+    LowConfidence,
+    /// The text is a repetition loop.
+    Repetitive,
+    /// A known phantom phrase over quiet audio.
+    Blocklisted,
+}
+
+/// Decide whether a segment should be dropped. `audio` is the 16kHz buffer
+/// the segment timestamps refer to.
+pub fn check_segment(
+    segment: &Segment,
+    audio: &[f32],
+    config: &HallucinationFilterConfig,
+) -> Option<DropReason> {
+    let text = segment.text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    if is_annotation(text) {
+        return Some(DropReason::Annotation);
+    }
+
+    let rms = segment_rms(segment, audio);
+    if rms < config.silence_rms {
+        return Some(DropReason::Silence);
+    }
+
+    let quiet = rms < config.quiet_rms;
+    if quiet && segment.avg_token_prob < config.min_avg_token_prob {
+        return Some(DropReason::LowConfidence);
+    }
+
+    if text.len() >= MIN_COMPRESSION_TEXT_LEN
+        && compression_ratio(text) > config.compression_ratio_threshold
+    {
+        return Some(DropReason::Repetitive);
+    }
+
+    if quiet && is_blocklisted(text, &config.blocklist) {
+        return Some(DropReason::Blocklisted);
+    }
+
+    None
+}
+
+/// Remove hallucinated segments from a transcription and rebuild its text.
+pub fn apply(
+    result: TranscriptionResult,
+    audio: &[f32],
+    config: &HallucinationFilterConfig,
+) -> TranscriptionResult {
+    if !config.enabled {
+        return result;
+    }
+
+    let segments: Vec<Segment> = result
+        .segments
+        .into_iter()
+        .filter(|segment| match check_segment(segment, audio, config) {
+            Some(reason) => {
+                log::info!(
+                    "Dropped hallucinated segment ({:?}): {:?}",
+                    reason,
+                    segment.text.trim()
+                );
+                false
+            }
+            None => true,
+        })
+        .collect();
+
+    TranscriptionResult {
+        text: segments_text(&segments),
+        segments,
+        ..result
+    }
+}
+
+/// RMS of the audio covered by a segment. Segments that fall entirely
+/// outside the buffer (e.g. in padding) count as silent.
+fn segment_rms(segment: &Segment, audio: &[f32]) -> f32 {
+    let start = (segment.start_ms as usize * SAMPLES_PER_MS).min(audio.len());
+    let end = (segment.end_ms as usize * SAMPLES_PER_MS).min(audio.len());
+    if end <= start {
+        return 0.0;
+    }
+    compute_rms(&audio[start..end])
+}
+
+/// True for text that is entirely a bracketed annotation, e.g. "[BLANK_AUDIO]",
+/// "(upbeat music)" or "*sigh*".
+fn is_annotation(text: &str) -> bool {
+    let pairs = [('[', ']'), ('(', ')'), ('*', '*'), ('♪', '♪')];
+    pairs
+        .iter()
+        .any(|&(open, close)| text.len() > 1 && text.starts_with(open) && text.ends_with(close))
+}
+
+/// Lowercase, drop punctuation and collapse whitespace.
+fn normalize_phrase(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// True if the whole segment text matches a blocklisted phrase.
+fn is_blocklisted(text: &str, blocklist: &[String]) -> bool {
+    let normalized = normalize_phrase(text);
+    !normalized.is_empty()
+        && blocklist
+            .iter()
+            .any(|phrase| normalize_phrase(phrase) == normalized)
+}
+
+/// Shorter text is never judged repetitive: zlib's fixed overhead makes the
+/// ratio meaningless for a few words, and "yes yes yes" is a real answer.
+const MIN_COMPRESSION_TEXT_LEN: usize = 50;
+
+/// Compression ratio of `text` under zlib, as computed by Whisper to detect
+/// repetition loops. Normal prose scores ~1.0–1.5; loops score well above 2.
+pub fn compression_ratio(text: &str) -> f32 {
+    let bytes = text.as_bytes();
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(bytes)
+        .and_then(|()| encoder.finish())
+        .map(|c| c.len())
+        .unwrap_or(bytes.len());
+    bytes.len() as f32 / compressed as f32
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,338 @@
+use super::*;
+use serde::Deserialize;
+
+/// A span of synthetic audio with a given loudness.
+#[derive(Deserialize)]
+struct AudioSpan {
+    ms: usize,
+    rms: f32,
+}
+
+/// A Whisper output together with the text that should survive filtering.
+/// The audio is described as synthetic spans of a given loudness; there are
+/// no fixtures recorded from a real microphone yet.
+#[derive(Deserialize)]
+struct Fixture {
+    description: String,
+    audio: Vec<AudioSpan>,
+    segments: Vec<Segment>,
+    expected_text: String,
+}
+
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "silence_thanks",
+        include_str!("fixtures/silence_thanks.json"),
+    ),
+    (
+        "blank_audio_annotation",
+        include_str!("fixtures/blank_audio_annotation.json"),
+    ),
+    (
+        "spoken_thank_you",
+        include_str!("fixtures/spoken_thank_you.json"),
+    ),
+    (
+        "quiet_thank_you",
+        include_str!("fixtures/quiet_thank_you.json"),
+    ),
+    (
+        "repetition_loop",
+        include_str!("fixtures/repetition_loop.json"),
+    ),
+    (
+        "low_confidence_noise",
+        include_str!("fixtures/low_confidence_noise.json"),
+    ),
+    (
+        "segment_in_padding",
+        include_str!("fixtures/segment_in_padding.json"),
+    ),
+];
+
+/// Build a 16kHz buffer of 440Hz sine spans with the requested RMS levels.
+fn synth_audio(spans: &[AudioSpan]) -> Vec<f32> {
+    spans
+        .iter()
+        .flat_map(|span| {
+            let amplitude = span.rms * std::f32::consts::SQRT_2;
+            (0..span.ms * SAMPLES_PER_MS).map(move |i| {
+                amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin()
+            })
+        })
+        .collect()
+}
+
+fn segment(
+    start_ms: u64,
+    end_ms: u64,
+    text: &str,
+    avg_token_prob: f32,
+    first_token_uncertainty: f32,
+) -> Segment {
+    Segment {
+        start_ms,
+        end_ms,
+        text: text.to_string(),
+        avg_token_prob,
+        first_token_uncertainty,
+    }
+}
+
+fn result_of(segments: Vec<Segment>) -> TranscriptionResult {
+    TranscriptionResult {
+        text: segments_text(&segments),
+        segments,
+        language: "en".to_string(),
+        language_probability: None,
+        translated: false,
+        duration_ms: 100,
+        audio_duration_ms: 3000,
+    }
+}
+
+#[test]
+fn fixtures_filter_to_expected_text() {
+    let config = HallucinationFilterConfig::default();
+    for (name, json) in FIXTURES {
+        let fixture: Fixture = serde_json::from_str(json)
+            .unwrap_or_else(|e| panic!("Fixture {} is invalid: {}", name, e));
+        let audio = synth_audio(&fixture.audio);
+
+        let filtered = apply(result_of(fixture.segments), &audio, &config);
+        assert_eq!(
+            filtered.text, fixture.expected_text,
+            "{}: {}",
+            name, fixture.description
+        );
+    }
+}
+
+#[test]
+fn disabled_filter_keeps_everything() {
+    let config = HallucinationFilterConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    let audio = vec![0.0; 16000];
+    let result = result_of(vec![segment(0, 1000, " Thanks for watching!", 0.5, 0.9)]);
+
+    let filtered = apply(result.clone(), &audio, &config);
+    assert_eq!(filtered.text, result.text);
+    assert_eq!(filtered.segments, result.segments);
+}
+
+#[test]
+fn apply_preserves_timing_fields() {
+    let config = HallucinationFilterConfig::default();
+    let audio = vec![0.0; 16000];
+    let filtered = apply(
+        result_of(vec![segment(0, 1000, " [BLANK_AUDIO]", 0.9, 0.1)]),
+        &audio,
+        &config,
+    );
+    assert!(filtered.segments.is_empty());
+    assert_eq!(filtered.duration_ms, 100);
+    assert_eq!(filtered.audio_duration_ms, 3000);
+}
+
+#[test]
+fn check_segment_reports_reason() {
+    let config = HallucinationFilterConfig::default();
+    let loud = synth_audio(&[AudioSpan { ms: 1000, rms: 0.1 }]);
+    let quiet = synth_audio(&[AudioSpan {
+        ms: 1000,
+        rms: 0.01,
+    }]);
+    let silent = vec![0.0; 16000];
+
+    let cases = [
+        (
+            segment(0, 1000, " *sigh*", 0.9, 0.0),
+            &loud,
+            Some(DropReason::Annotation),
+        ),
+        (
+            segment(0, 1000, " Hello there.", 0.9, 0.0),
+            &silent,
+            Some(DropReason::Silence),
+        ),
+        (
+            segment(0, 1000, " Hello there.", 0.1, 0.9),
+            &quiet,
+            Some(DropReason::LowConfidence),
+        ),
+        (segment(0, 1000, " Hello there.", 0.1, 0.9), &loud, None),
+        (
+            segment(0, 1000, " Please subscribe!", 0.8, 0.1),
+            &quiet,
+            Some(DropReason::Blocklisted),
+        ),
+        (
+            segment(0, 1000, " Please subscribe!", 0.8, 0.1),
+            &loud,
+            None,
+        ),
+        (segment(0, 1000, " Hello there.", 0.9, 0.0), &loud, None),
+    ];
+    for (seg, audio, expected) in cases {
+        assert_eq!(
+            check_segment(&seg, audio, &config),
+            expected,
+            "{:?}",
+            seg.text
+        );
+    }
+}
+
+#[test]
+fn uncertain_first_token_is_not_treated_as_no_speech() {
+    // A name as the first word is unlikely, but the segment is still speech
+    let config = HallucinationFilterConfig::default();
+    let quiet = synth_audio(&[AudioSpan {
+        ms: 1000,
+        rms: 0.01,
+    }]);
+    let seg = segment(0, 1000, " Szymon, ship it today.", 0.7, 0.98);
+    assert_eq!(check_segment(&seg, &quiet, &config), None);
+}
+
+#[test]
+fn old_blocklist_rms_setting_is_read_as_quiet_rms() {
+    let config: HallucinationFilterConfig =
+        serde_json::from_str(r#"{ "blocklistMaxRms": 0.05 }"#).unwrap();
+    assert_eq!(config.quiet_rms, 0.05);
+}
+
+#[test]
+fn custom_blocklist_is_used() {
+    let config = HallucinationFilterConfig {
+        blocklist: vec!["Ευχαριστώ".to_string()],
+        ..Default::default()
+    };
+    let audio = synth_audio(&[AudioSpan {
+        ms: 1000,
+        rms: 0.01,
+    }]);
+
+    let custom = segment(0, 1000, " ευχαριστώ!", 0.8, 0.1);
+    assert_eq!(
+        check_segment(&custom, &audio, &config),
+        Some(DropReason::Blocklisted)
+    );
+
+    // Default phrases are no longer blocked once the list is replaced
+    let default_phrase = segment(0, 1000, " Thanks for watching.", 0.8, 0.1);
+    assert_eq!(check_segment(&default_phrase, &audio, &config), None);
+}
+
+#[test]
+fn blocklist_matches_whole_segment_only() {
+    let blocklist = vec!["thank you".to_string()];
+    assert!(is_blocklisted(" Thank you!", &blocklist));
+    assert!(is_blocklisted("THANK  YOU...", &blocklist));
+    assert!(!is_blocklisted("Thank you for the update.", &blocklist));
+    assert!(!is_blocklisted("...", &blocklist));
+}
+
+#[test]
+fn annotation_detection() {
+    assert!(is_annotation("[BLANK_AUDIO]"));
+    assert!(is_annotation("(keyboard clicking)"));
+    assert!(is_annotation("♪ ♪"));
+    assert!(!is_annotation("(see above) for details"));
+    assert!(!is_annotation("*"));
+}
+
+#[test]
+fn compression_ratio_separates_prose_from_loops() {
+    let prose = "Could you move the standup to Thursday and invite the design team?";
+    let looped = "I'm sorry. I'm sorry. I'm sorry. I'm sorry. I'm sorry. I'm sorry.";
+
+    assert!(
+        compression_ratio(prose) < 1.5,
+        "{}",
+        compression_ratio(prose)
+    );
+    assert!(
+        compression_ratio(looped) > 2.4,
+        "{}",
+        compression_ratio(looped)
+    );
+    assert_eq!(compression_ratio(""), 0.0);
+}
+
+#[test]
+fn short_repeated_answers_are_not_repetitive() {
+    let config = HallucinationFilterConfig::default();
+    let audio = synth_audio(&[AudioSpan { ms: 2000, rms: 0.1 }]);
+    for text in [
+        " Yes yes yes yes yes.",
+        " No, no, no, no.",
+        " Ha ha ha ha ha ha ha.",
+    ] {
+        let seg = segment(0, 2000, text, 0.8, 0.1);
+        assert_eq!(check_segment(&seg, &audio, &config), None, "{}", text);
+    }
+}
+
+#[test]
+fn quiet_dictated_sign_offs_are_kept() {
+    // -40 dBFS: a normal level for a laptop mic at arm's length
+    let config = HallucinationFilterConfig::default();
+    let audio = synth_audio(&[AudioSpan {
+        ms: 1000,
+        rms: 0.01,
+    }]);
+    for text in [" Thank you.", " Bye.", " You."] {
+        let seg = segment(0, 1000, text, 0.8, 0.3);
+        assert_eq!(check_segment(&seg, &audio, &config), None, "{}", text);
+    }
+}
+
+#[test]
+fn segment_rms_clamps_to_buffer() {
+    let audio = synth_audio(&[AudioSpan { ms: 500, rms: 0.1 }]);
+    let inside = segment(0, 500, "a", 1.0, 0.0);
+    let partly = segment(250, 2000, "a", 1.0, 0.0);
+    let outside = segment(600, 900, "a", 1.0, 0.0);
+
+    assert!((segment_rms(&inside, &audio) - 0.1).abs() < 0.005);
+    assert!((segment_rms(&partly, &audio) - 0.1).abs() < 0.005);
+    assert_eq!(segment_rms(&outside, &audio), 0.0);
+}
+
+#[test]
+fn config_deserializes_with_defaults() {
+    let config: HallucinationFilterConfig =
+        serde_json::from_str(r#"{ "enabled": false, "silenceRms": 0.01 }"#).unwrap();
+    assert!(!config.enabled);
+    assert_eq!(config.silence_rms, 0.01);
+    assert_eq!(config.blocklist.len(), DEFAULT_BLOCKLIST.len());
+}
+
+#[test]
+fn validate_rejects_out_of_range_thresholds() {
+    assert!(HallucinationFilterConfig::default().validate().is_ok());
+    let invalid = [
+        HallucinationFilterConfig {
+            min_avg_token_prob: 1.5,
+            ..Default::default()
+        },
+        HallucinationFilterConfig {
+            compression_ratio_threshold: 0.0,
+            ..Default::default()
+        },
+        HallucinationFilterConfig {
+            silence_rms: -0.1,
+            ..Default::default()
+        },
+        HallucinationFilterConfig {
+            quiet_rms: f32::NAN,
+            ..Default::default()
+        },
+    ];
+    for config in invalid {
+        assert!(config.validate().is_err(), "{:?}", config);
+    }
+}
@@ -0,0 +1,87 @@
+// Transcription language selection.
+//
+// The user either picks a language whisper supports (ISO 639-1 code such as
+// "de") or "auto", in which case whisper detects the spoken language per
+// recording. English-only (`.en`) models can only transcribe English.
+
+use serde::Serialize;
+
+/// Setting value that asks whisper to detect the spoken language.
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// The only language English-only models can transcribe.
+pub const ENGLISH: &str = "en";
+
+/// A language the user can pick.
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageInfo {
+    /// Whisper language code ("en", "de", ...) or "auto".
+    pub code: String,
+    /// Human-readable name.
+    pub name: String,
+}
+
+/// All selectable languages: "auto" followed by every language whisper knows.
+pub fn list_languages() -> Vec<LanguageInfo> {
+    let mut languages = vec![LanguageInfo {
+        code: AUTO_LANGUAGE.to_string(),
+        name: "Auto-detect".to_string(),
+    }];
+
+    for id in 0..=whisper_rs::get_lang_max_id() {
+        if let (Some(code), Some(name)) = (
+            whisper_rs::get_lang_str(id),
+            whisper_rs::get_lang_str_full(id),
+        ) {
+            languages.push(LanguageInfo {
+                code: code.to_string(),
+                name: capitalize(name),
+            });
+        }
+    }
+
+    languages
+}
+
+/// Whether `language` is "auto" or a language code whisper supports.
+pub fn is_supported(language: &str) -> bool {
+    language == AUTO_LANGUAGE || whisper_rs::get_lang_id(language).is_some()
+}
+
+/// Whisper's language parameter for a language setting (None = auto-detect).
+pub fn whisper_language(language: &str) -> Option<&str> {
+    if language == AUTO_LANGUAGE {
+        None
+    } else {
+        Some(language)
+    }
+}
+
+/// Whether a catalog model ID names an English-only model.
+pub fn is_english_only_model(model_id: &str) -> bool {
+    model_id.ends_with(".en")
+}
+
+/// Refuse language settings the model cannot transcribe.
+/// Auto-detection is allowed everywhere; English-only models simply resolve it to English.
+pub fn validate(language: &str, multilingual: bool) -> Result<(), String> {
+    if multilingual || language == AUTO_LANGUAGE || language == ENGLISH {
+        Ok(())
+    } else {
+        Err(format!(
+            "Language '{}' needs a multilingual model. The selected model only supports English.",
+            language
+        ))
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
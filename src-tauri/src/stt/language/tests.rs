@@ -0,0 +1,38 @@
+use super::*;
+
+#[test]
+fn auto_maps_to_detection() {
+    assert_eq!(whisper_language(AUTO_LANGUAGE), None);
+    assert_eq!(whisper_language("de"), Some("de"));
+    assert_eq!(whisper_language(ENGLISH), Some("en"));
+}
+
+#[test]
+fn english_only_models_are_recognized() {
+    assert!(is_english_only_model("base.en"));
+    assert!(is_english_only_model("medium.en"));
+    assert!(!is_english_only_model("small"));
+    assert!(!is_english_only_model("medium"));
+}
+
+#[test]
+fn english_only_model_refuses_other_languages() {
+    assert!(validate("de", false).is_err());
+    assert!(validate("ja", false).is_err());
+    assert!(validate(ENGLISH, false).is_ok());
+    assert!(validate(AUTO_LANGUAGE, false).is_ok());
+}
+
+#[test]
+fn multilingual_model_accepts_any_language() {
+    assert!(validate("de", true).is_ok());
+    assert!(validate(ENGLISH, true).is_ok());
+    assert!(validate(AUTO_LANGUAGE, true).is_ok());
+}
+
+#[test]
+fn capitalize_language_names() {
+    assert_eq!(capitalize("german"), "German");
+    assert_eq!(capitalize("haitian creole"), "Haitian creole");
+    assert_eq!(capitalize(""), "");
+}
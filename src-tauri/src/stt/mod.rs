@@ -1,11 +1,24 @@
+pub mod file;
+pub mod filter;
+pub mod language;
 pub mod models;
+pub mod vocabulary;
 pub mod whisper;
 
+use crate::cleanup::CleanupState;
+use crate::download::{DownloadState, ModelKind};
+use crate::settings::config::SettingsState;
+use file::FileTranscription;
+use filter::HallucinationFilterConfig;
+use language::LanguageInfo;
 use models::ModelInfo;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter, Manager, Runtime};
-use whisper::{GpuBackendInfo, TranscriptionResult, WhisperEngine};
+use whisper::streaming::StreamingSession;
+use whisper::decoding::DecodingOptions;
+use whisper::{GpuBackendInfo, TranscribeOptions, TranscriptionResult, WhisperEngine};
 
 /// Tauri-managed state for the STT subsystem.
 pub struct SttState {
@@ -14,6 +27,20 @@ pub struct SttState {
     pub app_data_dir: Mutex<Option<PathBuf>>,
     /// User's preferred GPU backend ("cpu", "cuda", "vulkan").
     pub gpu_backend: Mutex<String>,
+    /// Transcription language code, or "auto" to detect it per recording.
+    pub language: Mutex<String>,
+    /// Translate every recording into English.
+    pub translate: AtomicBool,
+    /// User vocabulary passed to whisper as an initial prompt.
+    pub vocabulary: Mutex<Vec<String>>,
+    /// Whisper decoding parameters.
+    pub decoding: Mutex<DecodingOptions>,
+    /// Whether to transcribe incrementally while recording.
+    pub streaming_enabled: AtomicBool,
+    /// Thresholds for dropping hallucinated segments.
+    pub filter_config: Mutex<HallucinationFilterConfig>,
+    /// Active streaming session for the current recording, if any.
+    streaming_session: Mutex<Option<StreamingSession>>,
 }
 
 impl SttState {
@@ -22,6 +49,25 @@ impl SttState {
             engine: WhisperEngine::new(),
             app_data_dir: Mutex::new(None),
             gpu_backend: Mutex::new("cpu".to_string()),
+            language: Mutex::new(language::ENGLISH.to_string()),
+            translate: AtomicBool::new(false),
+            vocabulary: Mutex::new(Vec::new()),
+            decoding: Mutex::new(DecodingOptions::default()),
+            streaming_enabled: AtomicBool::new(false),
+            filter_config: Mutex::new(HallucinationFilterConfig::default()),
+            streaming_session: Mutex::new(None),
+        }
+    }
+
+    /// Transcription options from the current settings.
+    /// `translate` forces translation for this recording (translate hotkey).
+    pub fn transcribe_options(&self, translate: bool) -> TranscribeOptions {
+        TranscribeOptions {
+            language: language::whisper_language(&self.language.lock().unwrap())
+                .map(str::to_string),
+            translate: translate || self.translate.load(Ordering::Relaxed),
+            vocabulary: self.vocabulary.lock().unwrap().clone(),
+            decoding: self.decoding.lock().unwrap().clone(),
         }
     }
 
@@ -76,6 +122,31 @@ pub fn preload_model<R: Runtime>(app: &AppHandle<R>, state: &SttState, model_id:
     }
 }
 
+/// Start streaming transcription over the live speech buffer, if enabled.
+/// Call right after audio capture starts.
+pub fn start_streaming<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &SttState,
+    speech_buffer: Arc<Mutex<Vec<f32>>>,
+    translate: bool,
+) {
+    if !state.streaming_enabled.load(Ordering::Relaxed) {
+        return;
+    }
+    if !state.engine.is_loaded() {
+        log::info!("No Whisper model loaded, skipping streaming transcription");
+        return;
+    }
+
+    let session = StreamingSession::start(app, speech_buffer, state.transcribe_options(translate));
+    *state.streaming_session.lock().unwrap() = Some(session);
+}
+
+/// Take the streaming session of the recording that just ended, if any.
+pub fn take_streaming_session(state: &SttState) -> Option<StreamingSession> {
+    state.streaming_session.lock().unwrap().take()
+}
+
 // --------------- Tauri commands ---------------
 
 #[tauri::command]
@@ -84,16 +155,19 @@ pub fn list_whisper_models(state: tauri::State<'_, SttState>) -> Result<Vec<Mode
     Ok(models::list_models(&data_dir))
 }
 
-#[tauri::command]
+// Runs off the main thread (blocking I/O) so `cancel_model_download` can be handled meanwhile
+#[tauri::command(async)]
 pub fn download_whisper_model(
     app: AppHandle,
     model_id: String,
     state: tauri::State<'_, SttState>,
+    downloads: tauri::State<'_, DownloadState>,
 ) -> Result<String, String> {
     let data_dir = state.data_dir()?;
 
-    // Run download on a background thread (blocking I/O)
-    let path = models::download_model(&app, &data_dir, &model_id)?;
+    let job = downloads.begin(ModelKind::Whisper, &model_id)?;
+    let mirror = downloads.mirror_url();
+    let path = models::download_model(&app, &data_dir, &job, mirror.as_deref())?;
     Ok(path.to_string_lossy().to_string())
 }
 
@@ -116,6 +190,7 @@ pub fn delete_whisper_model(
 pub fn load_whisper_model(
     model_id: String,
     state: tauri::State<'_, SttState>,
+    settings: tauri::State<'_, SettingsState>,
 ) -> Result<(), String> {
     let data_dir = state.data_dir()?;
 
@@ -129,10 +204,24 @@ pub fn load_whisper_model(
         ));
     }
 
+    language::validate(
+        &state.language.lock().unwrap(),
+        models::is_multilingual(&data_dir, &model_id),
+    )?;
+
+    // Remember the choice so it is preloaded on the next launch
+    let previous = settings.get().whisper_model;
+    settings.update(|s| s.whisper_model = model_id.clone())?;
+
     let use_gpu = *state.gpu_backend.lock().unwrap() != "cpu";
-    state
+    if let Err(e) = state
         .engine
         .load_model(&path.to_string_lossy(), &model_id, use_gpu)
+    {
+        let _ = settings.update(|s| s.whisper_model = previous);
+        return Err(e);
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -150,7 +239,44 @@ pub fn transcribe(
     audio: Vec<f32>,
     state: tauri::State<'_, SttState>,
 ) -> Result<TranscriptionResult, String> {
-    state.engine.transcribe(&audio, Some("en"))
+    let result = state.engine.transcribe(&audio, &state.transcribe_options(false))?;
+    Ok(filter::apply(result, &audio, &state.filter_config.lock().unwrap()))
+}
+
+/// Transcribe an archived recording again with the loaded model and the
+/// current settings (e.g. after switching to a bigger model).
+#[tauri::command(async)]
+pub fn transcribe_recording(
+    id: String,
+    state: tauri::State<'_, SttState>,
+) -> Result<TranscriptionResult, String> {
+    let audio = crate::audio::archive::load(&state.data_dir()?, &id)?;
+    let result = state.engine.transcribe(&audio, &state.transcribe_options(false))?;
+    Ok(filter::apply(result, &audio, &state.filter_config.lock().unwrap()))
+}
+
+/// Transcribe an audio file (WAV, FLAC, MP3 or Ogg Vorbis) with the loaded
+/// model, reporting progress as `stt://file-progress` events. With `cleanup`
+/// the text of each chunk also goes through the selected cleanup tier.
+#[tauri::command(async)]
+pub fn transcribe_file(
+    app: AppHandle,
+    path: String,
+    cleanup: bool,
+    state: tauri::State<'_, SttState>,
+    cleanup_state: tauri::State<'_, CleanupState>,
+) -> Result<FileTranscription, String> {
+    let options = state.transcribe_options(false);
+    let filter_config = state.filter_config.lock().unwrap().clone();
+    let clean = |text: &str| crate::cleanup::run_cleanup(&cleanup_state, text);
+    file::transcribe_file(
+        &app,
+        &state.engine,
+        Path::new(&path),
+        &options,
+        &filter_config,
+        cleanup.then_some(clean),
+    )
 }
 
 #[tauri::command]
@@ -162,11 +288,13 @@ pub fn get_gpu_backends() -> Vec<GpuBackendInfo> {
 pub fn set_gpu_backend(
     backend: String,
     state: tauri::State<'_, SttState>,
+    settings: tauri::State<'_, SettingsState>,
 ) -> Result<(), String> {
     let backends = whisper::available_backends();
     if !backends.iter().any(|b| b.id == backend && b.available) {
         return Err(format!("GPU backend '{}' is not available", backend));
     }
+    settings.update(|s| s.gpu_backend = backend.clone())?;
     *state.gpu_backend.lock().unwrap() = backend;
     Ok(())
 }
@@ -175,3 +303,164 @@ pub fn set_gpu_backend(
 pub fn get_gpu_backend(state: tauri::State<'_, SttState>) -> String {
     state.gpu_backend.lock().unwrap().clone()
 }
+
+#[tauri::command]
+pub fn get_streaming_enabled(state: tauri::State<'_, SttState>) -> bool {
+    state.streaming_enabled.load(Ordering::Relaxed)
+}
+
+#[tauri::command]
+pub fn set_streaming_enabled(
+    enabled: bool,
+    state: tauri::State<'_, SttState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    settings.update(|s| s.streaming_transcription = enabled)?;
+    state.streaming_enabled.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_hallucination_filter(state: tauri::State<'_, SttState>) -> HallucinationFilterConfig {
+    state.filter_config.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_hallucination_filter(
+    config: HallucinationFilterConfig,
+    state: tauri::State<'_, SttState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.update(|s| s.hallucination_filter = config.clone())?;
+    *state.filter_config.lock().unwrap() = config;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_languages() -> Vec<LanguageInfo> {
+    language::list_languages()
+}
+
+#[tauri::command]
+pub fn get_language(state: tauri::State<'_, SttState>) -> String {
+    state.language.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_language(
+    language: String,
+    state: tauri::State<'_, SttState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    if !language::is_supported(&language) {
+        return Err(format!("Unsupported language: {}", language));
+    }
+    if let Some(multilingual) = state.engine.is_multilingual() {
+        language::validate(&language, multilingual)?;
+    }
+
+    settings.update(|s| s.language = language.clone())?;
+    *state.language.lock().unwrap() = language;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_translate(state: tauri::State<'_, SttState>) -> bool {
+    state.translate.load(Ordering::Relaxed)
+}
+
+#[tauri::command]
+pub fn set_translate(
+    enabled: bool,
+    state: tauri::State<'_, SttState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    if enabled && state.engine.is_multilingual() == Some(false) {
+        return Err(
+            "Translation needs a multilingual model. The selected model only supports English."
+                .to_string(),
+        );
+    }
+
+    settings.update(|s| s.translate = enabled)?;
+    state.translate.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_vocabulary(state: tauri::State<'_, SttState>) -> Vec<String> {
+    state.vocabulary.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn add_vocabulary_term(term: String, state: tauri::State<'_, SttState>) -> Result<(), String> {
+    let data_dir = state.data_dir()?;
+    let mut terms = state.vocabulary.lock().unwrap();
+    // Only apply the change once it is on disk
+    let mut updated = terms.clone();
+    if vocabulary::add_term(&mut updated, &term)? {
+        vocabulary::save(&data_dir, &updated)?;
+        *terms = updated;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remove_vocabulary_term(
+    term: String,
+    state: tauri::State<'_, SttState>,
+) -> Result<(), String> {
+    let data_dir = state.data_dir()?;
+    let mut terms = state.vocabulary.lock().unwrap();
+    let mut updated = terms.clone();
+    if vocabulary::remove_term(&mut updated, &term) {
+        vocabulary::save(&data_dir, &updated)?;
+        *terms = updated;
+    }
+    Ok(())
+}
+
+/// Import terms from a text file (one per line or comma-separated).
+/// Returns the number of new terms added; invalid entries are skipped.
+#[tauri::command]
+pub fn import_vocabulary(path: String, state: tauri::State<'_, SttState>) -> Result<usize, String> {
+    let data_dir = state.data_dir()?;
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read vocabulary file: {}", e))?;
+
+    let mut terms = state.vocabulary.lock().unwrap();
+    let mut updated = terms.clone();
+    let mut added = 0;
+    for term in vocabulary::parse_import(&text) {
+        match vocabulary::add_term(&mut updated, term) {
+            Ok(true) => added += 1,
+            Ok(false) => {}
+            Err(e) => log::warn!("Skipping vocabulary entry {:?}: {}", term, e),
+        }
+    }
+
+    if added > 0 {
+        vocabulary::save(&data_dir, &updated)?;
+        *terms = updated;
+    }
+    log::info!("Imported {} vocabulary terms from {}", added, path);
+    Ok(added)
+}
+
+#[tauri::command]
+pub fn get_decoding_options(state: tauri::State<'_, SttState>) -> DecodingOptions {
+    state.decoding.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_decoding_options(
+    options: DecodingOptions,
+    state: tauri::State<'_, SttState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    options.validate()?;
+    settings.update(|s| s.decoding = options.clone())?;
+    *state.decoding.lock().unwrap() = options;
+    Ok(())
+}
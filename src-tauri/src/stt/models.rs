@@ -1,13 +1,12 @@
+use super::language;
+use crate::catalog;
+use crate::download::{self, Download, DownloadError, DownloadJob, ModelKind};
+use crate::user_models::{self, UserModel};
 use serde::Serialize;
 use std::fs;
-use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, Runtime};
 
-/// Base URL for downloading whisper.cpp GGML models from HuggingFace.
-const HF_BASE_URL: &str =
-    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
-
 /// Metadata for a single Whisper model.
 #[derive(Debug, Clone, Serialize)]
 pub struct ModelInfo {
@@ -17,65 +16,14 @@ pub struct ModelInfo {
     pub size_bytes: u64,
     pub description: String,
     pub downloaded: bool,
+    /// Imported by the user rather than part of the built-in catalog.
+    pub imported: bool,
 }
 
-/// Catalog entry (compile-time).
-struct ModelCatalogEntry {
-    id: &'static str,
-    name: &'static str,
-    filename: &'static str,
-    size_bytes: u64,
-    description: &'static str,
-}
-
-const MODEL_CATALOG: &[ModelCatalogEntry] = &[
-    ModelCatalogEntry {
-        id: "tiny.en",
-        name: "Tiny (English)",
-        filename: "ggml-tiny.en.bin",
-        size_bytes: 77_704_715,
-        description: "Fastest, least accurate (~75 MB)",
-    },
-    ModelCatalogEntry {
-        id: "base.en",
-        name: "Base (English)",
-        filename: "ggml-base.en.bin",
-        size_bytes: 147_964_211,
-        description: "Good balance of speed and accuracy (~150 MB)",
-    },
-    ModelCatalogEntry {
-        id: "small.en",
-        name: "Small (English)",
-        filename: "ggml-small.en.bin",
-        size_bytes: 487_601_967,
-        description: "More accurate, slower (~500 MB)",
-    },
-    ModelCatalogEntry {
-        id: "small",
-        name: "Small (Multilingual)",
-        filename: "ggml-small.bin",
-        size_bytes: 487_601_967,
-        description: "Multilingual support (~500 MB)",
-    },
-    ModelCatalogEntry {
-        id: "medium.en",
-        name: "Medium (English)",
-        filename: "ggml-medium.en.bin",
-        size_bytes: 1_533_774_781,
-        description: "High accuracy (~1.5 GB)",
-    },
-    ModelCatalogEntry {
-        id: "medium",
-        name: "Medium (Multilingual)",
-        filename: "ggml-medium.bin",
-        size_bytes: 1_533_774_781,
-        description: "High accuracy, multilingual (~1.5 GB)",
-    },
-];
-
 /// Download progress event payload.
 #[derive(Clone, Serialize)]
 pub struct DownloadProgressEvent {
+    pub job_id: u64,
     pub model_id: String,
     pub downloaded_bytes: u64,
     pub total_bytes: u64,
@@ -91,8 +39,10 @@ pub fn models_dir(app_data_dir: &Path) -> PathBuf {
 
 /// Get the file path for a model by ID.
 pub fn model_path(app_data_dir: &Path, model_id: &str) -> Option<PathBuf> {
-    let entry = MODEL_CATALOG.iter().find(|m| m.id == model_id)?;
-    Some(models_dir(app_data_dir).join(entry.filename))
+    match catalog::load(app_data_dir).find(ModelKind::Whisper, model_id) {
+        Some(entry) => Some(models_dir(app_data_dir).join(&entry.filename)),
+        None => user_models::find(app_data_dir, ModelKind::Whisper, model_id).map(|m| m.path),
+    }
 }
 
 /// Check if a model is downloaded.
@@ -104,21 +54,63 @@ pub fn is_downloaded(app_data_dir: &Path, model_id: &str) -> bool {
 
 /// List all models with download status.
 pub fn list_models(app_data_dir: &Path) -> Vec<ModelInfo> {
-    MODEL_CATALOG
+    let dir = models_dir(app_data_dir);
+    catalog::load(app_data_dir)
+        .entries(ModelKind::Whisper)
         .iter()
         .map(|entry| ModelInfo {
-            id: entry.id.to_string(),
-            name: entry.name.to_string(),
-            filename: entry.filename.to_string(),
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            filename: entry.filename.clone(),
             size_bytes: entry.size_bytes,
-            description: entry.description.to_string(),
-            downloaded: is_downloaded(app_data_dir, entry.id),
+            description: entry.description.clone(),
+            downloaded: dir.join(&entry.filename).exists(),
+            imported: false,
         })
+        .chain(
+            user_models::list(app_data_dir, ModelKind::Whisper)
+                .into_iter()
+                .map(imported_model_info),
+        )
         .collect()
 }
 
+fn imported_model_info(model: UserModel) -> ModelInfo {
+    ModelInfo {
+        filename: model
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        description: if model.copied {
+            "Imported model".to_string()
+        } else {
+            format!("Imported from {}", model.path.display())
+        },
+        downloaded: model.path.exists(),
+        imported: true,
+        id: model.id,
+        name: model.name,
+        size_bytes: model.size_bytes,
+    }
+}
+
+/// Whether a model handles languages other than English.
+pub fn is_multilingual(app_data_dir: &Path, model_id: &str) -> bool {
+    match user_models::find(app_data_dir, ModelKind::Whisper, model_id) {
+        Some(model) => model.multilingual.unwrap_or(true),
+        None => !language::is_english_only_model(model_id),
+    }
+}
+
 /// Delete a downloaded model.
+/// Imported models are unregistered; their file is only deleted if it was copied.
 pub fn delete_model(app_data_dir: &Path, model_id: &str) -> Result<(), String> {
+    if user_models::remove(app_data_dir, ModelKind::Whisper, model_id)? {
+        return Ok(());
+    }
+
     let path = model_path(app_data_dir, model_id)
         .ok_or_else(|| format!("Unknown model: {}", model_id))?;
 
@@ -129,20 +121,22 @@ pub fn delete_model(app_data_dir: &Path, model_id: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Download a model from HuggingFace with progress events.
+/// Download a model from the catalog (or `mirror`) with progress events.
 /// Runs synchronously — call from a background thread.
 pub fn download_model<R: Runtime>(
     app: &AppHandle<R>,
     app_data_dir: &Path,
-    model_id: &str,
+    job: &DownloadJob,
+    mirror: Option<&str>,
 ) -> Result<PathBuf, String> {
-    let entry = MODEL_CATALOG
-        .iter()
-        .find(|m| m.id == model_id)
+    let model_id = job.model_id.as_str();
+    let manifest = catalog::load(app_data_dir);
+    let entry = manifest
+        .find(ModelKind::Whisper, model_id)
         .ok_or_else(|| format!("Unknown model: {}", model_id))?;
 
     let dir = models_dir(app_data_dir);
-    let dest = dir.join(entry.filename);
+    let dest = dir.join(&entry.filename);
 
     // Skip if already downloaded
     if dest.exists() {
@@ -150,54 +144,19 @@ pub fn download_model<R: Runtime>(
         return Ok(dest);
     }
 
-    let url = format!("{}/{}", HF_BASE_URL, entry.filename);
+    let url = manifest.download_url(entry, mirror);
     log::info!("Downloading model {} from {}", model_id, url);
 
-    let client = reqwest::blocking::Client::builder()
-        .timeout(None) // No timeout for large downloads
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let response = client
-        .get(&url)
-        .send()
-        .map_err(|e| format!("Download request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Download failed: HTTP {}",
-            response.status()
-        ));
-    }
-
-    let total_bytes = response.content_length().unwrap_or(entry.size_bytes);
-
-    // Write to a temp file first, then rename (atomic)
-    let tmp_dest = dir.join(format!("{}.tmp", entry.filename));
-    let mut file = fs::File::create(&tmp_dest)
-        .map_err(|e| format!("Failed to create file: {}", e))?;
-
-    let mut downloaded: u64 = 0;
-    let mut buf = vec![0u8; 64 * 1024]; // 64KB chunks
-    let mut reader = response;
-
-    loop {
-        let bytes_read = reader
-            .read(&mut buf)
-            .map_err(|e| format!("Download read error: {}", e))?;
-
-        if bytes_read == 0 {
-            break;
-        }
-
-        file.write_all(&buf[..bytes_read])
-            .map_err(|e| format!("File write error: {}", e))?;
-
-        downloaded += bytes_read as u64;
-
-        // Emit progress every 64KB chunk
+    let download = Download {
+        url: &url,
+        dest: &dest,
+        sha256: entry.sha256.as_deref(),
+        size_hint: entry.size_bytes,
+        cancel: Some(job.cancel_flag()),
+    };
+    let result = download::download_file(&download, |downloaded_bytes, total_bytes| {
         let percent = if total_bytes > 0 {
-            (downloaded as f32 / total_bytes as f32) * 100.0
+            (downloaded_bytes as f32 / total_bytes as f32) * 100.0
         } else {
             0.0
         };
@@ -205,27 +164,24 @@ pub fn download_model<R: Runtime>(
         let _ = app.emit(
             "model://download-progress",
             DownloadProgressEvent {
+                job_id: job.id,
                 model_id: model_id.to_string(),
-                downloaded_bytes: downloaded,
+                downloaded_bytes,
                 total_bytes,
                 percent,
             },
         );
-    }
+    });
 
-    file.flush()
-        .map_err(|e| format!("File flush error: {}", e))?;
-    drop(file);
-
-    // Rename tmp to final
-    fs::rename(&tmp_dest, &dest)
-        .map_err(|e| format!("Failed to finalize download: {}", e))?;
+    if let Err(e) = result {
+        if e == DownloadError::Cancelled {
+            log::info!("Download of model {} cancelled", model_id);
+            let _ = app.emit("model://download-cancelled", job.info());
+        }
+        return Err(e.into());
+    }
 
-    log::info!(
-        "Model {} downloaded successfully ({} bytes)",
-        model_id,
-        downloaded
-    );
+    log::info!("Model {} downloaded successfully", model_id);
 
     Ok(dest)
 }
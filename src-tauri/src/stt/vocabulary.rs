@@ -0,0 +1,147 @@
+// Custom vocabulary for biasing Whisper.
+//
+// Users keep a list of product names, identifiers and people's names that
+// Whisper tends to misspell. The list is stored as `vocabulary.json` in the
+// app data directory and passed to whisper as an initial prompt, trimmed to
+// the model's prompt token budget.
+
+use crate::settings::config::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Vocabulary file name inside the app data directory.
+const VOCABULARY_FILENAME: &str = "vocabulary.json";
+
+/// Longest accepted term (characters). Longer entries are almost certainly
+/// pasted sentences, which make poor prompts.
+pub const MAX_TERM_CHARS: usize = 64;
+
+/// Text placed before the term list in the prompt.
+const PROMPT_PREFIX: &str = "Glossary:";
+
+/// On-disk format.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VocabularyFile {
+    terms: Vec<String>,
+}
+
+/// Path of the vocabulary file inside the app data directory.
+pub fn vocabulary_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(VOCABULARY_FILENAME)
+}
+
+/// Load the vocabulary (missing or unreadable file = empty list).
+pub fn load(app_data_dir: &Path) -> Vec<String> {
+    let path = vocabulary_path(app_data_dir);
+    if !path.exists() {
+        return Vec::new();
+    }
+
+    let parsed = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<VocabularyFile>(&json).map_err(|e| e.to_string()));
+
+    match parsed {
+        Ok(file) => file.terms,
+        Err(e) => {
+            log::warn!("Vocabulary file unreadable, starting empty: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Write the vocabulary atomically.
+pub fn save(app_data_dir: &Path, terms: &[String]) -> Result<(), String> {
+    let file = VocabularyFile {
+        terms: terms.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize vocabulary: {}", e))?;
+
+    write_atomic(&vocabulary_path(app_data_dir), json.as_bytes())
+}
+
+/// Clean up a user-entered term: trim and collapse inner whitespace.
+pub fn normalize_term(term: &str) -> Result<String, String> {
+    let term = term.split_whitespace().collect::<Vec<_>>().join(" ");
+    if term.is_empty() {
+        return Err("Vocabulary term is empty".to_string());
+    }
+    if term.chars().count() > MAX_TERM_CHARS {
+        return Err(format!(
+            "Vocabulary term is longer than {} characters",
+            MAX_TERM_CHARS
+        ));
+    }
+    Ok(term)
+}
+
+/// Add a term unless an equal one (ignoring case) is already present.
+/// Returns whether the list changed.
+pub fn add_term(terms: &mut Vec<String>, term: &str) -> Result<bool, String> {
+    let term = normalize_term(term)?;
+    if terms.iter().any(|t| same_term(t, &term)) {
+        return Ok(false);
+    }
+    terms.push(term);
+    Ok(true)
+}
+
+/// Remove a term (ignoring case). Returns whether it was present.
+pub fn remove_term(terms: &mut Vec<String>, term: &str) -> bool {
+    let term = term.trim();
+    let before = terms.len();
+    terms.retain(|t| !same_term(t, term));
+    terms.len() != before
+}
+
+/// Terms are compared case-insensitively.
+fn same_term(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// Split imported text into candidate terms: one per line or comma-separated.
+/// Lines starting with `#` are comments.
+pub fn parse_import(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+/// Build the initial prompt from as many terms as fit in `max_tokens`.
+/// Earlier terms win; `count_tokens` returns the token length of a prompt.
+/// Returns None when the list is empty or not even the first term fits.
+pub fn build_prompt(
+    terms: &[String],
+    max_tokens: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> Option<String> {
+    let mut prompt: Option<String> = None;
+
+    for (i, term) in terms.iter().enumerate() {
+        let candidate = match &prompt {
+            None => format!("{} {}", PROMPT_PREFIX, term),
+            Some(p) => format!("{}, {}", p, term),
+        };
+        if count_tokens(&candidate) > max_tokens {
+            log::warn!(
+                "Vocabulary exceeds the prompt budget of {} tokens, using {} of {} terms",
+                max_tokens,
+                i,
+                terms.len()
+            );
+            break;
+        }
+        prompt = Some(candidate);
+    }
+
+    prompt
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,103 @@
+use super::*;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("vocabulary")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// Rough stand-in for the whisper tokenizer: one token per word or punctuation mark.
+fn count_words(text: &str) -> usize {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .count()
+        + text.matches(',').count()
+}
+
+fn terms(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn load_missing_file_is_empty() {
+    let dir = unique_dir("missing");
+    assert!(load(&dir).is_empty());
+}
+
+#[test]
+fn save_and_load_roundtrip() {
+    let dir = unique_dir("roundtrip");
+    let list = terms(&["Vozr", "whisper-rs", "Tauri"]);
+    save(&dir, &list).unwrap();
+    assert_eq!(load(&dir), list);
+    assert!(!vocabulary_path(&dir).with_extension("json.tmp").exists());
+}
+
+#[test]
+fn load_corrupt_file_is_empty() {
+    let dir = unique_dir("corrupt");
+    fs::write(vocabulary_path(&dir), "not json").unwrap();
+    assert!(load(&dir).is_empty());
+}
+
+#[test]
+fn add_term_normalizes_and_dedupes() {
+    let mut list = Vec::new();
+    assert!(add_term(&mut list, "  serde   json ").unwrap());
+    assert!(!add_term(&mut list, "Serde Json").unwrap());
+    assert_eq!(list, terms(&["serde json"]));
+}
+
+#[test]
+fn add_term_rejects_empty_and_long() {
+    let mut list = Vec::new();
+    assert!(add_term(&mut list, "   ").is_err());
+    assert!(add_term(&mut list, &"x".repeat(MAX_TERM_CHARS + 1)).is_err());
+    assert!(add_term(&mut list, &"x".repeat(MAX_TERM_CHARS)).is_ok());
+}
+
+#[test]
+fn remove_term_ignores_case() {
+    let mut list = terms(&["Kubernetes", "Ólafur"]);
+    assert!(remove_term(&mut list, "kubernetes"));
+    assert!(remove_term(&mut list, " ÓLAFUR "));
+    assert!(!remove_term(&mut list, "missing"));
+    assert!(list.is_empty());
+}
+
+#[test]
+fn parse_import_handles_lines_commas_and_comments() {
+    let text = "# team names\nAnneke, Bartosz\n\n  tokio  \nreqwest,,serde\n";
+    assert_eq!(
+        parse_import(text),
+        vec!["Anneke", "Bartosz", "tokio", "reqwest", "serde"]
+    );
+}
+
+#[test]
+fn build_prompt_lists_terms() {
+    let prompt = build_prompt(&terms(&["Vozr", "cpal"]), 100, count_words).unwrap();
+    assert_eq!(prompt, "Glossary: Vozr, cpal");
+}
+
+#[test]
+fn build_prompt_empty_list_is_none() {
+    assert_eq!(build_prompt(&[], 100, count_words), None);
+}
+
+#[test]
+fn build_prompt_respects_token_budget() {
+    let list = terms(&["one", "two", "three", "four"]);
+    // "Glossary: one, two" = 3 words + 1 comma
+    let prompt = build_prompt(&list, 4, count_words).unwrap();
+    assert_eq!(prompt, "Glossary: one, two");
+    assert!(count_words(&prompt) <= 4);
+
+    // Not even the first term fits
+    assert_eq!(build_prompt(&list, 1, count_words), None);
+}
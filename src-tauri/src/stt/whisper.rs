@@ -1,6 +1,30 @@
+pub mod decoding;
+pub mod streaming;
+
+use super::language::{self, ENGLISH};
+use super::vocabulary;
+use decoding::DecodingOptions;
+use std::borrow::Cow;
 use std::sync::Mutex;
 use std::time::Instant;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use whisper_rs::{FullParams, WhisperContext, WhisperContextParameters, WhisperState};
+
+/// Whisper input sample rate expressed per millisecond (16kHz).
+pub const SAMPLES_PER_MS: usize = 16;
+
+/// whisper.cpp silently produces no output for inputs shorter than 1s,
+/// so shorter buffers are padded with trailing silence up to this length.
+pub const MIN_INPUT_SAMPLES: usize = 1_050 * SAMPLES_PER_MS;
+
+/// `audio`, padded with trailing silence to `MIN_INPUT_SAMPLES` if shorter.
+pub fn pad_to_min_input(audio: &[f32]) -> Cow<'_, [f32]> {
+    if audio.len() >= MIN_INPUT_SAMPLES {
+        return Cow::Borrowed(audio);
+    }
+    let mut padded = audio.to_vec();
+    padded.resize(MIN_INPUT_SAMPLES, 0.0);
+    Cow::Owned(padded)
+}
 
 /// Wrapper around WhisperContext for thread-safe access.
 /// WhisperContext is not Send/Sync, so we hold it behind a Mutex
@@ -56,9 +80,73 @@ impl WhisperEngine {
         self.current_model_id.lock().unwrap().clone()
     }
 
+    /// Whether the loaded model can transcribe languages other than English
+    /// (None if no model is loaded).
+    pub fn is_multilingual(&self) -> Option<bool> {
+        self.context
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|wrapper| wrapper.0.is_multilingual())
+    }
+
     /// Transcribe audio data (f32 PCM, 16kHz, mono).
-    /// Returns the transcribed text.
-    pub fn transcribe(&self, audio: &[f32], language: Option<&str>) -> Result<TranscriptionResult, String> {
+    /// Returns the transcribed text and its per-segment breakdown.
+    pub fn transcribe(
+        &self,
+        audio: &[f32],
+        options: &TranscribeOptions,
+    ) -> Result<TranscriptionResult, String> {
+        let start = Instant::now();
+        let Decoded {
+            segments,
+            language,
+            language_probability,
+            translated,
+        } = self.run_full(audio, options)?;
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let audio_duration_ms = (audio.len() / SAMPLES_PER_MS) as u64;
+
+        let text = segments_text(&segments);
+
+        log::info!(
+            "Transcription complete: {}ms {} audio in {}ms ({:.1}x realtime), {} segments, {} chars",
+            audio_duration_ms,
+            language,
+            duration_ms,
+            if duration_ms > 0 {
+                audio_duration_ms as f64 / duration_ms as f64
+            } else {
+                0.0
+            },
+            segments.len(),
+            text.len()
+        );
+
+        Ok(TranscriptionResult {
+            text,
+            segments,
+            language,
+            language_probability,
+            translated,
+            duration_ms,
+            audio_duration_ms,
+        })
+    }
+
+    /// Transcribe audio and return only its segments (used by streaming mode).
+    /// Inputs shorter than whisper's 1s minimum are padded with silence.
+    pub fn transcribe_segments(
+        &self,
+        audio: &[f32],
+        options: &TranscribeOptions,
+    ) -> Result<Decoded, String> {
+        self.run_full(&pad_to_min_input(audio), options)
+    }
+
+    /// Run whisper over the whole buffer and collect its segments.
+    fn run_full(&self, audio: &[f32], options: &TranscribeOptions) -> Result<Decoded, String> {
         let mut guard = self.context.lock().unwrap();
         let wrapper = guard
             .as_mut()
@@ -69,76 +157,227 @@ impl WhisperEngine {
             .create_state()
             .map_err(|e| format!("Failed to create Whisper state: {:?}", e))?;
 
+        let n_threads = options.decoding.n_threads();
+
+        // Resolve the language up front so detection runs once and its confidence is kept
+        let multilingual = ctx.is_multilingual();
+        let (language, language_probability) = match options.language.as_deref() {
+            Some(language) => (language.to_string(), None),
+            None if !multilingual => (ENGLISH.to_string(), None),
+            None => {
+                let (code, probability) = detect_language(&mut state, audio, n_threads)?;
+                log::info!("Detected language '{}' (p={:.2})", code, probability);
+                (code, Some(probability))
+            }
+        };
+        language::validate(&language, multilingual)?;
+
+        // English-only models have no translate task; their output is English anyway
+        let translate = options.translate && multilingual;
+        if options.translate && !multilingual {
+            log::warn!("Translation requested but the model is English-only, transcribing instead");
+        }
+
         // Configure transcription parameters
-        let mut params = FullParams::new(SamplingStrategy::BeamSearch {
-            beam_size: 5,
-            patience: -1.0,
-        });
+        let mut params = FullParams::new(options.decoding.sampling_strategy());
+        options.decoding.apply(&mut params);
 
-        params.set_language(language.or(Some("en")));
-        params.set_no_timestamps(true);
+        params.set_language(Some(&language));
+        params.set_translate(translate);
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
         params.set_suppress_blank(true);
 
-        // Use available CPU threads (cap at 4 to avoid hogging)
-        let n_threads = std::thread::available_parallelism()
-            .map(|n| n.get().min(4) as i32)
-            .unwrap_or(2);
-        params.set_n_threads(n_threads);
-
-        let start = Instant::now();
+        // Bias decoding towards the user's vocabulary, within whisper's prompt budget
+        let prompt_budget = ctx.n_text_ctx().max(0) as usize / 2;
+        let prompt = vocabulary::build_prompt(&options.vocabulary, prompt_budget, |text| {
+            ctx.tokenize(text, prompt_budget + 1)
+                .map(|tokens| tokens.len())
+                .unwrap_or(usize::MAX)
+        });
+        if let Some(prompt) = &prompt {
+            params.set_initial_prompt(prompt);
+        }
 
         state
             .full(params, audio)
             .map_err(|e| format!("Whisper transcription failed: {:?}", e))?;
 
-        let duration_ms = start.elapsed().as_millis() as u64;
-        let audio_duration_ms = (audio.len() as f64 / 16.0) as u64; // 16kHz = 16 samples/ms
+        // Token IDs at or above EOT are special (timestamps, language, task, ...)
+        let first_special_token = ctx.token_eot();
 
         // Collect segments
         let n_segments = state
             .full_n_segments()
             .map_err(|e| format!("Failed to get segment count: {:?}", e))?;
 
-        let mut text = String::new();
+        let mut segments = Vec::with_capacity(n_segments.max(0) as usize);
         for i in 0..n_segments {
-            let segment = state
+            let text = state
                 .full_get_segment_text(i)
                 .map_err(|e| format!("Failed to get segment {}: {:?}", i, e))?;
-            text.push_str(&segment);
-        }
 
-        let text = text.trim().to_string();
+            // whisper.cpp reports segment times in 10ms units
+            let t0 = state
+                .full_get_segment_t0(i)
+                .map_err(|e| format!("Failed to get segment {} start: {:?}", i, e))?;
+            let t1 = state
+                .full_get_segment_t1(i)
+                .map_err(|e| format!("Failed to get segment {} end: {:?}", i, e))?;
 
-        log::info!(
-            "Transcription complete: {}ms audio in {}ms ({:.1}x realtime), {} segments, {} chars",
-            audio_duration_ms,
-            duration_ms,
-            if duration_ms > 0 {
-                audio_duration_ms as f64 / duration_ms as f64
-            } else {
-                0.0
-            },
-            n_segments,
-            text.len()
-        );
+            let n_tokens = state
+                .full_n_tokens(i)
+                .map_err(|e| format!("Failed to get token count for segment {}: {:?}", i, e))?;
 
-        Ok(TranscriptionResult {
-            text,
-            duration_ms,
-            audio_duration_ms,
+            let mut tokens = Vec::with_capacity(n_tokens.max(0) as usize);
+            for j in 0..n_tokens {
+                let id = state
+                    .full_get_token_id(i, j)
+                    .map_err(|e| format!("Failed to get token {} of segment {}: {:?}", j, i, e))?;
+                let prob = state
+                    .full_get_token_prob(i, j)
+                    .map_err(|e| format!("Failed to get token {} of segment {}: {:?}", j, i, e))?;
+                tokens.push((id, prob));
+            }
+
+            let (avg_token_prob, first_token_uncertainty) = token_confidence(&tokens, first_special_token);
+
+            segments.push(Segment {
+                start_ms: t0.max(0) as u64 * 10,
+                end_ms: t1.max(0) as u64 * 10,
+                text,
+                avg_token_prob,
+                first_token_uncertainty,
+            });
+        }
+
+        Ok(Decoded {
+            segments,
+            language,
+            language_probability,
+            translated: translate,
         })
     }
 }
 
+/// Detect the spoken language from the first 30s of audio.
+/// Returns the language code and its probability.
+fn detect_language(
+    state: &mut WhisperState,
+    audio: &[f32],
+    n_threads: usize,
+) -> Result<(String, f32), String> {
+    state
+        .pcm_to_mel(audio, n_threads)
+        .map_err(|e| format!("Failed to compute spectrogram: {:?}", e))?;
+    let (lang_id, probs) = state
+        .lang_detect(0, n_threads)
+        .map_err(|e| format!("Language detection failed: {:?}", e))?;
+
+    let code = whisper_rs::get_lang_str(lang_id)
+        .ok_or_else(|| format!("Unknown language id detected: {}", lang_id))?;
+    let probability = probs.get(lang_id as usize).copied().unwrap_or(0.0);
+    Ok((code.to_string(), probability))
+}
+
+/// Concatenate segment texts into the final transcript.
+pub fn segments_text(segments: &[Segment]) -> String {
+    let text: String = segments.iter().map(|s| s.text.as_str()).collect();
+    text.trim().to_string()
+}
+
+/// Compute (average text-token probability, first-token uncertainty) from a
+/// segment's `(token_id, probability)` pairs. Special tokens
+/// (`id >= first_special_token`) are ignored.
+///
+/// The uncertainty is the probability mass the decoder did *not* give to the
+/// segment's first text token. It is not Whisper's no-speech probability,
+/// which the whisper.cpp build linked through whisper-rs 0.13 does not expose.
+pub fn token_confidence(tokens: &[(i32, f32)], first_special_token: i32) -> (f32, f32) {
+    let text_probs: Vec<f32> = tokens
+        .iter()
+        .filter(|(id, _)| *id < first_special_token)
+        .map(|(_, p)| p.clamp(0.0, 1.0))
+        .collect();
+
+    match text_probs.first() {
+        Some(&first) => {
+            let avg = text_probs.iter().sum::<f32>() / text_probs.len() as f32;
+            (avg, 1.0 - first)
+        }
+        None => (0.0, 1.0),
+    }
+}
+
+/// A decoded segment with timing and confidence information.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Segment {
+    /// Segment start, relative to the start of the input (ms).
+    pub start_ms: u64,
+    /// Segment end, relative to the start of the input (ms).
+    pub end_ms: u64,
+    /// Raw segment text as produced by whisper (usually has a leading space).
+    pub text: String,
+    /// Mean probability of the segment's text tokens [0.0, 1.0].
+    pub avg_token_prob: f32,
+    /// 1 - probability of the first text token [0.0, 1.0]. High for silence,
+    /// but also for speech that starts with an unlikely word such as a name,
+    /// so it is not a no-speech probability.
+    pub first_token_uncertainty: f32,
+}
+
+impl Segment {
+    /// Copy of this segment moved `offset_ms` later in time.
+    pub fn shifted(&self, offset_ms: u64) -> Self {
+        Self {
+            start_ms: self.start_ms + offset_ms,
+            end_ms: self.end_ms + offset_ms,
+            ..self.clone()
+        }
+    }
+}
+
+/// Per-call transcription settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscribeOptions {
+    /// Spoken language code (None = auto-detect).
+    pub language: Option<String>,
+    /// Translate the speech into English instead of transcribing it verbatim.
+    pub translate: bool,
+    /// Terms to bias recognition towards (fed to whisper as the initial prompt).
+    pub vocabulary: Vec<String>,
+    /// Sampling strategy, temperature, threads, ...
+    pub decoding: DecodingOptions,
+}
+
+/// Output of a single whisper pass.
+#[derive(Debug, Clone)]
+pub struct Decoded {
+    pub segments: Vec<Segment>,
+    /// Language the audio was spoken in.
+    pub language: String,
+    /// Detection confidence, if the language was auto-detected.
+    pub language_probability: Option<f32>,
+    /// Whether the text was translated into English.
+    pub translated: bool,
+}
+
 /// Result of a transcription.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TranscriptionResult {
     /// The transcribed text.
     pub text: String,
+    /// Per-segment timing and confidence, in transcript order.
+    pub segments: Vec<Segment>,
+    /// Language the audio was spoken in (e.g. "en").
+    pub language: String,
+    /// Detection confidence [0.0, 1.0] when the language was auto-detected;
+    /// None when it was set explicitly.
+    pub language_probability: Option<f32>,
+    /// Whether `text` is an English translation of the speech.
+    pub translated: bool,
     /// How long the transcription took (ms).
     pub duration_ms: u64,
     /// Duration of the input audio (ms).
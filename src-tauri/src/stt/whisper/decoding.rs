@@ -0,0 +1,128 @@
+// User-tunable Whisper decoding parameters.
+//
+// Beam search with a temperature fallback is whisper's most accurate setup;
+// greedy decoding is several times faster. Fast machines can raise the beam
+// size or thread count, slow ones can trade accuracy for latency.
+
+use serde::{Deserialize, Serialize};
+use whisper_rs::{FullParams, SamplingStrategy};
+
+/// Largest beam size accepted (whisper.cpp caps its decoders at 8 by default,
+/// larger values only cost time).
+pub const MAX_BEAM_SIZE: u32 = 8;
+
+/// Candidates sampled per temperature step in greedy mode when falling back
+/// to a non-zero temperature (whisper's default).
+const GREEDY_BEST_OF: i32 = 5;
+
+/// Thread cap used when the thread count is left on automatic.
+const AUTO_THREAD_CAP: usize = 4;
+
+/// Decoding strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SamplingMode {
+    Greedy,
+    BeamSearch,
+}
+
+/// Decoding parameters. Persisted as part of the user settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DecodingOptions {
+    /// Greedy or beam search.
+    pub sampling: SamplingMode,
+    /// Number of beams in beam search mode.
+    pub beam_size: u32,
+    /// Initial sampling temperature (0.0 = deterministic).
+    pub temperature: f32,
+    /// Temperature added on each retry when a segment fails whisper's
+    /// quality checks (0.0 disables the fallback).
+    pub temperature_increment: f32,
+    /// CPU threads (0 = automatic).
+    pub threads: u32,
+    /// Maximum segment length in characters (0 = no limit).
+    pub max_segment_length: u32,
+    /// Suppress non-speech tokens (music notes, sound annotations, ...).
+    pub suppress_non_speech: bool,
+}
+
+impl Default for DecodingOptions {
+    fn default() -> Self {
+        Self {
+            sampling: SamplingMode::BeamSearch,
+            beam_size: 5,
+            temperature: 0.0,
+            temperature_increment: 0.2,
+            threads: 0,
+            max_segment_length: 0,
+            suppress_non_speech: false,
+        }
+    }
+}
+
+impl DecodingOptions {
+    /// Reject values whisper would misbehave with.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_BEAM_SIZE).contains(&self.beam_size) {
+            return Err(format!("Beam size must be between 1 and {}", MAX_BEAM_SIZE));
+        }
+        if !(0.0..=1.0).contains(&self.temperature) {
+            return Err("Temperature must be between 0.0 and 1.0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.temperature_increment) {
+            return Err("Temperature increment must be between 0.0 and 1.0".to_string());
+        }
+        let max_threads = available_threads() as u32;
+        if self.threads > max_threads {
+            return Err(format!(
+                "Thread count must be at most {} on this machine",
+                max_threads
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of threads to run whisper with.
+    pub fn n_threads(&self) -> usize {
+        match self.threads {
+            0 => available_threads().min(AUTO_THREAD_CAP),
+            n => n as usize,
+        }
+    }
+
+    /// The whisper-rs sampling strategy for these options.
+    pub fn sampling_strategy(&self) -> SamplingStrategy {
+        match self.sampling {
+            SamplingMode::Greedy => SamplingStrategy::Greedy {
+                best_of: GREEDY_BEST_OF,
+            },
+            SamplingMode::BeamSearch => SamplingStrategy::BeamSearch {
+                beam_size: self.beam_size as i32,
+                patience: -1.0,
+            },
+        }
+    }
+
+    /// Apply everything except the sampling strategy to `params`.
+    pub fn apply(&self, params: &mut FullParams) {
+        params.set_n_threads(self.n_threads() as i32);
+        params.set_temperature(self.temperature);
+        params.set_temperature_inc(self.temperature_increment);
+        params.set_suppress_non_speech_tokens(self.suppress_non_speech);
+        if self.max_segment_length > 0 {
+            params.set_max_len(self.max_segment_length as i32);
+            // Without word splitting whisper cuts segments mid-word
+            params.set_split_on_word(true);
+        }
+    }
+}
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(2)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use super::*;
+
+#[test]
+fn defaults_match_previous_hardcoded_params() {
+    let options = DecodingOptions::default();
+    assert_eq!(options.sampling, SamplingMode::BeamSearch);
+    assert_eq!(options.beam_size, 5);
+    assert!(options.validate().is_ok());
+    assert!(matches!(
+        options.sampling_strategy(),
+        SamplingStrategy::BeamSearch {
+            beam_size: 5,
+            patience
+        } if patience == -1.0
+    ));
+}
+
+#[test]
+fn greedy_strategy() {
+    let options = DecodingOptions {
+        sampling: SamplingMode::Greedy,
+        ..Default::default()
+    };
+    assert!(matches!(
+        options.sampling_strategy(),
+        SamplingStrategy::Greedy { best_of: 5 }
+    ));
+}
+
+#[test]
+fn auto_threads_are_capped() {
+    let options = DecodingOptions::default();
+    let n = options.n_threads();
+    assert!(n >= 1);
+    assert!(n <= AUTO_THREAD_CAP);
+}
+
+#[test]
+fn explicit_threads_are_used() {
+    let options = DecodingOptions {
+        threads: 1,
+        ..Default::default()
+    };
+    assert_eq!(options.n_threads(), 1);
+}
+
+#[test]
+fn validate_rejects_out_of_range_values() {
+    let invalid = [
+        DecodingOptions {
+            beam_size: 0,
+            ..Default::default()
+        },
+        DecodingOptions {
+            beam_size: MAX_BEAM_SIZE + 1,
+            ..Default::default()
+        },
+        DecodingOptions {
+            temperature: 1.5,
+            ..Default::default()
+        },
+        DecodingOptions {
+            temperature_increment: -0.1,
+            ..Default::default()
+        },
+        DecodingOptions {
+            threads: 100_000,
+            ..Default::default()
+        },
+    ];
+    for options in &invalid {
+        assert!(options.validate().is_err(), "{:?}", options);
+    }
+}
+
+#[test]
+fn deserializes_partial_json_with_defaults() {
+    let options: DecodingOptions =
+        serde_json::from_str(r#"{ "sampling": "greedy", "maxSegmentLength": 40 }"#).unwrap();
+    assert_eq!(options.sampling, SamplingMode::Greedy);
+    assert_eq!(options.max_segment_length, 40);
+    assert_eq!(options.beam_size, 5);
+    assert_eq!(options.temperature_increment, 0.2);
+}
@@ -0,0 +1,310 @@
+// Streaming (incremental) transcription.
+//
+// While the user is speaking, a worker thread repeatedly transcribes the
+// not-yet-committed tail of the growing speech buffer and emits partial
+// hypotheses as `stt://partial` events. Words that two consecutive passes agree
+// on are considered stable; whole segments made of stable words are committed
+// and their audio is dropped from the next window. When recording stops only
+// the short uncommitted tail still has to be transcribed.
+//
+// With auto-detection the language found by the first pass is kept for the
+// rest of the recording so later windows cannot flip between languages.
+
+use super::{
+    segments_text, Decoded, Segment, TranscribeOptions, TranscriptionResult, WhisperEngine,
+    SAMPLES_PER_MS,
+};
+use crate::stt::language::ENGLISH;
+use crate::stt::SttState;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// How often the worker checks the speech buffer for new audio.
+const POLL_INTERVAL_MS: u64 = 100;
+
+/// Minimum amount of new audio before re-running a pass (ms).
+const STEP_MS: usize = 1_000;
+
+/// Don't bother transcribing windows shorter than this (ms).
+const MIN_WINDOW_MS: usize = 1_000;
+
+/// Once the uncommitted window grows past this, commit everything except the
+/// last segment even without agreement so the window stays within whisper's
+/// 30s context.
+const MAX_WINDOW_MS: usize = 25_000;
+
+/// Partial transcription event payload.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PartialTranscriptEvent {
+    /// Text that will not change any more.
+    pub stable: String,
+    /// Most recent guess for the rest of the utterance; may still change.
+    pub tentative: String,
+}
+
+// ---- Stitching ----
+
+/// Stitches overlapping window hypotheses into a stable transcript.
+pub struct StreamStitcher {
+    /// Committed segments, timed relative to the start of the speech buffer.
+    committed_segments: Vec<Segment>,
+    /// Number of speech-buffer samples covered by `committed_segments`.
+    committed_samples: usize,
+    /// Words of the previous hypothesis for the uncommitted window.
+    previous_words: Vec<String>,
+}
+
+impl StreamStitcher {
+    pub fn new() -> Self {
+        Self {
+            committed_segments: Vec::new(),
+            committed_samples: 0,
+            previous_words: Vec::new(),
+        }
+    }
+
+    /// Offset into the speech buffer where the next window should start.
+    pub fn committed_samples(&self) -> usize {
+        self.committed_samples
+    }
+
+    /// Feed the hypothesis for a window starting at `committed_samples()`.
+    /// `window_samples` is the length of the audio that was transcribed.
+    pub fn update(
+        &mut self,
+        segments: &[Segment],
+        window_samples: usize,
+    ) -> PartialTranscriptEvent {
+        let words: Vec<String> = segments.iter().flat_map(|s| split_words(&s.text)).collect();
+        let agreed = common_prefix_len(&self.previous_words, &words);
+        let force_commit = window_samples > MAX_WINDOW_MS * SAMPLES_PER_MS;
+
+        // Commit whole segments that lie entirely within the agreed prefix.
+        // The last segment is never committed: it may still be growing.
+        let window_offset_ms = (self.committed_samples / SAMPLES_PER_MS) as u64;
+        let mut consumed_words = 0;
+        let mut committed_until_ms = None;
+        for segment in segments.iter().take(segments.len().saturating_sub(1)) {
+            let n = split_words(&segment.text).len();
+            if consumed_words + n > agreed && !force_commit {
+                break;
+            }
+            consumed_words += n;
+            committed_until_ms = Some(segment.end_ms as usize);
+            self.committed_segments
+                .push(segment.shifted(window_offset_ms));
+        }
+
+        if let Some(end_ms) = committed_until_ms {
+            self.committed_samples += (end_ms * SAMPLES_PER_MS).min(window_samples);
+        }
+
+        let agreed = agreed.max(consumed_words);
+        self.previous_words = words[consumed_words..].to_vec();
+
+        PartialTranscriptEvent {
+            stable: join_text(
+                &segments_text(&self.committed_segments),
+                &words[consumed_words..agreed].join(" "),
+            ),
+            tentative: words[agreed..].join(" "),
+        }
+    }
+
+    /// Append the segments of the final tail (timed relative to
+    /// `committed_samples()`) to the committed ones.
+    pub fn finish(self, tail_segments: &[Segment]) -> Vec<Segment> {
+        let tail_offset_ms = (self.committed_samples / SAMPLES_PER_MS) as u64;
+        let mut segments = self.committed_segments;
+        segments.extend(tail_segments.iter().map(|s| s.shifted(tail_offset_ms)));
+        segments
+    }
+}
+
+/// Split segment text into whitespace-separated words.
+fn split_words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+/// Normalize a word for agreement checks (case and punctuation differ between passes).
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Number of leading words two hypotheses agree on.
+fn common_prefix_len(a: &[String], b: &[String]) -> usize {
+    a.iter()
+        .zip(b)
+        .take_while(|(x, y)| normalize_word(x) == normalize_word(y))
+        .count()
+}
+
+/// Join two text fragments with a single space, skipping empty ones.
+fn join_text(a: &str, b: &str) -> String {
+    match (a.is_empty(), b.is_empty()) {
+        (true, _) => b.to_string(),
+        (_, true) => a.to_string(),
+        _ => format!("{} {}", a, b),
+    }
+}
+
+// ---- Worker ----
+
+/// What the worker hands back when it stops.
+struct WorkerOutput {
+    stitcher: StreamStitcher,
+    /// Options used for the passes so far; the language is filled in once detected.
+    options: TranscribeOptions,
+    /// Detection confidence, if the language was auto-detected.
+    language_probability: Option<f32>,
+}
+
+/// A running streaming transcription session.
+pub struct StreamingSession {
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<WorkerOutput>,
+}
+
+impl StreamingSession {
+    /// Spawn the streaming worker over the given speech buffer.
+    pub fn start<R: Runtime>(
+        app: &AppHandle<R>,
+        speech_buffer: Arc<Mutex<Vec<f32>>>,
+        options: TranscribeOptions,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let app_handle = app.clone();
+
+        let handle = thread::spawn(move || {
+            streaming_worker(&app_handle, speech_buffer, options, stop_clone)
+        });
+
+        log::info!("Streaming transcription started");
+        Self { stop, handle }
+    }
+
+    /// Stop the worker and discard its results.
+    pub fn cancel(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.handle.join();
+    }
+
+    /// Stop the worker and produce the final result for `audio` (the full
+    /// speech buffer). Only the uncommitted tail is transcribed here, with the
+    /// options (and detected language) the streaming passes used.
+    pub fn finish(
+        self,
+        engine: &WhisperEngine,
+        audio: &[f32],
+    ) -> Result<TranscriptionResult, String> {
+        self.stop.store(true, Ordering::Relaxed);
+        let WorkerOutput {
+            stitcher,
+            options,
+            language_probability,
+        } = self
+            .handle
+            .join()
+            .map_err(|_| "Streaming worker panicked".to_string())?;
+
+        let start = Instant::now();
+        let tail = &audio[stitcher.committed_samples().min(audio.len())..];
+        let decoded = if tail.is_empty() {
+            Decoded {
+                segments: Vec::new(),
+                language: options.language.unwrap_or_else(|| ENGLISH.to_string()),
+                language_probability,
+                translated: options.translate,
+            }
+        } else {
+            let decoded = engine.transcribe_segments(tail, &options)?;
+            Decoded {
+                language_probability: language_probability.or(decoded.language_probability),
+                ..decoded
+            }
+        };
+
+        let segments = stitcher.finish(&decoded.segments);
+        let text = segments_text(&segments);
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        log::info!(
+            "Streaming transcription finalized: {}ms tail in {}ms, {} chars",
+            tail.len() / SAMPLES_PER_MS,
+            duration_ms,
+            text.len()
+        );
+
+        Ok(TranscriptionResult {
+            text,
+            segments,
+            language: decoded.language,
+            language_probability: decoded.language_probability,
+            translated: decoded.translated,
+            duration_ms,
+            audio_duration_ms: (audio.len() / SAMPLES_PER_MS) as u64,
+        })
+    }
+}
+
+/// The streaming worker: re-transcribes the uncommitted window as audio arrives.
+fn streaming_worker<R: Runtime>(
+    app: &AppHandle<R>,
+    speech_buffer: Arc<Mutex<Vec<f32>>>,
+    mut options: TranscribeOptions,
+    stop: Arc<AtomicBool>,
+) -> WorkerOutput {
+    let stt_state: tauri::State<'_, SttState> = app.state();
+    let mut stitcher = StreamStitcher::new();
+    let mut language_probability = None;
+    let mut last_len = 0;
+
+    while !stop.load(Ordering::Relaxed) {
+        thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+
+        let window: Vec<f32> = {
+            let buffer = speech_buffer.lock().unwrap();
+            if buffer.len() < last_len + STEP_MS * SAMPLES_PER_MS {
+                continue;
+            }
+            last_len = buffer.len();
+            buffer[stitcher.committed_samples().min(buffer.len())..].to_vec()
+        };
+
+        if window.len() < MIN_WINDOW_MS * SAMPLES_PER_MS {
+            continue;
+        }
+
+        match stt_state.engine.transcribe_segments(&window, &options) {
+            Ok(decoded) => {
+                if options.language.is_none() {
+                    options.language = Some(decoded.language);
+                    language_probability = decoded.language_probability;
+                }
+                let partial = stitcher.update(&decoded.segments, window.len());
+                let _ = app.emit("stt://partial", partial);
+            }
+            Err(e) => {
+                log::warn!("Streaming pass failed, waiting for final pass: {}", e);
+                break;
+            }
+        }
+    }
+
+    WorkerOutput {
+        stitcher,
+        options,
+        language_probability,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,168 @@
+use super::*;
+
+fn seg(start_ms: u64, end_ms: u64, text: &str) -> Segment {
+    Segment {
+        start_ms,
+        end_ms,
+        text: text.to_string(),
+        avg_token_prob: 0.9,
+        first_token_uncertainty: 0.05,
+    }
+}
+
+const ONE_SECOND: usize = 1_000 * SAMPLES_PER_MS;
+
+#[test]
+fn first_pass_is_entirely_tentative() {
+    let mut stitcher = StreamStitcher::new();
+    let partial = stitcher.update(&[seg(0, 1000, " Hello world")], ONE_SECOND);
+
+    assert_eq!(partial.stable, "");
+    assert_eq!(partial.tentative, "Hello world");
+    assert_eq!(stitcher.committed_samples(), 0);
+}
+
+#[test]
+fn agreed_prefix_becomes_stable() {
+    let mut stitcher = StreamStitcher::new();
+    stitcher.update(&[seg(0, 1000, " Hello world")], ONE_SECOND);
+    let partial = stitcher.update(&[seg(0, 2000, " Hello world, how are")], 2 * ONE_SECOND);
+
+    assert_eq!(partial.stable, "Hello world,");
+    assert_eq!(partial.tentative, "how are");
+}
+
+#[test]
+fn agreement_ignores_case_and_punctuation() {
+    let mut stitcher = StreamStitcher::new();
+    stitcher.update(&[seg(0, 1000, " hello World")], ONE_SECOND);
+    let partial = stitcher.update(&[seg(0, 2000, " Hello, world. Next")], 2 * ONE_SECOND);
+
+    assert_eq!(partial.stable, "Hello, world.");
+    assert_eq!(partial.tentative, "Next");
+}
+
+#[test]
+fn stable_segments_are_committed_and_advance_window() {
+    let mut stitcher = StreamStitcher::new();
+    let first = [seg(0, 1500, " First sentence."), seg(1500, 2000, " Sec")];
+    stitcher.update(&first, 2 * ONE_SECOND);
+
+    let second = [
+        seg(0, 1500, " First sentence."),
+        seg(1500, 3000, " Second one"),
+    ];
+    let partial = stitcher.update(&second, 3 * ONE_SECOND);
+
+    assert_eq!(stitcher.committed_samples(), 1500 * SAMPLES_PER_MS);
+    assert_eq!(partial.stable, "First sentence.");
+    assert_eq!(partial.tentative, "Second one");
+}
+
+#[test]
+fn last_segment_is_never_committed() {
+    let mut stitcher = StreamStitcher::new();
+    stitcher.update(&[seg(0, 1000, " Hello")], ONE_SECOND);
+    stitcher.update(&[seg(0, 1000, " Hello")], ONE_SECOND);
+
+    assert_eq!(stitcher.committed_samples(), 0);
+}
+
+#[test]
+fn disagreement_keeps_text_tentative() {
+    let mut stitcher = StreamStitcher::new();
+    stitcher.update(
+        &[seg(0, 1000, " Wreck a"), seg(1000, 2000, " nice")],
+        2 * ONE_SECOND,
+    );
+    let partial = stitcher.update(
+        &[seg(0, 1000, " Recognize"), seg(1000, 2000, " speech")],
+        2 * ONE_SECOND,
+    );
+
+    assert_eq!(partial.stable, "");
+    assert_eq!(partial.tentative, "Recognize speech");
+    assert_eq!(stitcher.committed_samples(), 0);
+}
+
+#[test]
+fn oversized_window_forces_commit() {
+    let mut stitcher = StreamStitcher::new();
+    let window = (MAX_WINDOW_MS + 1_000) * SAMPLES_PER_MS;
+    let segments = [
+        seg(0, 20_000, " Long rambling part"),
+        seg(20_000, 26_000, " tail"),
+    ];
+    stitcher.update(&segments, window);
+
+    assert_eq!(stitcher.committed_samples(), 20_000 * SAMPLES_PER_MS);
+}
+
+#[test]
+fn committed_samples_never_exceed_window() {
+    let mut stitcher = StreamStitcher::new();
+    let segments = [seg(0, 5_000, " Padded"), seg(5_000, 6_000, " end")];
+    stitcher.update(&segments, ONE_SECOND);
+    stitcher.update(&segments, ONE_SECOND);
+
+    assert!(stitcher.committed_samples() <= ONE_SECOND);
+}
+
+#[test]
+fn finish_appends_tail_to_committed_text() {
+    let mut stitcher = StreamStitcher::new();
+    let segments = [seg(0, 1000, " Committed part."), seg(1000, 2000, " Tail")];
+    stitcher.update(&segments, 2 * ONE_SECOND);
+    stitcher.update(&segments, 2 * ONE_SECOND);
+
+    let segments = stitcher.finish(&[seg(0, 1000, " Tail words here.")]);
+    assert_eq!(segments_text(&segments), "Committed part. Tail words here.");
+}
+
+#[test]
+fn finish_shifts_tail_segments_past_committed_audio() {
+    let mut stitcher = StreamStitcher::new();
+    let segments = [seg(0, 1200, " One."), seg(1200, 2000, " Two")];
+    stitcher.update(&segments, 2 * ONE_SECOND);
+    stitcher.update(&segments, 2 * ONE_SECOND);
+
+    let segments = stitcher.finish(&[seg(0, 800, " Two three.")]);
+    assert_eq!(segments.len(), 2);
+    assert_eq!((segments[0].start_ms, segments[0].end_ms), (0, 1200));
+    assert_eq!((segments[1].start_ms, segments[1].end_ms), (1200, 2000));
+}
+
+#[test]
+fn committed_segments_keep_buffer_relative_times() {
+    let mut stitcher = StreamStitcher::new();
+    let first = [seg(0, 1000, " A."), seg(1000, 2000, " B")];
+    stitcher.update(&first, 2 * ONE_SECOND);
+    stitcher.update(&first, 2 * ONE_SECOND);
+
+    // Next window starts 1s into the buffer
+    let second = [seg(0, 1000, " B."), seg(1000, 1500, " C")];
+    stitcher.update(&second, 2 * ONE_SECOND);
+    stitcher.update(&second, 2 * ONE_SECOND);
+
+    let segments = stitcher.finish(&[]);
+    assert_eq!(segments_text(&segments), "A. B.");
+    assert_eq!((segments[1].start_ms, segments[1].end_ms), (1000, 2000));
+}
+
+#[test]
+fn finish_without_passes_uses_tail_only() {
+    let stitcher = StreamStitcher::new();
+    let segments = stitcher.finish(&[seg(0, 500, " Just this.")]);
+    assert_eq!(segments_text(&segments), "Just this.");
+}
+
+#[test]
+fn partial_event_serializes() {
+    let event = PartialTranscriptEvent {
+        stable: "Hello".to_string(),
+        tentative: "wor".to_string(),
+    };
+    let json = serde_json::to_string(&event).unwrap();
+    assert!(json.contains("\"stable\":\"Hello\""));
+    assert!(json.contains("\"tentative\":\"wor\""));
+}
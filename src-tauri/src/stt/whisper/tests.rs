@@ -26,7 +26,16 @@ fn unload_when_not_loaded_is_safe() {
 fn transcribe_without_model_returns_error() {
     let engine = WhisperEngine::new();
     let audio = vec![0.0f32; 16000]; // 1 second of silence
-    let result = engine.transcribe(&audio, Some("en"));
+    let result = engine.transcribe(&audio, &TranscribeOptions::default());
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("No Whisper model loaded"));
+}
+
+#[test]
+fn transcribe_segments_without_model_returns_error() {
+    let engine = WhisperEngine::new();
+    let audio = vec![0.0f32; 8000]; // 0.5s — shorter than whisper's minimum
+    let result = engine.transcribe_segments(&audio, &TranscribeOptions::default());
     assert!(result.is_err());
     assert!(result.unwrap_err().contains("No Whisper model loaded"));
 }
@@ -67,15 +76,95 @@ fn transcription_result_fields() {
     // Verify the struct can be constructed and serialized
     let result = TranscriptionResult {
         text: "Hello world".to_string(),
+        segments: vec![Segment {
+            start_ms: 0,
+            end_ms: 1200,
+            text: " Hello world".to_string(),
+            avg_token_prob: 0.92,
+            first_token_uncertainty: 0.03,
+        }],
+        language: "de".to_string(),
+        language_probability: Some(0.87),
+        translated: false,
         duration_ms: 1500,
         audio_duration_ms: 5000,
     };
 
     assert_eq!(result.text, "Hello world");
+    assert_eq!(result.segments.len(), 1);
     assert_eq!(result.duration_ms, 1500);
     assert_eq!(result.audio_duration_ms, 5000);
 
     // Should be serializable
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("Hello world"));
+    assert!(json.contains("\"avg_token_prob\""));
+    assert!(json.contains("\"first_token_uncertainty\""));
+    assert!(json.contains("\"language\":\"de\""));
+    assert!(json.contains("\"language_probability\""));
+}
+
+// --------------- Segment confidence tests ---------------
+
+const EOT: i32 = 50256;
+
+#[test]
+fn token_confidence_averages_text_tokens() {
+    let tokens = [(100, 0.9), (200, 0.7), (300, 0.8)];
+    let (avg, uncertainty) = token_confidence(&tokens, EOT);
+    assert!((avg - 0.8).abs() < 1e-6);
+    assert!((uncertainty - 0.1).abs() < 1e-6);
+}
+
+#[test]
+fn token_confidence_ignores_special_tokens() {
+    // Timestamp tokens come before and after the text
+    let tokens = [(EOT + 100, 0.2), (100, 0.6), (200, 0.8), (EOT + 150, 0.1)];
+    let (avg, uncertainty) = token_confidence(&tokens, EOT);
+    assert!((avg - 0.7).abs() < 1e-6);
+    assert!((uncertainty - 0.4).abs() < 1e-6);
+}
+
+#[test]
+fn token_confidence_without_text_tokens_is_uncertain() {
+    let tokens = [(EOT, 0.9), (EOT + 1, 0.9)];
+    assert_eq!(token_confidence(&tokens, EOT), (0.0, 1.0));
+    assert_eq!(token_confidence(&[], EOT), (0.0, 1.0));
+}
+
+#[test]
+fn segments_text_joins_and_trims() {
+    let segments = vec![
+        Segment {
+            start_ms: 0,
+            end_ms: 1000,
+            text: " Hello".to_string(),
+            avg_token_prob: 0.9,
+            first_token_uncertainty: 0.1,
+        },
+        Segment {
+            start_ms: 1000,
+            end_ms: 2000,
+            text: " world. ".to_string(),
+            avg_token_prob: 0.9,
+            first_token_uncertainty: 0.1,
+        },
+    ];
+    assert_eq!(segments_text(&segments), "Hello world.");
+    assert_eq!(segments_text(&[]), "");
+}
+
+#[test]
+fn segment_shifted_moves_times_only() {
+    let segment = Segment {
+        start_ms: 100,
+        end_ms: 900,
+        text: " Hi".to_string(),
+        avg_token_prob: 0.5,
+        first_token_uncertainty: 0.2,
+    };
+    let shifted = segment.shifted(1000);
+    assert_eq!((shifted.start_ms, shifted.end_ms), (1100, 1900));
+    assert_eq!(shifted.text, segment.text);
+    assert_eq!(shifted.avg_token_prob, segment.avg_token_prob);
 }
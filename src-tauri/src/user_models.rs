@@ -0,0 +1,475 @@
+// User-imported model files.
+//
+// Besides the built-in catalogs, users can register their own model files:
+// GGML files for Whisper (e.g. a fine-tuned model) and GGUF files for the
+// local LLM. An imported file is either copied into the models directory or
+// referenced where it is. The entries are stored as `models/user_models.json`
+// in the app data directory and merged into the catalogs' model lists.
+
+use crate::download::ModelKind;
+use crate::settings::config::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// User catalog file name inside `<app data>/models`.
+const USER_CATALOG_FILENAME: &str = "user_models.json";
+
+/// Prefix of imported model IDs, keeping them apart from catalog IDs.
+pub const ID_PREFIX: &str = "user-";
+
+/// GGML file magic ("ggml" read as a little-endian u32).
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+
+/// GGUF file magic.
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+/// whisper.cpp treats models with at least this many tokens as multilingual.
+const MULTILINGUAL_N_VOCAB: i32 = 51_865;
+
+/// Longest GGUF key or string value read into memory (64KB). Anything longer
+/// means the file is not a GGUF model.
+const MAX_GGUF_STRING: u64 = 64 * 1024;
+
+/// Container format of a model file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelFormat {
+    Ggml,
+    Gguf,
+}
+
+/// An imported model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserModel {
+    pub id: String,
+    pub kind: ModelKind,
+    pub name: String,
+    pub path: PathBuf,
+    /// The file was copied into the models directory and is deleted with the entry.
+    pub copied: bool,
+    pub size_bytes: u64,
+    pub format: ModelFormat,
+    /// Whisper only: whether the model handles languages other than English.
+    pub multilingual: Option<bool>,
+    /// GGUF only: the `general.architecture` metadata value.
+    pub architecture: Option<String>,
+}
+
+/// On-disk format.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UserCatalogFile {
+    models: Vec<UserModel>,
+}
+
+/// What a model file's header says about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelHeader {
+    pub format: ModelFormat,
+    pub multilingual: Option<bool>,
+    pub architecture: Option<String>,
+}
+
+/// Path of the user catalog inside the app data directory.
+pub fn catalog_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("models").join(USER_CATALOG_FILENAME)
+}
+
+/// Load the user catalog (missing or unreadable file = no imported models).
+pub fn load(app_data_dir: &Path) -> Vec<UserModel> {
+    let path = catalog_path(app_data_dir);
+    if !path.exists() {
+        return Vec::new();
+    }
+
+    let parsed = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<UserCatalogFile>(&json).map_err(|e| e.to_string()));
+
+    match parsed {
+        Ok(file) => file.models,
+        Err(e) => {
+            log::warn!("User model catalog unreadable, ignoring it: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Write the user catalog atomically.
+pub fn save(app_data_dir: &Path, models: &[UserModel]) -> Result<(), String> {
+    let file = UserCatalogFile {
+        models: models.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize user models: {}", e))?;
+
+    write_atomic(&catalog_path(app_data_dir), json.as_bytes())
+}
+
+/// Imported models of one kind.
+pub fn list(app_data_dir: &Path, kind: ModelKind) -> Vec<UserModel> {
+    load(app_data_dir)
+        .into_iter()
+        .filter(|m| m.kind == kind)
+        .collect()
+}
+
+/// Look up an imported model.
+pub fn find(app_data_dir: &Path, kind: ModelKind, model_id: &str) -> Option<UserModel> {
+    load(app_data_dir)
+        .into_iter()
+        .find(|m| m.kind == kind && m.id == model_id)
+}
+
+/// Register a model file. With `copy` the file is copied into `models_dir`,
+/// otherwise the entry points at `source`. Runs synchronously — copying a
+/// large model takes a while.
+pub fn import(
+    app_data_dir: &Path,
+    models_dir: &Path,
+    kind: ModelKind,
+    source: &Path,
+    name: Option<&str>,
+    copy: bool,
+) -> Result<UserModel, String> {
+    let expected = match kind {
+        ModelKind::Whisper => ModelFormat::Ggml,
+        ModelKind::Llm => ModelFormat::Gguf,
+        ModelKind::Vad => return Err("VAD models cannot be imported".to_string()),
+    };
+
+    let metadata = fs::metadata(source).map_err(|e| format!("Failed to read model file: {}", e))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a file: {}", source.display()));
+    }
+
+    let header = read_header(source)?;
+    if header.format != expected {
+        return Err(match expected {
+            ModelFormat::Ggml => "Whisper models must be GGML files (ggml-*.bin)".to_string(),
+            ModelFormat::Gguf => "LLM models must be GGUF files (*.gguf)".to_string(),
+        });
+    }
+
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| format!("Not a file: {}", source.display()))?;
+    let name = name
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            Path::new(file_name)
+                .file_stem()
+                .unwrap_or(file_name)
+                .to_string_lossy()
+                .to_string()
+        });
+
+    let mut models = load(app_data_dir);
+    let id = unique_id(&name, &models);
+
+    let path = if copy {
+        // Named after the ID so copies never collide with catalog filenames
+        let extension = match header.format {
+            ModelFormat::Ggml => "bin",
+            ModelFormat::Gguf => "gguf",
+        };
+        let dest = models_dir.join(format!("{}.{}", id, extension));
+        if dest.exists() {
+            return Err(format!("Model file {} already exists", dest.display()));
+        }
+        fs::create_dir_all(models_dir).map_err(|e| format!("Failed to create dir: {}", e))?;
+
+        // Copy to a temp file first so an interrupted copy never looks complete
+        let tmp_dest = crate::download::partial_path(&dest);
+        fs::copy(source, &tmp_dest).map_err(|e| {
+            let _ = fs::remove_file(&tmp_dest);
+            format!("Failed to copy model file: {}", e)
+        })?;
+        fs::rename(&tmp_dest, &dest).map_err(|e| format!("Failed to finalize import: {}", e))?;
+        dest
+    } else {
+        source
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve model path: {}", e))?
+    };
+
+    let model = UserModel {
+        id,
+        kind,
+        name,
+        path,
+        copied: copy,
+        size_bytes: metadata.len(),
+        format: header.format,
+        multilingual: header.multilingual,
+        architecture: header.architecture,
+    };
+    models.push(model.clone());
+
+    if let Err(e) = save(app_data_dir, &models) {
+        if copy {
+            let _ = fs::remove_file(&model.path);
+        }
+        return Err(e);
+    }
+
+    log::info!(
+        "Imported {:?} model '{}' as {} ({})",
+        kind,
+        model.name,
+        model.id,
+        model.path.display()
+    );
+    Ok(model)
+}
+
+/// Unregister an imported model, deleting its file if it was copied.
+/// Returns false if no such model is registered.
+pub fn remove(app_data_dir: &Path, kind: ModelKind, model_id: &str) -> Result<bool, String> {
+    let mut models = load(app_data_dir);
+    let Some(index) = models
+        .iter()
+        .position(|m| m.kind == kind && m.id == model_id)
+    else {
+        return Ok(false);
+    };
+
+    let model = models.remove(index);
+    if model.copied && model.path.exists() {
+        fs::remove_file(&model.path).map_err(|e| format!("Failed to delete model: {}", e))?;
+    }
+    save(app_data_dir, &models)?;
+
+    log::info!(
+        "Removed imported model {} ({})",
+        model_id,
+        model.path.display()
+    );
+    Ok(true)
+}
+
+/// ID for a new model: the prefixed, slugified name, numbered if taken.
+fn unique_id(name: &str, existing: &[UserModel]) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() {
+        format!("{}model", ID_PREFIX)
+    } else {
+        format!("{}{}", ID_PREFIX, slug)
+    };
+
+    let taken = |id: &str| existing.iter().any(|m| m.id == id);
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|id| !taken(id))
+        .unwrap()
+}
+
+// ---- Header validation ----
+
+/// Identify a model file from its header.
+pub fn read_header(path: &Path) -> Result<ModelHeader, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open model file: {}", e))?;
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| "File is too small to be a model".to_string())?;
+
+    if &magic == GGUF_MAGIC {
+        read_gguf_header(&mut reader)
+    } else if u32::from_le_bytes(magic) == GGML_MAGIC {
+        read_ggml_header(&mut reader)
+    } else {
+        Err("Not a GGML or GGUF model file".to_string())
+    }
+}
+
+/// whisper.cpp GGML hyperparameters following the magic.
+fn read_ggml_header(reader: &mut impl Read) -> Result<ModelHeader, String> {
+    let invalid = || "Invalid GGML header".to_string();
+
+    // n_vocab, n_audio_ctx, n_audio_state, n_audio_head, n_audio_layer,
+    // n_text_ctx, n_text_state, n_text_head, n_text_layer, n_mels, ftype
+    let mut hparams = [0i32; 11];
+    for value in hparams.iter_mut() {
+        *value = read_i32(reader).map_err(|_| invalid())?;
+    }
+    let n_vocab = hparams[0];
+    let n_mels = hparams[9];
+
+    // All model dimensions are positive; whisper uses 80 or 128 mel bins
+    if hparams[..9].iter().any(|&v| v <= 0) || !matches!(n_mels, 80 | 128) {
+        return Err("GGML file is not a Whisper model".to_string());
+    }
+
+    Ok(ModelHeader {
+        format: ModelFormat::Ggml,
+        multilingual: Some(n_vocab >= MULTILINGUAL_N_VOCAB),
+        architecture: None,
+    })
+}
+
+/// GGUF version, counts and metadata, looking for `general.architecture`.
+fn read_gguf_header(reader: &mut (impl Read + Seek)) -> Result<ModelHeader, String> {
+    let invalid = |e: std::io::Error| format!("Invalid GGUF header: {}", e);
+
+    let version = read_u32(reader).map_err(invalid)?;
+    if !matches!(version, 2 | 3) {
+        return Err(format!("Unsupported GGUF version {}", version));
+    }
+    let _tensor_count = read_u64(reader).map_err(invalid)?;
+    let kv_count = read_u64(reader).map_err(invalid)?;
+
+    let mut architecture = None;
+    for _ in 0..kv_count {
+        let key = read_gguf_string(reader).map_err(invalid)?;
+        let value_type = read_u32(reader).map_err(invalid)?;
+        if key == "general.architecture" && value_type == GGUF_TYPE_STRING {
+            architecture = Some(read_gguf_string(reader).map_err(invalid)?);
+            break;
+        }
+        skip_gguf_value(reader, value_type).map_err(invalid)?;
+    }
+
+    let architecture = architecture
+        .filter(|a| !a.is_empty())
+        .ok_or_else(|| "GGUF file has no model architecture".to_string())?;
+
+    Ok(ModelHeader {
+        format: ModelFormat::Gguf,
+        multilingual: None,
+        architecture: Some(architecture),
+    })
+}
+
+const GGUF_TYPE_STRING: u32 = 8;
+const GGUF_TYPE_ARRAY: u32 = 9;
+
+/// Byte size of a fixed-size GGUF value type.
+fn gguf_scalar_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        0 | 1 | 7 => Some(1), // u8, i8, bool
+        2 | 3 => Some(2),     // u16, i16
+        4..=6 => Some(4),     // u32, i32, f32
+        10..=12 => Some(8),   // u64, i64, f64
+        _ => None,
+    }
+}
+
+fn skip_gguf_value(reader: &mut (impl Read + Seek), value_type: u32) -> std::io::Result<()> {
+    if let Some(size) = gguf_scalar_size(value_type) {
+        return skip(reader, size);
+    }
+    match value_type {
+        GGUF_TYPE_STRING => {
+            let len = read_u64(reader)?;
+            skip(reader, len)
+        }
+        GGUF_TYPE_ARRAY => {
+            let item_type = read_u32(reader)?;
+            let count = read_u64(reader)?;
+            match gguf_scalar_size(item_type) {
+                Some(size) => skip(reader, size.saturating_mul(count)),
+                None => (0..count).try_for_each(|_| skip_gguf_value(reader, item_type)),
+            }
+        }
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unknown value type {}", value_type),
+        )),
+    }
+}
+
+fn read_gguf_string(reader: &mut impl Read) -> std::io::Result<String> {
+    let len = read_u64(reader)?;
+    if len > MAX_GGUF_STRING {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "string too long",
+        ));
+    }
+    let mut buf = vec![0u8; len as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+fn skip(reader: &mut impl Seek, len: u64) -> std::io::Result<()> {
+    let len = i64::try_from(len)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "length out of range"))?;
+    reader.seek(SeekFrom::Current(len)).map(|_| ())
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_i32(reader: &mut impl Read) -> std::io::Result<i32> {
+    read_u32(reader).map(|v| v as i32)
+}
+
+fn read_u64(reader: &mut impl Read) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+// --------------- Tauri commands ---------------
+
+/// Register a local GGML (Whisper) or GGUF (LLM) file. Imported models show
+/// up in `list_whisper_models` / `list_llm_models` and load and delete
+/// through the existing model commands.
+#[tauri::command(async)]
+pub fn import_model(
+    app: AppHandle,
+    kind: ModelKind,
+    path: String,
+    name: Option<String>,
+    copy: bool,
+) -> Result<UserModel, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data directory unavailable: {}", e))?;
+    let models_dir = match kind {
+        ModelKind::Whisper => crate::stt::models::models_dir(&data_dir),
+        ModelKind::Llm => crate::cleanup::local_llm::models_dir(&data_dir),
+        ModelKind::Vad => crate::audio::models::models_dir(&data_dir),
+    };
+
+    import(
+        &data_dir,
+        &models_dir,
+        kind,
+        Path::new(&path),
+        name.as_deref(),
+        copy,
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,224 @@
+use super::*;
+
+/// Create a unique temp dir per test to avoid parallel test interference.
+fn unique_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("vozr_test")
+        .join("user_models")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// A minimal whisper.cpp GGML file with the given vocabulary size.
+fn ggml_bytes(n_vocab: i32) -> Vec<u8> {
+    let mut bytes = GGML_MAGIC.to_le_bytes().to_vec();
+    // n_vocab, n_audio_ctx, n_audio_state, n_audio_head, n_audio_layer,
+    // n_text_ctx, n_text_state, n_text_head, n_text_layer, n_mels, ftype
+    for v in [n_vocab, 1500, 384, 6, 4, 448, 384, 6, 4, 80, 1] {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    bytes.extend_from_slice(&[0u8; 32]);
+    bytes
+}
+
+fn gguf_string(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend_from_slice(&(s.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+/// A minimal GGUF v3 file. A few unrelated keys (including arrays) come
+/// before `general.architecture` so the parser has to skip them.
+fn gguf_bytes(architecture: Option<&str>) -> Vec<u8> {
+    let mut bytes = GGUF_MAGIC.to_vec();
+    bytes.extend_from_slice(&3u32.to_le_bytes());
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // tensor count
+    let kv_count: u64 = if architecture.is_some() { 4 } else { 3 };
+    bytes.extend_from_slice(&kv_count.to_le_bytes());
+
+    gguf_string(&mut bytes, "general.name");
+    bytes.extend_from_slice(&GGUF_TYPE_STRING.to_le_bytes());
+    gguf_string(&mut bytes, "Tiny Test");
+
+    gguf_string(&mut bytes, "general.file_type");
+    bytes.extend_from_slice(&4u32.to_le_bytes()); // u32
+    bytes.extend_from_slice(&15u32.to_le_bytes());
+
+    gguf_string(&mut bytes, "tokenizer.ggml.tokens");
+    bytes.extend_from_slice(&GGUF_TYPE_ARRAY.to_le_bytes());
+    bytes.extend_from_slice(&GGUF_TYPE_STRING.to_le_bytes());
+    bytes.extend_from_slice(&3u64.to_le_bytes());
+    for token in ["<s>", "</s>", "hello"] {
+        gguf_string(&mut bytes, token);
+    }
+
+    if let Some(architecture) = architecture {
+        gguf_string(&mut bytes, "general.architecture");
+        bytes.extend_from_slice(&GGUF_TYPE_STRING.to_le_bytes());
+        gguf_string(&mut bytes, architecture);
+    }
+    bytes
+}
+
+fn write_file(dir: &Path, name: &str, bytes: &[u8]) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn reads_ggml_whisper_header() {
+    let dir = unique_dir("ggml_header");
+    let english = write_file(&dir, "en.bin", &ggml_bytes(51_864));
+    let multilingual = write_file(&dir, "multi.bin", &ggml_bytes(51_865));
+
+    let header = read_header(&english).unwrap();
+    assert_eq!(header.format, ModelFormat::Ggml);
+    assert_eq!(header.multilingual, Some(false));
+    assert_eq!(read_header(&multilingual).unwrap().multilingual, Some(true));
+}
+
+#[test]
+fn reads_gguf_architecture() {
+    let dir = unique_dir("gguf_header");
+    let path = write_file(&dir, "model.gguf", &gguf_bytes(Some("llama")));
+
+    let header = read_header(&path).unwrap();
+    assert_eq!(header.format, ModelFormat::Gguf);
+    assert_eq!(header.architecture.as_deref(), Some("llama"));
+}
+
+#[test]
+fn rejects_invalid_files() {
+    let dir = unique_dir("invalid");
+    let cases = [
+        write_file(&dir, "empty.bin", b""),
+        write_file(&dir, "text.bin", b"not a model at all"),
+        write_file(&dir, "truncated.bin", &ggml_bytes(51_864)[..20]),
+        write_file(&dir, "no_arch.gguf", &gguf_bytes(None)),
+    ];
+    for path in &cases {
+        assert!(read_header(path).is_err(), "{}", path.display());
+    }
+
+    // A GGML file whose hyperparameters are not Whisper's
+    let mut not_whisper = ggml_bytes(51_864);
+    not_whisper[40..44].copy_from_slice(&7i32.to_le_bytes()); // n_mels
+    let path = write_file(&dir, "not_whisper.bin", &not_whisper);
+    assert!(read_header(&path).is_err());
+}
+
+#[test]
+fn rejects_format_that_does_not_match_kind() {
+    let dir = unique_dir("kind_mismatch");
+    let data_dir = dir.join("data");
+    let gguf = write_file(&dir, "model.gguf", &gguf_bytes(Some("llama")));
+    let ggml = write_file(&dir, "model.bin", &ggml_bytes(51_865));
+
+    assert!(import(&data_dir, &dir, ModelKind::Whisper, &gguf, None, false).is_err());
+    assert!(import(&data_dir, &dir, ModelKind::Llm, &ggml, None, false).is_err());
+    assert!(import(&data_dir, &dir, ModelKind::Vad, &ggml, None, false).is_err());
+    assert!(load(&data_dir).is_empty());
+}
+
+#[test]
+fn import_by_reference_keeps_file_in_place() {
+    let dir = unique_dir("reference");
+    let data_dir = dir.join("data");
+    let models_dir = dir.join("models");
+    let source = write_file(&dir, "ggml-finetuned.bin", &ggml_bytes(51_865));
+
+    let model = import(
+        &data_dir,
+        &models_dir,
+        ModelKind::Whisper,
+        &source,
+        None,
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(model.id, "user-ggml-finetuned");
+    assert_eq!(model.name, "ggml-finetuned");
+    assert_eq!(model.path, source.canonicalize().unwrap());
+    assert!(!model.copied);
+    assert_eq!(model.multilingual, Some(true));
+    assert_eq!(load(&data_dir), vec![model.clone()]);
+
+    // Removing a referenced model leaves the user's file alone
+    assert!(remove(&data_dir, ModelKind::Whisper, &model.id).unwrap());
+    assert!(source.exists());
+    assert!(load(&data_dir).is_empty());
+}
+
+#[test]
+fn import_by_copy_owns_the_file() {
+    let dir = unique_dir("copy");
+    let data_dir = dir.join("data");
+    let models_dir = dir.join("models");
+    let source = write_file(&dir, "mistral.gguf", &gguf_bytes(Some("llama")));
+
+    let model = import(
+        &data_dir,
+        &models_dir,
+        ModelKind::Llm,
+        &source,
+        Some("  My Mistral 7B  "),
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(model.id, "user-my-mistral-7b");
+    assert_eq!(model.name, "My Mistral 7B");
+    assert_eq!(model.path, models_dir.join("user-my-mistral-7b.gguf"));
+    assert_eq!(fs::read(&model.path).unwrap(), fs::read(&source).unwrap());
+    assert_eq!(model.architecture.as_deref(), Some("llama"));
+
+    assert!(remove(&data_dir, ModelKind::Llm, &model.id).unwrap());
+    assert!(!model.path.exists());
+    assert!(source.exists());
+}
+
+#[test]
+fn ids_are_unique_per_name() {
+    let dir = unique_dir("unique_ids");
+    let data_dir = dir.join("data");
+    let source = write_file(&dir, "model.bin", &ggml_bytes(51_864));
+
+    let ids: Vec<String> = (0..3)
+        .map(|_| {
+            import(&data_dir, &dir, ModelKind::Whisper, &source, None, false)
+                .unwrap()
+                .id
+        })
+        .collect();
+    assert_eq!(ids, vec!["user-model", "user-model-2", "user-model-3"]);
+}
+
+#[test]
+fn list_and_find_filter_by_kind() {
+    let dir = unique_dir("by_kind");
+    let data_dir = dir.join("data");
+    let ggml = write_file(&dir, "a.bin", &ggml_bytes(51_864));
+    let gguf = write_file(&dir, "b.gguf", &gguf_bytes(Some("phi3")));
+    let whisper = import(&data_dir, &dir, ModelKind::Whisper, &ggml, None, false).unwrap();
+    let llm = import(&data_dir, &dir, ModelKind::Llm, &gguf, None, false).unwrap();
+
+    assert_eq!(list(&data_dir, ModelKind::Whisper), vec![whisper.clone()]);
+    assert_eq!(list(&data_dir, ModelKind::Llm), vec![llm.clone()]);
+    assert_eq!(
+        find(&data_dir, ModelKind::Whisper, &whisper.id),
+        Some(whisper)
+    );
+    assert_eq!(find(&data_dir, ModelKind::Whisper, &llm.id), None);
+    assert!(!remove(&data_dir, ModelKind::Whisper, &llm.id).unwrap());
+}
+
+#[test]
+fn corrupt_catalog_loads_empty() {
+    let dir = unique_dir("corrupt");
+    fs::create_dir_all(dir.join("models")).unwrap();
+    fs::write(catalog_path(&dir), "{ not json").unwrap();
+    assert!(load(&dir).is_empty());
+}
@@ -3,7 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { Mic, MicOff, Check, Loader2 } from "lucide-react";
 import { Button } from "@/components/ui/button";
-import type { AudioLevel } from "@/types";
+import type { AudioLevel, VadModelStatus } from "@/types";
 
 interface MicrophoneStepProps {
   onNext: () => void;
@@ -14,6 +14,27 @@ type MicStatus = "idle" | "requesting" | "granted" | "denied";
 export default function MicrophoneStep({ onNext }: MicrophoneStepProps) {
   const [status, setStatus] = useState<MicStatus>("idle");
   const [audioLevel, setAudioLevel] = useState(0);
+  const [vad, setVad] = useState<VadModelStatus | null>(null);
+  const [vadDownloading, setVadDownloading] = useState(false);
+  const [vadError, setVadError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<VadModelStatus>("get_vad_model_status")
+      .then(setVad)
+      .catch(() => setVad(null));
+  }, []);
+
+  const downloadVad = async () => {
+    setVadDownloading(true);
+    setVadError(null);
+    try {
+      setVad(await invoke<VadModelStatus>("download_vad_model"));
+    } catch (e) {
+      setVadError(String(e));
+    } finally {
+      setVadDownloading(false);
+    }
+  };
 
   // Listen for audio level to confirm mic access works
   useEffect(() => {
@@ -90,6 +111,38 @@ export default function MicrophoneStep({ onNext }: MicrophoneStepProps) {
             />
           </div>
 
+          {vad && (
+            <div className="mt-4 flex items-center gap-2 text-[length:var(--font-size-caption)] text-text-secondary">
+              {vad.active ? (
+                <>
+                  <Check size={14} className="text-accent-success" />
+                  <span>Voice detection active</span>
+                </>
+              ) : (
+                <>
+                  <span>Using basic voice detection</span>
+                  <Button
+                    size="sm"
+                    variant="outline"
+                    disabled={vadDownloading}
+                    onClick={downloadVad}
+                  >
+                    {vadDownloading ? (
+                      <Loader2 size={14} className="animate-spin" />
+                    ) : (
+                      `Download (${Math.max(1, Math.round(vad.size_bytes / 1_000_000))} MB)`
+                    )}
+                  </Button>
+                </>
+              )}
+            </div>
+          )}
+          {vadError && (
+            <p className="mt-2 max-w-[360px] text-[length:var(--font-size-caption)] text-accent-error">
+              {vadError}
+            </p>
+          )}
+
           <p className="mt-6 max-w-[340px] text-[length:var(--font-size-caption)] text-text-tertiary">
             Your audio is processed on this device. We never store recordings or
             send audio to the cloud.
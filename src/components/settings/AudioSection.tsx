@@ -1,4 +1,5 @@
 import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { Label } from "@/components/ui/label";
 import { Button } from "@/components/ui/button";
@@ -40,6 +41,14 @@ export default function AudioSection({
     };
   }, []);
 
+  // Watch for microphones being plugged in and out while this is shown
+  useEffect(() => {
+    invoke("set_device_watch", { active: true });
+    return () => {
+      invoke("set_device_watch", { active: false });
+    };
+  }, []);
+
   // Decay audio level when no events come in
   useEffect(() => {
     const interval = setInterval(() => {
@@ -78,7 +87,7 @@ export default function AudioSection({
                 {audioDevices.map((d) => (
                   <SelectItem key={d.id} value={d.id}>
                     {d.name}
-                    {d.isDefault ? " (Default)" : ""}
+                    {d.is_default ? " (Default)" : ""}
                   </SelectItem>
                 ))}
               </SelectContent>
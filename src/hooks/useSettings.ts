@@ -1,13 +1,28 @@
 import { useCallback, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import type { ActivationMode, CleanupTier, CloudProvider } from "@/types";
 
 // ---- Backend return types ----
 
 export interface AudioDeviceInfo {
+  /** Stable ID: "<host>:<name>", "#<n>" appended for duplicate names */
   id: string;
   name: string;
-  isDefault: boolean;
+  host: string;
+  is_default: boolean;
+  capabilities: {
+    sample_rates: number[];
+    min_sample_rate: number | null;
+    max_sample_rate: number | null;
+    channels: number[];
+    sample_formats: string[];
+  };
+  default_format: {
+    sample_rate: number;
+    channels: number;
+    sample_format: string;
+  } | null;
 }
 
 export interface WhisperModelInfo {
@@ -179,8 +194,9 @@ export function useSettings(): SettingsState & SettingsActions {
   }, []);
 
   const setAudioDevice = useCallback(async (deviceId: string | null) => {
-    await invoke("set_audio_device", { deviceId });
-    setState((prev) => ({ ...prev, audioDevice: deviceId }));
+    // A device that is gone resolves to another one or the system default
+    const selected = await invoke<string | null>("set_audio_device", { deviceId });
+    setState((prev) => ({ ...prev, audioDevice: selected }));
   }, []);
 
   const saveApiKey = useCallback(async (provider: CloudProvider, key: string) => {
@@ -233,6 +249,21 @@ export function useSettings(): SettingsState & SettingsActions {
     setState((prev) => ({ ...prev, audioDevices: devices }));
   }, []);
 
+  // Refresh the device list when microphones are plugged in or out
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    listen("audio://devices-changed", () => {
+      refreshAudioDevices().catch((e) =>
+        console.error("Failed to refresh audio devices:", e),
+      );
+    }).then((fn) => {
+      unlisten = fn;
+    });
+    return () => {
+      unlisten?.();
+    };
+  }, [refreshAudioDevices]);
+
   const refreshWhisperModels = useCallback(async () => {
     const models = await invoke<WhisperModelInfo[]>("list_whisper_models");
     const current = await invoke<string | null>("get_current_whisper_model");
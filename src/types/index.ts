@@ -62,7 +62,57 @@ export interface Settings {
 export interface AudioDevice {
   id: string;
   name: string;
-  isDefault: boolean;
+  host: string;
+  is_default: boolean;
+}
+
+/** Silero VAD model status (shown during onboarding) */
+export interface VadModelStatus {
+  model_id: string;
+  name: string;
+  size_bytes: number;
+  downloaded: boolean;
+  /** Recordings trim silence with VAD */
+  active: boolean;
+}
+
+/** Microphone test measurements (`audio://mic-test` and `audio://mic-test-ended` events, `stop_mic_test`) */
+export interface MicTestReport {
+  sample_rate: number;
+  channels: number;
+  /** Device rate derived from the audio received, null in the first second */
+  measured_sample_rate: number | null;
+  duration_ms: number;
+  /** Levels in dBFS */
+  level_db: number;
+  peak: number;
+  peak_db: number;
+  clipped_samples: number;
+  clipping: boolean;
+  noise_floor_db: number | null;
+  speech_level_db: number | null;
+  snr_db: number | null;
+  recommended_start_threshold: number | null;
+  recommended_stop_threshold: number | null;
+}
+
+/** Recording archive settings (opt-in, off by default) */
+export interface ArchiveConfig {
+  enabled: boolean;
+  maxRecordings: number;
+  maxAgeDays: number;
+  maxSizeMb: number;
+}
+
+/** Archived recording (`list_recordings`) */
+export interface RecordingInfo {
+  id: string;
+  /** WAV file path, for playback */
+  path: string;
+  /** Unix milliseconds */
+  created_ms: number;
+  duration_ms: number;
+  size_bytes: number;
 }
 
 /** Whisper model info for the settings UI */